@@ -1,7 +1,10 @@
 //! Read-eval-print loop.
-use std::io::Write;
+use std::fmt::Display;
+use std::io::{IsTerminal, Write};
 use vuur_compile::{disassemble, Chunk};
+use vuur_lexer::span::{SourceFile, Span};
 use vuur_lexer::Lexer;
+use vuur_parse::error::{format_error, ColorChoice};
 use vuur_vm::VM;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -13,9 +16,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 fn run_repl() -> std::io::Result<()> {
     let mut input = String::new();
+    let mut line_no = 0;
 
     loop {
         input.clear();
+        line_no += 1;
 
         print!(">>> ");
         std::io::stdout().flush()?; // ensure prompt shows
@@ -23,56 +28,78 @@ fn run_repl() -> std::io::Result<()> {
 
         // stdin reads up to and including newline or EOF
         let trimmed = input.trim();
+        let source = SourceFile::new(format!("<repl:{line_no}>"), trimmed);
 
         for token in Lexer::from_source(trimmed).into_iter() {
             println!("{:?} '{}'", token.kind, token.fragment(trimmed));
         }
 
-        match vuur_parse::parse_str(trimmed) {
-            Ok(module) => {
-                println!("-----------");
-                println!("Syntax Tree");
-                println!("");
-                println!("{:#?}", module);
-
-                match vuur_compile::compile(&module) {
-                    Ok(chunk) => {
-                        println!("Saving chunk");
-                        save_chunk(&chunk);
-
-                        let mut buf = String::new();
-                        match disassemble(&mut buf, &chunk) {
-                            Ok(_) => {
-                                println!("-----------");
-                                println!("Disassembly");
-                                println!("");
-                                println!("{}", buf);
-                            }
-                            Err(err) => eprintln!("{}", err),
-                        }
-
-                        println!("--------");
-                        println!("Evaluate");
-                        println!("");
-                        let mut vm = VM::new();
-                        match vm.run(&chunk) {
-                            // TODO: Support other types
-                            Some(value) => println!("{}", value as i32),
-                            None => println!("null"),
-                        }
+        let (module, diagnostics) = vuur_parse::parse_str_with_diagnostics(trimmed);
+        if !diagnostics.is_empty() {
+            let color = if std::io::stderr().is_terminal() {
+                ColorChoice::Always
+            } else {
+                ColorChoice::Never
+            };
+            for err in &diagnostics {
+                eprint!("{}", format_error(&source, err, color));
+            }
+            continue;
+        }
+
+        println!("-----------");
+        println!("Syntax Tree");
+        println!();
+        println!("{:#?}", module);
+
+        match vuur_compile::compile(&module) {
+            Ok(chunk) => {
+                println!("Saving chunk");
+                save_chunk(&chunk);
+
+                let mut buf = String::new();
+                match disassemble(&mut buf, &chunk) {
+                    Ok(_) => {
+                        println!("-----------");
+                        println!("Disassembly");
+                        println!();
+                        println!("{}", buf);
                     }
                     Err(err) => eprintln!("{}", err),
                 }
+
+                println!("--------");
+                println!("Evaluate");
+                println!();
+                let mut vm = VM::new();
+                match vm.run(&chunk) {
+                    Some(value) => println!("{}", value),
+                    None => println!("null"),
+                }
+
+                let fiber = vm.fiber();
+                if let Some(error) = fiber.error() {
+                    let span = fiber.error_addr().and_then(|addr| chunk.source_span(addr));
+                    report_error(&source, span, format_args!("runtime error: {error}"));
+                }
             }
-            Err(err) => eprintln!("{}", err),
+            Err(err) => report_error(&source, err.span, &err),
         }
     }
 }
 
+/// Print an error, prefixed with its location in the source when known.
+fn report_error(source: &SourceFile, span: Option<Span>, err: impl Display) {
+    match span {
+        Some(span) => eprintln!("{}: {}", source.location(span.start), err),
+        None => eprintln!("{}: {}", source.name(), err),
+    }
+}
+
 fn save_chunk(chunk: &Chunk) {
     let mut buf = Vec::new();
     chunk.encode(&mut buf).unwrap();
 
     let mut file = std::fs::File::create("test_codegen.bin").unwrap();
-    file.write(&buf).unwrap();
+    file.write_all(&buf).unwrap();
 }
@@ -27,21 +27,47 @@ pub mod opcodes {
 
     pub const PUSH_CONST:     OpCode = 0x10;
     pub const PUSH_CONST_IMM: OpCode = 0x11;
+    pub const PUSH_STR:       OpCode = 0x12;
+    pub const PUSH_CONST_I64: OpCode = 0x13;
+    pub const PUSH_CONST_F32: OpCode = 0x16;
+    pub const PUSH_CONST_F64: OpCode = 0x17;
+    pub const PUSH_CONST_BOOL: OpCode = 0x1A;
     
+    // ------------------------------------------------------------------------
+    // Strings
+    pub const TO_STR:     OpCode = 0x18; // convert value to string
+    pub const CONCAT_STR: OpCode = 0x19;
+
     // ------------------------------------------------------------------------
     // Variables
     pub const LOAD_LOCAL: OpCode = 0x14;
     pub const STORE_LOCAL: OpCode = 0x15;
+    pub const LOAD_UPVALUE:  OpCode = 0x1B; // push variable captured in upvalue K of the current closure
+    pub const STORE_UPVALUE: OpCode = 0x1C; // pop value into variable captured in upvalue K
+    pub const CLOSE_UPVALUE: OpCode = 0x1D; // move captured locals from slot K upwards off the stack
 
     // ------------------------------------------------------------------------
     // Callables
     pub const FUNC: OpCode = 0x20;
+    pub const PUSH_FUNC: OpCode = 0x21; // push function K as a value
+    pub const CLOSURE:   OpCode = 0x22; // push closure of function K, without upvalues
+    pub const CAPTURE_LOCAL:   OpCode = 0x23; // add upvalue for local K to the closure on top of the stack
+    pub const CAPTURE_UPVALUE: OpCode = 0x24; // add upvalue K of the current closure to the closure on top of the stack
 
-    pub const SKIP_1:  OpCode = 0x30;
+    pub const SKIP_1:  OpCode = 0x30; // skip next instruction if true
     pub const SKIP_LT: OpCode = 0x31;
     pub const SKIP_LE: OpCode = 0x32;
     pub const SKIP_EQ_I32: OpCode = 0x32;
     pub const JUMP_FALSE: OpCode = 0x33; // conditional jump
+    pub const RANGE_NEXT_I32:      OpCode = 0x34; // advance exclusive range, skip next instruction when done
+    pub const RANGE_NEXT_INCL_I32: OpCode = 0x35; // advance inclusive range, skip next instruction when done
+
+    // ------------------------------------------------------------------------
+    // Objects
+    pub const NEW_OBJ:   OpCode = 0x40; // push zeroed object of type K
+    pub const GET_FIELD: OpCode = 0x41; // replace object with value of field
+    pub const SET_FIELD: OpCode = 0x42; // pop value into field of object, leaving the object
+    pub const TO_IFACE:  OpCode = 0x43; // replace object with interface value using vtable K
 
     // ------------------------------------------------------------------------
     // Control Flow
@@ -50,11 +76,65 @@ pub mod opcodes {
     pub const RETURN:   OpCode = 0x52;
     pub const JUMP:     OpCode = 0x53; // unconditional jump
     pub const ABORT:    OpCode = 0xFF;
+
+    // ------------------------------------------------------------------------
+    // Wide and floating point arithmetic
+    pub const ADD_I64: OpCode = 0x60;
+    pub const SUB_I64: OpCode = 0x61;
+    pub const MUL_I64: OpCode = 0x62;
+    pub const DIV_I64: OpCode = 0x63;
+    pub const NEG_I64: OpCode = 0x64;
+    pub const EQ_I64:  OpCode = 0x65;
+
+    pub const ADD_F32: OpCode = 0x68;
+    pub const SUB_F32: OpCode = 0x69;
+    pub const MUL_F32: OpCode = 0x6A;
+    pub const DIV_F32: OpCode = 0x6B;
+    pub const NEG_F32: OpCode = 0x6C;
+    pub const EQ_F32:  OpCode = 0x6D;
+
+    pub const ADD_F64: OpCode = 0x70;
+    pub const SUB_F64: OpCode = 0x71;
+    pub const MUL_F64: OpCode = 0x72;
+    pub const DIV_F64: OpCode = 0x73;
+    pub const NEG_F64: OpCode = 0x74;
+    pub const EQ_F64:  OpCode = 0x75;
+
+    // ------------------------------------------------------------------------
+    // Comparison, resulting in a bool
+    pub const NE_I32: OpCode = 0x80;
+    pub const LT_I32: OpCode = 0x81;
+    pub const LE_I32: OpCode = 0x82;
+    pub const GT_I32: OpCode = 0x83;
+    pub const GE_I32: OpCode = 0x84;
+
+    pub const NE_I64: OpCode = 0x88;
+    pub const LT_I64: OpCode = 0x89;
+    pub const LE_I64: OpCode = 0x8A;
+    pub const GT_I64: OpCode = 0x8B;
+    pub const GE_I64: OpCode = 0x8C;
+
+    pub const NE_F32: OpCode = 0x90;
+    pub const LT_F32: OpCode = 0x91;
+    pub const LE_F32: OpCode = 0x92;
+    pub const GT_F32: OpCode = 0x93;
+    pub const GE_F32: OpCode = 0x94;
+
+    pub const NE_F64: OpCode = 0x98;
+    pub const LT_F64: OpCode = 0x99;
+    pub const LE_F64: OpCode = 0x9A;
+    pub const GT_F64: OpCode = 0x9B;
+    pub const GE_F64: OpCode = 0x9C;
+
+    // ------------------------------------------------------------------------
+    // Boolean logic
+    pub const EQ_BOOL:  OpCode = 0xA0;
+    pub const NE_BOOL:  OpCode = 0xA1;
+    pub const NOT_BOOL: OpCode = 0xA2;
 }
 
 // TODO: Fix bytecode write and use without compiler
 pub(crate) trait WriteBytecode {
-    fn write_data(&mut self, data: u32) -> io::Result<()>;
     fn write_simple(&mut self, op: OpCode) -> io::Result<u32>;
     fn write_k(&mut self, op: OpCode, k: u32) -> io::Result<u32>;
     fn write_a(&mut self, op: OpCode, a: i32) -> io::Result<()>;
@@ -64,12 +144,6 @@ pub(crate) trait WriteBytecode {
 }
 
 impl WriteBytecode for Vec<u32> {
-    #[inline]
-    fn write_data(&mut self, data: u32) -> io::Result<()> {
-        self.push(data);
-        Ok(())
-    }
-
     #[inline]
     fn write_simple(&mut self, op: OpCode) -> io::Result<u32> {
         let addr = self.len() as u32;
@@ -146,6 +220,66 @@ pub fn encode_a(op: OpCode, a: i32) -> u32 {
     (op as u32) | (((a & 0xFFFFFF) as u32) << 8)
 }
 
+/// Encode the argument of a field instruction, which is the
+/// field's index and the ID of the type declaring the field.
+///
+/// ```text
+/// |    24 |    16 |     8 |
+/// |-------|-------|-------|
+/// |    type ID    | field |
+/// ```
+///
+/// ```
+/// # use vuur_compile::bytecode::{decode_field, encode_field};
+/// let k = encode_field(3, 7);
+/// assert_eq!(decode_field(k), (3, 7));
+/// ```
+#[inline]
+pub fn encode_field(type_id: u32, field_index: u32) -> u32 {
+    debug_assert!(type_id <= 0xFFFF, "type ID must fit in 16 bits");
+    debug_assert!(field_index <= 0xFF, "field index must fit in 8 bits");
+    ((type_id & 0xFFFF) << 8) | (field_index & 0xFF)
+}
+
+/// Decode the argument of a field instruction, as produced
+/// by [`encode_field`], into the type ID and field index.
+#[inline]
+pub fn decode_field(k: u32) -> (u32, u32) {
+    ((k >> 8) & 0xFFFF, k & 0xFF)
+}
+
+/// Encode the argument of a dynamic call instruction, which is the
+/// number of arguments, including the receiver, and the vtable slot
+/// of the method being called.
+///
+/// When calling a function value, the function takes the place
+/// of the receiver and the slot is unused.
+///
+/// ```text
+/// |    24 |    16 |     8 |
+/// |-------|-------|-------|
+/// |     slot      | arity |
+/// ```
+///
+/// ```
+/// # use vuur_compile::bytecode::{decode_dyn_call, encode_dyn_call};
+/// let k = encode_dyn_call(2, 5);
+/// assert_eq!(decode_dyn_call(k), (2, 5));
+/// ```
+#[inline]
+pub fn encode_dyn_call(arity: u32, slot: u32) -> u32 {
+    debug_assert!(arity <= 0xFF, "arity must fit in 8 bits");
+    debug_assert!(slot <= 0xFFFF, "vtable slot must fit in 16 bits");
+    ((slot & 0xFFFF) << 8) | (arity & 0xFF)
+}
+
+/// Decode the argument of a dynamic call instruction, as
+/// produced by [`encode_dyn_call`], into the arity and vtable slot.
+#[inline]
+pub fn decode_dyn_call(k: u32) -> (u32, u32) {
+    (k & 0xFF, (k >> 8) & 0xFFFF)
+}
+
 /// Encode the given 64-bit integer as two 32-bit integers.
 ///
 /// The resulting encoding is intended to be encoded further
@@ -163,6 +297,18 @@ pub fn encode_u64(value: u64) -> [u32; 2] {
     [(value & 0xFFFFFFFF) as u32, ((value & 0xFFFFFFFF00000000) >> 32) as u32]
 }
 
+/// Decode two 32-bit integers, as produced by [`encode_u64`],
+/// back into a 64-bit integer.
+///
+/// ```
+/// # use vuur_compile::bytecode::decode_u64;
+/// assert_eq!(decode_u64([1, 2]), 0x200000001);
+/// ```
+#[inline]
+pub fn decode_u64([low, high]: [u32; 2]) -> u64 {
+    (low as u64) | ((high as u64) << 32)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -197,6 +343,7 @@ mod test {
 
         for (input, output) in cases {
             assert_eq!(*output, encode_u64(*input));
+            assert_eq!(*input, decode_u64(*output));
         }
     }
 }
@@ -2,11 +2,13 @@
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use vuur_lexer::span::Span;
 
 use crate::constants::*;
 use crate::error::{CompileError, ErrorKind, Result};
 use crate::func::{FuncDef, FuncId};
 use crate::limits::*;
+use crate::typedef::{FieldDef, InterfaceDef, InterfaceId, MethodDef, TypeDef, TypeId, VTable};
 
 // TODO: Serialise and deserialise chunk to binary file
 
@@ -15,11 +17,21 @@ pub struct Chunk {
     /// Bytecode
     pub(crate) code: Vec<u32>,
     pub(crate) funcs: Vec<FuncDef>,
-    pub(crate) data: Vec<Box<[u8]>>,
+    /// Structure types, indexed by [`TypeId`].
+    pub(crate) types: Vec<TypeDef>,
+    /// Interface types, indexed by [`InterfaceId`].
+    pub(crate) interfaces: Vec<InterfaceDef>,
+    /// Method tables of structure types converted to interfaces.
+    pub(crate) vtables: Vec<VTable>,
     /// Name of file where the original source was loaded.
     pub(crate) name: String,
     pub(crate) header: ChunkHeader,
     pub(crate) entrypoint: Option<FuncId>,
+    /// Positions in the source code that the bytecode was compiled from.
+    ///
+    /// Sorted pairs of bytecode address and source span, where
+    /// each pair covers the instructions up to the next pair.
+    pub(crate) source_marks: Vec<(u32, Span)>,
 }
 
 impl Chunk {
@@ -30,10 +42,13 @@ impl Chunk {
         Self {
             name: name.to_string(),
             funcs: vec![Self::stub_func_def()],
-            data: Vec::new(),
+            types: Vec::new(),
+            interfaces: Vec::new(),
+            vtables: Vec::new(),
             code,
             header: ChunkHeader::empty(),
             entrypoint: None,
+            source_marks: Vec::new(),
         }
     }
 
@@ -41,10 +56,13 @@ impl Chunk {
         Self {
             name: CHUNK_DEFAULT_NAME.to_owned(),
             funcs: vec![Self::stub_func_def()],
-            data: Vec::new(),
+            types: Vec::new(),
+            interfaces: Vec::new(),
+            vtables: Vec::new(),
             code,
             header: ChunkHeader::empty(),
             entrypoint: None,
+            source_marks: Vec::new(),
         }
     }
 
@@ -57,22 +75,58 @@ impl Chunk {
         self.funcs.get(func_id as usize)
     }
 
+    #[inline]
+    pub fn type_by_id(&self, type_id: TypeId) -> Option<&TypeDef> {
+        self.types.get(type_id.to_usize())
+    }
+
+    /// Structure type declarations, in order of their [`TypeId`].
+    #[inline]
+    pub fn types(&self) -> &[TypeDef] {
+        &self.types
+    }
+
+    #[inline]
+    pub fn interface_by_id(&self, interface_id: InterfaceId) -> Option<&InterfaceDef> {
+        self.interfaces.get(interface_id.to_usize())
+    }
+
+    /// Interface type declarations, in order of their [`InterfaceId`].
+    #[inline]
+    pub fn interfaces(&self) -> &[InterfaceDef] {
+        &self.interfaces
+    }
+
+    #[inline]
+    pub fn vtable_by_id(&self, vtable_id: u32) -> Option<&VTable> {
+        self.vtables.get(vtable_id as usize)
+    }
+
     fn stub_func_def() -> FuncDef {
         FuncDef {
             id: None,
             // Point bytecode to end of chunk to avoid conflicts with real functions.
-            bytecode_span: (std::u32::MAX, std::u32::MAX),
+            bytecode_span: (u32::MAX, u32::MAX),
             local_count: 0,
             arity: 0,
+            constants: Vec::new(),
+            strings: Vec::new(),
         }
     }
 
+    /// Source code that the instruction at the given address was compiled from.
+    pub fn source_span(&self, addr: usize) -> Option<Span> {
+        let index = self.source_marks.partition_point(|(mark_addr, _)| *mark_addr as usize <= addr);
+        index.checked_sub(1).map(|index| self.source_marks[index].1)
+    }
+
     #[inline]
     pub fn code(&self) -> &[u32] {
         &self.code
     }
 
     #[inline]
+    #[cfg(test)]
     pub(crate) fn code_mut(&mut self) -> &mut Vec<u32> {
         &mut self.code
     }
@@ -109,13 +163,92 @@ impl Chunk {
     /// Adds a function definition to the chunk's function table.
     pub(crate) fn add_func(&mut self, mut func: FuncDef) -> FuncId {
         assert!(self.funcs.len() < MAX_FUNCS, "maximum number of functions reached");
-        assert!(self.funcs.len() > 0, "function table must start at 1");
+        assert!(!self.funcs.is_empty(), "function table must start at 1");
         let next_id = FuncId::new(self.funcs.len() as u32);
         func.id = next_id;
         self.funcs.push(func);
         next_id.unwrap()
     }
 
+    /// Adds a type declaration to the chunk's type table.
+    pub(crate) fn add_type(&mut self, name: String, fields: Vec<FieldDef>) -> Result<TypeId> {
+        if self.types.len() >= MAX_TYPES {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                "maximum number of types reached",
+            ));
+        }
+
+        let id = TypeId(self.types.len() as u32);
+        self.types.push(TypeDef {
+            id,
+            name,
+            fields,
+            methods: Vec::new(),
+        });
+        Ok(id)
+    }
+
+    /// Adds a method to the method table of a type declaration.
+    pub(crate) fn add_method(&mut self, type_id: TypeId, name: String, func_id: FuncId) -> Result<()> {
+        let type_def = self.types.get_mut(type_id.to_usize()).ok_or_else(|| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("failed to find type for id {}", type_id.to_u32()),
+            )
+        })?;
+
+        if type_def.method(&name).is_some() {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("method '{name}' already declared for type '{}'", type_def.name),
+            ));
+        }
+
+        type_def.methods.push(MethodDef { name, func_id });
+        Ok(())
+    }
+
+    /// Adds an interface declaration to the chunk's interface table.
+    pub(crate) fn add_interface(&mut self, name: String, methods: Vec<String>) -> Result<InterfaceId> {
+        if self.interfaces.len() >= MAX_TYPES {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                "maximum number of types reached",
+            ));
+        }
+
+        let id = InterfaceId(self.interfaces.len() as u32);
+        self.interfaces.push(InterfaceDef { id, name, methods });
+        Ok(id)
+    }
+
+    /// Adds the method table of a structure type converted to an interface,
+    /// returning its index. Each pair of types shares a single vtable.
+    pub(crate) fn add_vtable(&mut self, type_id: TypeId, interface_id: InterfaceId, funcs: Vec<FuncId>) -> Result<u32> {
+        let existing = self
+            .vtables
+            .iter()
+            .position(|vtable| vtable.type_id == type_id && vtable.interface_id == interface_id);
+        if let Some(index) = existing {
+            return Ok(index as u32);
+        }
+
+        if self.vtables.len() >= MAX_VTABLES {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                "maximum number of vtables reached",
+            ));
+        }
+
+        self.vtables.push(VTable {
+            type_id,
+            interface_id,
+            funcs,
+        });
+        Ok(self.vtables.len() as u32 - 1)
+    }
+
     /// Adds a stub function to the chunk to reserve a function ID.
     pub(crate) fn add_func_stub(&mut self) -> FuncId {
         self.add_func(Self::stub_func_def())
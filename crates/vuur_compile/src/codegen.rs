@@ -1,14 +1,29 @@
+use std::collections::HashMap;
+
+use vuur_lexer::number::{parse_number_as, NumTy, NumValue};
+use vuur_lexer::span::{Span, Spanned};
+use vuur_lexer::Token;
 use vuur_parse::cond::{ElseStmt, IfStmt};
-use vuur_parse::expr::{CallArg, Expr, OperatorKind};
+use vuur_parse::delim::Delimited;
+use vuur_parse::expr::{CallArg, Expr, FuncLit, InterpPart, MemberAccess, MemberPath, NumLit, OperatorKind, StructLit};
+use vuur_parse::func::{FuncArg, Receiver, Separator};
+use vuur_parse::ident::Ident;
+use vuur_parse::loops::{ForStmt, WhileStmt};
 use vuur_parse::module::VuurModule;
 use vuur_parse::stmt::{DefStmt, SimpleStmt};
+use vuur_parse::ty::{InterfaceDef, Type, TypeDef, TypeKind};
+use vuur_parse::var::VarDef;
 
-use crate::bytecode::{decode_opcode, encode_u64, opcodes, WriteBytecode};
+use crate::bytecode::{
+    decode_k, decode_opcode, encode_dyn_call, encode_field, encode_k, encode_u64, opcodes, OpCode, WriteBytecode,
+};
 use crate::chunk::{Chunk, ChunkHeader};
 use crate::constants::*;
 use crate::error::{CompileError, ErrorKind, Result};
 use crate::func::FuncId;
 use crate::limits::*;
+use crate::typedef::{FieldDef, FieldTy, InterfaceId, TypeId};
+use crate::types::{FuncSig, FuncTyId, Ty};
 use crate::FuncDef;
 
 pub const ENTRYPOINT_NAME: &str = "Main";
@@ -31,7 +46,17 @@ struct FuncEnv {
     /// String constant values.
     strings: Vec<String>,
     /// Local variable values, including the function's parameters.
-    locals: Vec<String>,
+    ///
+    /// Only the locals of the scopes currently being compiled are kept,
+    /// so the index of a local is the stack slot it occupies.
+    locals: Vec<Local>,
+    /// Peak number of locals live at the same time, which is
+    /// the number of stack slots the function needs.
+    max_locals: usize,
+    /// Nesting depth of the lexical scope being compiled.
+    ///
+    /// The function's parameters are in the outermost scope.
+    scope_depth: u32,
     /// Local functions.
     ///
     /// This indicates which functions belong to this scope. The `FuncId` is
@@ -39,37 +64,181 @@ struct FuncEnv {
     ///
     /// TODO: Functions should be looked up by name, receiver type and argument signature.
     funcs: Vec<(String, FuncId)>,
+    /// Local types.
+    ///
+    /// The `TypeId` is generated by the `Chunk`, where it's the index of
+    /// the type's layout descriptor.
+    types: Vec<(String, TypeId)>,
+    /// Local interface types, which share a namespace with the other types.
+    interfaces: Vec<(String, InterfaceId)>,
+    /// Stack of loops enclosing the statement being compiled.
+    loops: Vec<LoopEnv>,
     /// Buffer of bytecode that belongs to this function.
     bytecode: Vec<u32>,
-    /// Start and end bytecode addresses.
-    bytecode_span: (u32, u32),
+    /// Positions in the source code that the bytecode was compiled from.
+    ///
+    /// Each mark is a bytecode address, relative to the start of this
+    /// function, and the source span of the instructions from that
+    /// address up to the next mark.
+    source_marks: Vec<(u32, Span)>,
+    /// Addresses of the jump instructions in the bytecode.
+    ///
+    /// Jump targets are relative to the start of this function,
+    /// and are relocated when it's emitted into the chunk.
+    jumps: Vec<u32>,
     /// Number of arguments needed to call this function.
     arity: u8,
+    /// Type of the value returned by this function.
+    rtn: Ty,
+    /// Whether the function is created at runtime as a closure,
+    /// which allows it to capture the enclosing function's variables.
+    ///
+    /// Functions declared at the top level of the module, and
+    /// methods, are called statically and can't capture.
+    is_closure: bool,
+    /// Variables captured from enclosing functions,
+    /// indexed by the closure's upvalue slot.
+    upvalues: Vec<Capture>,
 }
 
 impl FuncEnv {
-    fn add_constant(&mut self, konst: ConstValue) -> usize {
-        self.constants.add_constant(konst)
-    }
-
-    /// Declare a new local variable in this scope.
-    // TODO: Local variable type information
-    fn insert_local(&mut self, name: &str) -> Result<LocalId> {
-        match self.resolve_local(name) {
-            Some(_) => Err(CompileError {
-                message: format!("local variable '{name}' already declared (shadowing not implemented yet)"),
-                kind: ErrorKind::Compiler,
-            }),
+    /// Add a scalar to the function's constant table, returning
+    /// its index. Identical constants share the same index.
+    fn add_constant(&mut self, konst: ConstValue) -> Result<u32> {
+        let index = self.constants.add_constant(konst);
+
+        if index + konst.encoded_size() > MAX_CONSTANTS {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                "maximum function constants exceeded",
+            ));
+        }
+
+        Ok(index as u32)
+    }
+
+    /// Add a string to the function's string table, returning
+    /// its index. Identical strings share the same index.
+    fn add_string(&mut self, string: &str) -> Result<usize> {
+        match self.strings.iter().position(|s| s == string) {
+            Some(index) => Ok(index),
             None => {
-                let local_id = LocalId(self.locals.len() as u32);
-                self.locals.push(name.to_string());
-                Ok(local_id)
+                if self.strings.len() >= MAX_STRINGS {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        "maximum function string constants exceeded",
+                    ));
+                }
+
+                self.strings.push(string.to_string());
+                Ok(self.strings.len() - 1)
             }
         }
     }
 
-    fn resolve_local(&self, name: &str) -> Option<LocalId> {
-        self.locals.iter().position(|n| n == name).map(|idx| LocalId(idx as u32))
+    /// Declare a new local variable in the current scope.
+    ///
+    /// The local shadows any variable with the same name
+    /// declared in an enclosing scope.
+    fn insert_local(&mut self, name: &str, ty: Ty) -> Result<LocalId> {
+        let is_declared = self
+            .locals
+            .iter()
+            .rev()
+            .take_while(|local| local.depth == self.scope_depth)
+            .any(|local| local.name == name);
+
+        if is_declared {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("local variable '{name}' already declared in this scope"),
+            ));
+        }
+
+        if self.locals.len() >= MAX_LOCALS {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                "maximum function local variables exceeded",
+            ));
+        }
+
+        let local_id = LocalId(self.locals.len() as u32);
+        self.locals.push(Local {
+            name: name.to_string(),
+            ty,
+            depth: self.scope_depth,
+            is_captured: false,
+        });
+        self.max_locals = self.max_locals.max(self.locals.len());
+        Ok(local_id)
+    }
+
+    /// Find the innermost local variable with the given name.
+    fn resolve_local(&self, name: &str) -> Option<(LocalId, Ty)> {
+        self.locals
+            .iter()
+            .rposition(|local| local.name == name)
+            .map(|idx| (LocalId(idx as u32), self.locals[idx].ty))
+    }
+
+    /// Add a variable captured from the enclosing function, returning
+    /// its upvalue index. Each variable is only captured once.
+    fn add_upvalue(&mut self, capture: Capture) -> Result<u32> {
+        if let Some(index) = self.upvalues.iter().position(|upvalue| *upvalue == capture) {
+            return Ok(index as u32);
+        }
+
+        if self.upvalues.len() >= MAX_UPVALUES {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                "maximum function captured variables exceeded",
+            ));
+        }
+
+        self.upvalues.push(capture);
+        Ok(self.upvalues.len() as u32 - 1)
+    }
+
+    fn begin_scope(&mut self) {
+        self.scope_depth += 1;
+    }
+
+    /// Drop the locals of the innermost scope, so their
+    /// stack slots can be reused by later locals.
+    fn end_scope(&mut self) -> Result<()> {
+        debug_assert!(self.scope_depth > 0, "scope ended without beginning");
+        self.scope_depth -= 1;
+
+        let live = self.locals.iter().take_while(|local| local.depth <= self.scope_depth).count();
+        self.close_upvalues(live)?;
+        self.locals.truncate(live);
+
+        Ok(())
+    }
+
+    /// Close the upvalues of captured locals, from the given local upwards.
+    ///
+    /// Closures keep their captured variables after the locals go out
+    /// of scope, and the stack slots are reused by other locals.
+    fn close_upvalues(&mut self, first: usize) -> Result<()> {
+        let captured = self.locals.iter().skip(first).position(|local| local.is_captured);
+
+        if let Some(offset) = captured {
+            self.bytecode.write_k(opcodes::CLOSE_UPVALUE, (first + offset) as u32)?;
+        }
+
+        Ok(())
+    }
+
+    /// Attribute the instructions written from now on to the given source span.
+    fn mark_source(&mut self, span: Span) {
+        let addr = self.next_addr();
+
+        match self.source_marks.last_mut() {
+            // Nothing was written since the last mark.
+            Some(mark) if mark.0 == addr => mark.1 = span,
+            _ => self.source_marks.push((addr, span)),
+        }
     }
 
     fn prev_addr(&self) -> u32 {
@@ -84,6 +253,21 @@ impl FuncEnv {
     fn next_addr(&self) -> u32 {
         self.bytecode.len() as u32
     }
+
+    /// Write a jump to `target`, an address in this function's bytecode.
+    fn write_jump(&mut self, target: u32) -> Result<()> {
+        let addr = self.bytecode.write_k(opcodes::JUMP, target)?;
+        self.jumps.push(addr);
+        Ok(())
+    }
+
+    /// Replace the stub instruction at `addr` with a jump to `target`,
+    /// where both are addresses in this function's bytecode.
+    fn patch_jump(&mut self, addr: u32, target: u32) -> Result<()> {
+        self.bytecode.patch_k(addr, opcodes::JUMP, target)?;
+        self.jumps.push(addr);
+        Ok(())
+    }
 }
 
 impl Default for FuncEnv {
@@ -94,22 +278,66 @@ impl Default for FuncEnv {
             constants: ConstantTable::new(),
             strings: Vec::new(),
             locals: Vec::new(),
+            max_locals: 0,
+            scope_depth: 0,
             funcs: Vec::new(),
+            types: Vec::new(),
+            interfaces: Vec::new(),
+            loops: Vec::new(),
             bytecode: Vec::new(),
-            bytecode_span: (0, 0),
+            source_marks: Vec::new(),
+            jumps: Vec::new(),
             arity: 0,
+            rtn: Ty::Void,
+            is_closure: false,
+            upvalues: Vec::new(),
         }
     }
 }
 
+/// Jumps out of a loop, which are patched once
+/// the loop's bytecode is complete.
+#[derive(Default)]
+struct LoopEnv {
+    /// Addresses of `break` stubs, which jump to the end of the loop.
+    breaks: Vec<u32>,
+    /// Addresses of `continue` stubs, which jump to the start of the next iteration.
+    continues: Vec<u32>,
+    /// Number of locals declared outside the loop's body.
+    ///
+    /// Jumping out of the body leaves the scopes of the locals after these.
+    locals: usize,
+}
+
+/// Local variable, or function argument.
+struct Local {
+    name: String,
+    ty: Ty,
+    /// Depth of the scope the local was declared in.
+    depth: u32,
+    /// Whether a closure captured the local, so its upvalue
+    /// must be closed when the local goes out of scope.
+    is_captured: bool,
+}
+
+/// Where a closure captures a variable from, when it's created
+/// by the enclosing function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Capture {
+    /// Local variable of the enclosing function.
+    Local(u32),
+    /// Variable the enclosing function captured itself.
+    Upvalue(u32),
+}
+
 /// ID of local variable, or function argument.
 #[derive(Debug, Clone, Copy)]
 #[repr(transparent)]
 struct LocalId(u32);
 
-impl Into<u32> for LocalId {
-    fn into(self) -> u32 {
-        self.0
+impl From<LocalId> for u32 {
+    fn from(val: LocalId) -> Self {
+        val.0
     }
 }
 
@@ -128,7 +356,9 @@ impl ConstantTable {
 
     fn add_constant(&mut self, value: ConstValue) -> usize {
         match self.values.iter().position(|el| el == &value) {
-            Some(index) => index,
+            // Wide constants take up two slots, so the slot index
+            // is offset by the sizes of the constants before it.
+            Some(position) => self.values[..position].iter().map(|el| el.encoded_size()).sum(),
             None => {
                 let next_index = self.next_index;
                 self.next_index += value.encoded_size();
@@ -138,12 +368,20 @@ impl ConstantTable {
         }
     }
 
-    fn len(&self) -> usize {
-        self.next_index
-    }
+    /// Encode the constants into 32-bit slots, laid out
+    /// so the indices returned by [`Self::add_constant`] match.
+    fn encode(&self) -> Vec<u32> {
+        let mut slots = Vec::with_capacity(self.next_index);
+
+        for konst in self.values.iter().copied() {
+            if let Some(bits) = konst.to_bits() {
+                slots.push(bits);
+            } else if let Some(bits) = konst.to_bits2() {
+                slots.extend_from_slice(&bits);
+            }
+        }
 
-    fn is_empty(&self) -> bool {
-        self.next_index == 0
+        slots
     }
 }
 
@@ -158,7 +396,7 @@ enum ConstValue {
 
 impl ConstValue {
     /// Size of constant value when encoded into u32.
-    fn encoded_size(&self) -> usize {
+    fn encoded_size(self) -> usize {
         match self {
             Self::I32(_) | Self::F32(_) => 1,
             Self::I64(_) | Self::F64(_) => 2,
@@ -166,16 +404,17 @@ impl ConstValue {
         }
     }
 
-    fn to_bits(&self) -> Option<u32> {
-        match *self {
+    fn to_bits(self) -> Option<u32> {
+        match self {
             Self::I32(val) => Some(val as u32),
             Self::F32(val) => Some(val.to_bits()),
+            Self::Bool(val) => Some(val as u32),
             _ => None,
         }
     }
 
-    fn to_bits2(&self) -> Option<[u32; 2]> {
-        match *self {
+    fn to_bits2(self) -> Option<[u32; 2]> {
+        match self {
             Self::I64(val) => Some(encode_u64(val as u64)),
             Self::F64(val) => Some(encode_u64(val.to_bits())),
             _ => None,
@@ -183,10 +422,14 @@ impl ConstValue {
     }
 }
 
+/// Where a name was declared, relative to the function being compiled.
 #[derive(Debug)]
 enum SymbolScope<T> {
+    /// Declared in the function itself.
     Local(T),
+    /// Declared in an enclosing function, and captured as an upvalue.
     NonLocal(T),
+    /// Declared at the top level of the module.
     Global(T),
 }
 
@@ -209,15 +452,16 @@ pub struct BytecodeCodegen {
     chunk: Chunk,
     /// Stack of nested function environments.
     funcs: Vec<FuncEnv>,
-    /// Mapping of bytecode to original source line.
+    /// Signatures of declared functions, used to type check calls.
+    sigs: HashMap<FuncId, FuncSig>,
+    /// Method signatures of declared interfaces, indexed by vtable slot.
     ///
-    /// The index in the vector is equal to the bytecode's offset
-    /// in the generated binary. The elements are line numbers in
-    /// the source code text, starting at 1.
+    /// The receiver is the interface itself.
+    interface_sigs: HashMap<InterfaceId, Vec<FuncSig>>,
+    /// Signatures of function types, indexed by [`FuncTyId`].
     ///
-    /// Used to add source text information when disassembling.
-    // TODO: Source mapping
-    _lines: Vec<usize>,
+    /// Each signature is listed once, so function types can be compared by ID.
+    func_tys: Vec<FuncSig>,
 }
 
 impl BytecodeCodegen {
@@ -225,7 +469,9 @@ impl BytecodeCodegen {
         Self {
             chunk: Chunk::default(),
             funcs: Vec::with_capacity(64),
-            _lines: Vec::new(),
+            sigs: HashMap::new(),
+            interface_sigs: HashMap::new(),
+            func_tys: Vec::new(),
         }
     }
 
@@ -263,40 +509,160 @@ impl BytecodeCodegen {
         }
     }
 
-    /// Retrieve the top environment on the top of the environment stack.
-    fn top_env(&self) -> Option<&FuncEnv> {
-        self.funcs.last()
-    }
-
+    /// Find the innermost declared function with the given name.
+    ///
+    /// Only the top level of the module declares functions by name. Functions
+    /// nested in other functions are closures, stored in local variables.
     fn resolve_func(&self, name: &str) -> Result<SymbolScope<FuncId>> {
-        for (index, block) in self.funcs.iter().enumerate() {
-            let is_local = index == 0;
-            let is_global = if self.funcs.len() != 0 {
-                index == self.funcs.len() - 1
-            } else {
-                false
-            };
-
-            let maybe_func_id = block.funcs.iter().find(|(n, _)| n == name).map(|(_, id)| id).copied();
+        for (index, env) in self.funcs.iter().enumerate().rev() {
+            let maybe_func_id = env.funcs.iter().find(|(n, _)| n == name).map(|(_, id)| id).copied();
 
             if let Some(func_id) = maybe_func_id {
-                return if is_local {
+                return if index == self.funcs.len() - 1 {
                     Ok(SymbolScope::Local(func_id))
-                } else if is_global {
-                    Ok(SymbolScope::Global(func_id))
                 } else {
-                    Err(CompileError {
-                        message: format!("error resolving function '{name}': closures not implemented yet"),
-                        kind: ErrorKind::Compiler,
-                    })
+                    Ok(SymbolScope::Global(func_id))
+                };
+            }
+        }
+
+        Err(CompileError::new(
+            ErrorKind::Compiler,
+            format!("could not resolve function with name '{name}'"),
+        ))
+    }
+
+    /// Find the innermost variable with the given name, returning
+    /// its local slot or upvalue index, and its type.
+    ///
+    /// Variables of enclosing functions are captured by the
+    /// function being compiled, and the functions in between.
+    fn resolve_var(&mut self, name: &str) -> Result<Option<SymbolScope<(u32, Ty)>>> {
+        let top = self.funcs.len() - 1;
+
+        if let Some((local_id, ty)) = self.funcs[top].resolve_local(name) {
+            return Ok(Some(SymbolScope::Local((local_id.0, ty))));
+        }
+
+        Ok(self.resolve_upvalue(top, name)?.map(SymbolScope::NonLocal))
+    }
+
+    /// Capture a variable of the functions enclosing the function at the given
+    /// index of the environment stack, returning its upvalue index and type.
+    fn resolve_upvalue(&mut self, index: usize, name: &str) -> Result<Option<(u32, Ty)>> {
+        // The top level of the module isn't executed, so it has no variables to capture.
+        if !self.funcs[index].is_closure || index <= 1 {
+            return Ok(None);
+        }
+
+        let parent = &mut self.funcs[index - 1];
+        let (capture, ty) = match parent.resolve_local(name) {
+            Some((local_id, ty)) => {
+                parent.locals[local_id.0 as usize].is_captured = true;
+                (Capture::Local(local_id.0), ty)
+            }
+            None => match self.resolve_upvalue(index - 1, name)? {
+                Some((upvalue, ty)) => (Capture::Upvalue(upvalue), ty),
+                None => return Ok(None),
+            },
+        };
+
+        let upvalue = self.funcs[index].add_upvalue(capture)?;
+        Ok(Some((upvalue, ty)))
+    }
+
+    /// Find the innermost declared type with the given name.
+    fn resolve_struct(&self, name: &str) -> Option<TypeId> {
+        self.funcs
+            .iter()
+            .rev()
+            .find_map(|env| env.types.iter().find(|(n, _)| n == name).map(|(_, id)| *id))
+    }
+
+    /// Find the innermost declared interface with the given name.
+    fn resolve_interface(&self, name: &str) -> Option<InterfaceId> {
+        self.funcs
+            .iter()
+            .rev()
+            .find_map(|env| env.interfaces.iter().find(|(n, _)| n == name).map(|(_, id)| *id))
+    }
+
+    /// Resolve a type annotation to a builtin or declared type.
+    fn resolve_type(&mut self, ty: &Type) -> Result<Ty> {
+        match &ty.kind {
+            TypeKind::Ident(ident) => self.resolve_type_name(ident),
+            TypeKind::Struct(struct_def) => Err(CompileError::new(
+                ErrorKind::Compiler,
+                "anonymous struct types are not supported; declare the struct with a type statement",
+            )
+            .with_span(struct_def.span())),
+            TypeKind::Interface(interface_def) => Err(CompileError::new(
+                ErrorKind::Compiler,
+                "anonymous interface types are not supported; declare the interface with a type statement",
+            )
+            .with_span(interface_def.span())),
+            TypeKind::Func(func_def) => {
+                let args = func_def
+                    .args
+                    .iter()
+                    .map(|arg| self.resolve_type(arg))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let rtn = match &func_def.rtn {
+                    Some(rtn) => self.resolve_type(&rtn.ty)?,
+                    None => Ty::Void,
                 };
+
+                Ok(self.func_ty(FuncSig { args, rtn }))
             }
         }
+    }
+
+    /// Function type with the given signature.
+    fn func_ty(&mut self, sig: FuncSig) -> Ty {
+        let index = match self.func_tys.iter().position(|s| *s == sig) {
+            Some(index) => index,
+            None => {
+                self.func_tys.push(sig);
+                self.func_tys.len() - 1
+            }
+        };
+
+        Ty::Func(FuncTyId(index as u32))
+    }
+
+    fn resolve_type_name(&self, name: &Ident) -> Result<Ty> {
+        Ty::from_name(&name.text)
+            .or_else(|| self.resolve_struct(&name.text).map(Ty::Struct))
+            .or_else(|| self.resolve_interface(&name.text).map(Ty::Interface))
+            .ok_or_else(|| {
+                CompileError::new(ErrorKind::Compiler, format!("unknown type '{}'", name.text)).with_span(name.span())
+            })
+    }
 
-        Err(CompileError {
-            message: format!("could not resolve function with name '{name}'"),
-            kind: ErrorKind::Compiler,
-        })
+    /// Name of the type, as it would be written in source code.
+    fn ty_name(&self, ty: Ty) -> String {
+        match ty {
+            Ty::Struct(type_id) => match self.chunk.type_by_id(type_id) {
+                Some(type_def) => type_def.name.clone(),
+                None => ty.to_string(),
+            },
+            Ty::Interface(interface_id) => match self.chunk.interface_by_id(interface_id) {
+                Some(interface_def) => interface_def.name.clone(),
+                None => ty.to_string(),
+            },
+            Ty::Func(func_ty_id) => match self.func_tys.get(func_ty_id.to_usize()) {
+                Some(sig) => {
+                    let args = sig.args.iter().map(|ty| self.ty_name(*ty)).collect::<Vec<_>>();
+                    match sig.rtn {
+                        Ty::Void => format!("func({})", args.join(", ")),
+                        rtn => format!("func({}) -> {}", args.join(", "), self.ty_name(rtn)),
+                    }
+                }
+                None => ty.to_string(),
+            },
+            _ => ty.to_string(),
+        }
     }
 
     fn write_header(&mut self) {
@@ -309,16 +675,12 @@ impl BytecodeCodegen {
     }
 
     fn begin_func(&mut self) {
-        self.funcs.push(FuncEnv {
-            bytecode_span: (self.chunk.code.len() as u32, 0),
-            ..FuncEnv::default()
-        });
+        self.funcs.push(FuncEnv::default());
     }
 
     fn push_func(&mut self, func_id: FuncId) {
         self.funcs.push(FuncEnv {
             id: Some(func_id),
-            bytecode_span: (self.chunk.code.len() as u32, 0),
             ..FuncEnv::default()
         });
     }
@@ -326,7 +688,7 @@ impl BytecodeCodegen {
     /// Pops the top function state and emits it into the chunk bytecode.
     fn finish_func(&mut self) -> Result<FuncId> {
         match self.funcs.pop() {
-            Some(func) => {
+            Some(mut func) => {
                 // Emit function def header
                 // self.chunk.code_mut().write_simple(opcodes::FUNC)?;
 
@@ -338,9 +700,23 @@ impl BytecodeCodegen {
 
                 // Write bytecode instructions
                 let span_start = self.chunk.code.len() as u32;
+
+                // Jump targets are relative to the function's bytecode. Functions
+                // nested in its body have been emitted before it, so its position
+                // in the chunk is only known now.
+                for addr in &func.jumps {
+                    let (opcode, target) = decode_k(func.bytecode[*addr as usize]);
+                    func.bytecode[*addr as usize] = encode_k(opcode, span_start + target);
+                }
+
                 self.chunk.code.extend_from_slice(&func.bytecode);
                 let span_end = self.chunk.code.len() as u32;
 
+                // Source positions are relative to the function's bytecode.
+                for (addr, span) in &func.source_marks {
+                    self.chunk.source_marks.push((span_start + addr, *span));
+                }
+
                 let func_id = func.id.expect("block must have function ID");
 
                 // Both function arguments and local variables are compiled
@@ -354,13 +730,15 @@ impl BytecodeCodegen {
                 // the stack will be extended with superfluous slots for the arguments.
                 //
                 // This is calculated outside of the VM for performance.
-                let local_count = func.locals.len() - func.arity as usize;
+                let local_count = func.max_locals - func.arity as usize;
 
                 self.chunk.replace_func_stub(FuncDef {
                     id: Some(func_id),
                     bytecode_span: (span_start, span_end),
                     local_count,
                     arity: func.arity,
+                    constants: func.constants.encode(),
+                    strings: func.strings.iter().map(|s| s.as_str().into()).collect(),
                 });
 
                 Ok(func_id)
@@ -380,11 +758,13 @@ impl BytecodeCodegen {
         self.top_env_mut().id = Some(self.chunk.add_func_stub());
 
         self.compile_body(&module.stmts)?;
-        self.compile_return(None)?;
+        self.compile_return(Span::empty(module.span.end), None)?;
 
-        let entrypoint = self.resolve_func(ENTRYPOINT_NAME).map_err(|_| CompileError {
-            message: format!("failed to resolve module entrypoint '{ENTRYPOINT_NAME}'"),
-            kind: ErrorKind::Compiler,
+        let entrypoint = self.resolve_func(ENTRYPOINT_NAME).map_err(|_| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("failed to resolve module entrypoint '{ENTRYPOINT_NAME}'"),
+            )
         })?;
         assert!(entrypoint.is_local());
         self.chunk.entrypoint = entrypoint.local();
@@ -402,19 +782,178 @@ impl BytecodeCodegen {
     /// Compile the prototypes of types and function declaration,
     /// but not their bodies.
     fn compile_decls(&mut self, stmts: &[DefStmt]) -> Result<()> {
+        // Types are declared first, so function signatures can refer to them.
+        for stmt in stmts {
+            if let DefStmt::Type(type_def) = stmt {
+                self.compile_type_decl(type_def)?;
+            }
+        }
+
+        // Interface methods can refer to types declared after the interface.
+        for stmt in stmts {
+            if let DefStmt::Type(type_def) = stmt {
+                if let TypeKind::Interface(interface_def) = &type_def.ty.kind {
+                    self.compile_interface_sigs(type_def, interface_def)?;
+                }
+            }
+        }
+
+        // Functions nested in other functions are declared
+        // as local variables, when their statement is reached.
+        let is_module = self.funcs.len() <= 1;
         for stmt in stmts {
-            match stmt {
-                DefStmt::Type() => {
-                    todo!("compile type declaration")
+            if let DefStmt::Func(func) = stmt {
+                if is_module || func.receiver.is_some() {
+                    self.compile_func_prototype(func)?;
                 }
-                DefStmt::Func(func) => self.compile_func_prototype(func)?,
-                DefStmt::Var(var_def) => {
-                    self.top_env_mut().insert_local(&var_def.name.text)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_type_decl(&mut self, type_def: &TypeDef) -> Result<()> {
+        let name = type_def.name.text.as_str();
+
+        let env = self.top_env_mut();
+        if env.types.iter().any(|(n, _)| n == name) || env.interfaces.iter().any(|(n, _)| n == name) {
+            return Err(
+                CompileError::new(ErrorKind::Compiler, format!("type with name '{name}' already declared"))
+                    .with_span(type_def.name.span()),
+            );
+        }
+
+        let struct_def = match &type_def.ty.kind {
+            TypeKind::Struct(struct_def) => struct_def,
+            TypeKind::Interface(interface_def) => return self.compile_interface_decl(type_def, interface_def),
+            TypeKind::Ident(_) | TypeKind::Func(_) => {
+                return Err(CompileError::new(ErrorKind::Compiler, "type aliases are not supported")
+                    .with_span(type_def.ty.span()));
+            }
+        };
+
+        if struct_def.fields.len() > MAX_FIELDS {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("maximum number of fields exceeded in struct type '{name}'"),
+            )
+            .with_span(struct_def.span()));
+        }
+
+        if struct_def.fields.is_empty() {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("struct type '{name}' must have at least one field"),
+            )
+            .with_span(struct_def.span()));
+        }
+
+        let mut fields: Vec<FieldDef> = Vec::with_capacity(struct_def.fields.len());
+
+        for field in &struct_def.fields {
+            let field_name = field.name.text.as_str();
+
+            if fields.iter().any(|f| f.name == field_name) {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("field '{field_name}' already declared in struct type '{name}'"),
+                )
+                .with_span(field.name.span()));
+            }
+
+            let ty = match self.resolve_type(&field.ty)? {
+                Ty::Bool => FieldTy::Bool,
+                Ty::I32 => FieldTy::I32,
+                Ty::I64 => FieldTy::I64,
+                Ty::F32 => FieldTy::F32,
+                Ty::F64 => FieldTy::F64,
+                ty => {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        format!("type {} cannot be used as a struct field", self.ty_name(ty)),
+                    )
+                    .with_span(field.ty.span()));
                 }
-                _ => { /* skip */ }
+            };
+
+            fields.push(FieldDef {
+                name: field_name.to_string(),
+                ty,
+            });
+        }
+
+        let type_id = self
+            .chunk
+            .add_type(name.to_string(), fields)
+            .map_err(|err| err.with_span(type_def.name.span()))?;
+
+        // Add the type symbol to the current scope so it can be resolved later.
+        self.top_env_mut().types.push((name.to_string(), type_id));
+
+        Ok(())
+    }
+
+    fn compile_interface_decl(&mut self, type_def: &TypeDef, interface_def: &InterfaceDef) -> Result<()> {
+        let name = type_def.name.text.as_str();
+
+        if interface_def.methods.len() > MAX_INTERFACE_METHODS {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("maximum number of methods exceeded in interface type '{name}'"),
+            )
+            .with_span(interface_def.span()));
+        }
+
+        let mut methods: Vec<String> = Vec::with_capacity(interface_def.methods.len());
+
+        for method in &interface_def.methods {
+            let method_name = method.name.text.as_str();
+
+            if methods.iter().any(|m| m == method_name) {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("method '{method_name}' already declared in interface type '{name}'"),
+                )
+                .with_span(method.name.span()));
             }
+
+            methods.push(method_name.to_string());
+        }
+
+        let interface_id = self
+            .chunk
+            .add_interface(name.to_string(), methods)
+            .map_err(|err| err.with_span(type_def.name.span()))?;
+
+        // Add the type symbol to the current scope so it can be resolved later.
+        self.top_env_mut().interfaces.push((name.to_string(), interface_id));
+
+        Ok(())
+    }
+
+    /// Resolve the method signatures of an interface, once all
+    /// the types in scope have been declared.
+    fn compile_interface_sigs(&mut self, type_def: &TypeDef, interface_def: &InterfaceDef) -> Result<()> {
+        let interface_id = self.resolve_interface(&type_def.name.text).expect("interface must be declared");
+
+        let mut sigs = Vec::with_capacity(interface_def.methods.len());
+
+        for method in &interface_def.methods {
+            // The receiver of a method is its first argument.
+            let args = std::iter::once(Ok(Ty::Interface(interface_id)))
+                .chain(method.args.pairs.iter().map(|pair| self.resolve_type(&pair.item.ty)))
+                .collect::<Result<Vec<_>>>()?;
+
+            let rtn = match &method.rtn {
+                Some(rtn) => self.resolve_type(&rtn.ty)?,
+                None => Ty::Void,
+            };
+
+            sigs.push(FuncSig { args, rtn });
         }
 
+        self.interface_sigs.insert(interface_id, sigs);
+
         Ok(())
     }
 
@@ -424,16 +963,24 @@ impl BytecodeCodegen {
                 DefStmt::Func(func) => {
                     self.compile_func_body(func)?;
                 }
-                DefStmt::Return => {
-                    self.compile_return(None)?;
+                DefStmt::Return(keyword) => {
+                    self.compile_return(keyword.span(), None)?;
                 }
-                DefStmt::Return1(ret) => {
-                    self.compile_return(Some(ret))?;
+                DefStmt::Return1(keyword, ret) => {
+                    self.compile_return(keyword.span(), Some(ret))?;
                 }
                 DefStmt::Simple(stmt) => {
                     // FIXME: Remove comment when `force_multiline_blocks` is stabilised: https://github.com/rust-lang/rustfmt/issues/3374
                     match stmt {
                         SimpleStmt::If(stmt) => self.compile_if_stmt(stmt)?,
+                        SimpleStmt::While(stmt) => self.compile_while_stmt(stmt)?,
+                        SimpleStmt::For(stmt) => self.compile_for_stmt(stmt)?,
+                        SimpleStmt::Break(keyword) => {
+                            self.compile_loop_jump(keyword, |loop_env| &mut loop_env.breaks)?
+                        }
+                        SimpleStmt::Continue(keyword) => {
+                            self.compile_loop_jump(keyword, |loop_env| &mut loop_env.continues)?
+                        }
                         SimpleStmt::Expr(expr) => {
                             self.compile_expr(expr)?;
 
@@ -448,54 +995,135 @@ impl BytecodeCodegen {
                             // TODO: When expressions return type `void` then nothing will be left on the stack.
                             self.top_env_mut().bytecode.write_simple(opcodes::POP)?;
                         }
-                        _ => todo!("{stmt:?}"),
+                        SimpleStmt::Unknown(span) => return Err(syntax_error(*span)),
                     }
                 }
-                DefStmt::Var(var_def) => {
-                    // Local variables must have reserved stack positions
-                    // from the declaration pass.
-                    let local_id =
-                        self.top_env_mut()
-                            .resolve_local(&var_def.name.text)
-                            .ok_or_else(|| CompileError {
-                                message: format!("local variable '{}' is not defined", var_def.name.text),
-                                kind: ErrorKind::Compiler,
-                            })?;
-
-                    // Evaluating the expression will leave a result on the stack.
-                    // This result will have to be moved into the local variable's slot
-                    // so other instructions can find it.
-                    self.compile_expr(&var_def.rhs)?;
-
-                    // Store the value at the top of the stack into the
-                    // the stack slot belonging to the local variable.
-                    self.top_env_mut().bytecode.write_k(opcodes::STORE_LOCAL, local_id.into())?;
-                }
-                _ => todo!(),
+                DefStmt::Var(var_def) => self.compile_var_def(var_def)?,
+                // Types have no runtime code, and were declared with the prototypes.
+                DefStmt::Type(_) => {}
             }
         }
 
         Ok(())
     }
 
+    fn compile_var_def(&mut self, var_def: &VarDef) -> Result<()> {
+        let declared = var_def.ty.as_ref().map(|ty| self.resolve_type(ty)).transpose()?;
+
+        // Evaluating the expression will leave a result on the stack.
+        // This result will have to be moved into the local variable's slot
+        // so other instructions can find it.
+        let ty = match (&var_def.rhs, declared) {
+            (Some(rhs), _) => {
+                let ty = self.compile_expr_as(rhs, declared.unwrap_or(Ty::Unknown))?;
+
+                if ty == Ty::Void {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        format!("cannot assign void value to local variable '{}'", var_def.name.text),
+                    )
+                    .with_span(var_def.name.span()));
+                }
+
+                match declared {
+                    Some(declared) if !declared.accepts(ty) => {
+                        return Err(CompileError::new(
+                            ErrorKind::Compiler,
+                            format!(
+                                "mismatched types: expected {}, found {}",
+                                self.ty_name(declared),
+                                self.ty_name(ty)
+                            ),
+                        )
+                        .with_span(rhs.span()));
+                    }
+                    // The annotation takes precedence over the inferred type.
+                    Some(declared) => declared,
+                    None => ty,
+                }
+            }
+            (None, Some(declared)) => {
+                self.compile_zero_value(declared).map_err(|err| err.with_span(var_def.span))?;
+                declared
+            }
+            (None, None) => unreachable!("parser ensures variable has a type or an initializer"),
+        };
+
+        // The local variable is declared after its initializer, so the
+        // expression can't refer to the variable being defined.
+        let local_id = self
+            .top_env_mut()
+            .insert_local(&var_def.name.text, ty)
+            .map_err(|err| err.with_span(var_def.name.span()))?;
+
+        // Store the value at the top of the stack into the
+        // the stack slot belonging to the local variable.
+        self.top_env_mut().bytecode.write_k(opcodes::STORE_LOCAL, local_id.into())?;
+
+        Ok(())
+    }
+
+    /// Push the value a variable of the given type
+    /// starts out with when it has no initializer.
+    fn compile_zero_value(&mut self, ty: Ty) -> Result<()> {
+        let env = self.top_env_mut();
+
+        let konst = match ty {
+            Ty::I32 => {
+                env.bytecode.write_a(opcodes::PUSH_CONST_IMM, 0)?;
+                return Ok(());
+            }
+            Ty::Str => {
+                let index = env.add_string("")?;
+                env.bytecode.write_k(opcodes::PUSH_STR, index as u32)?;
+                return Ok(());
+            }
+            Ty::Bool => (ConstValue::Bool(false), opcodes::PUSH_CONST_BOOL),
+            Ty::I64 => (ConstValue::I64(0), opcodes::PUSH_CONST_I64),
+            Ty::F32 => (ConstValue::F32(0.0), opcodes::PUSH_CONST_F32),
+            Ty::F64 => (ConstValue::F64(0.0), opcodes::PUSH_CONST_F64),
+            // Fields of new objects are zeroed.
+            Ty::Struct(type_id) => {
+                env.bytecode.write_k(opcodes::NEW_OBJ, type_id.to_u32())?;
+                return Ok(());
+            }
+            Ty::Interface(_) | Ty::Func(_) | Ty::Void | Ty::Unknown => {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("type {} has no zero value", self.ty_name(ty)),
+                ));
+            }
+        };
+
+        let (konst, opcode) = konst;
+        let index = env.add_constant(konst)?;
+        env.bytecode.write_k(opcode, index)?;
+
+        Ok(())
+    }
+
     fn compile_func_prototype(&mut self, func: &vuur_parse::func::FuncDef) -> Result<()> {
         let name = func.name.text.as_str();
 
-        // TODO: Receiver
+        // Methods live in their type's method table, rather than in a scope.
+        if let Some(receiver) = &func.receiver {
+            return self.compile_method_prototype(func, receiver);
+        }
 
         // Use name to declare symbol and check for uniqueness in namespace.
         self.top_env_mut().name = Some(name.to_owned());
 
         // TODO: Signature (parameters and return type)
         // TODO: Check name + sig uniqueness
-        let _func_id = match self.resolve_func(name) {
+        match self.resolve_func(name) {
             // When the symbol already exists in the current scope, then
             // a name conflict has occurred.
             Ok(symbol) if symbol.is_local() => {
-                return Err(CompileError {
-                    message: format!("local function with name '{}' already declared", name),
-                    kind: ErrorKind::Compiler,
-                });
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("local function with name '{}' already declared", name),
+                )
+                .with_span(func.name.span()));
             }
             // When the symbol exists in the global scope, we can shadow it.
             Ok(_) | Err(_) => {
@@ -504,218 +1132,1282 @@ impl BytecodeCodegen {
 
                 // Add the function symbol to the current scope so it can be resolved later.
                 self.top_env_mut().funcs.push((name.to_string(), func_id));
+
+                // Declare the signature so calls can be checked before the body is compiled.
+                let sig = self.compile_func_sig(func)?;
+                self.sigs.insert(func_id, sig);
             }
         };
 
-        // TODO: Declare function type using signature
-
         Ok(())
     }
 
-    fn compile_func_body(&mut self, func: &vuur_parse::func::FuncDef) -> Result<()> {
-        // Function declaration should have been added to the lookup table
-        // in a previous pass.
-        let symbol = self.resolve_func(func.name.text.as_str())?;
-        assert!(
-            symbol.is_local(),
-            "function body can only be compiled in the scope where it was declared"
-        );
+    fn compile_method_prototype(&mut self, func: &vuur_parse::func::FuncDef, receiver: &Receiver) -> Result<()> {
+        let type_id = self.resolve_receiver(receiver)?;
 
-        let func_id = symbol.local().unwrap();
-        self.push_func(func_id);
+        // Objects are shared, so a receiver taken by value would be
+        // changed in place as well. Only pointer receivers say so.
+        if !receiver.is_ptr {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("method receiver must be a pointer, like '*{}'", receiver.ty.text),
+            )
+            .with_span(receiver.span()));
+        }
 
-        // Declare function arguments as local variables.
-        // TODO: Function receiver
-        let env = self.top_env_mut();
-        env.arity = func.args.pairs.len() as u8;
+        // Add a function stub to the chunk so we can reserve a function ID.
+        let func_id = self.chunk.add_func_stub();
+        self.chunk
+            .add_method(type_id, func.name.text.to_string(), func_id)
+            .map_err(|err| err.with_span(func.name.span()))?;
 
-        for arg_pair in func.args.pairs.iter() {
-            let arg = &arg_pair.item;
-            env.locals.push(arg.name.text.to_string());
-        }
+        let sig = self.compile_func_sig(func)?;
+        self.check_operator_sig(func, &sig)?;
+        self.sigs.insert(func_id, sig);
 
-        self.compile_body(&func.body.stmts)?;
+        Ok(())
+    }
 
-        // TODO: Check return expr type against signature return type.
-        // Ensure the function always returns.
-        if let Some(instruction) = self.top_env_mut().bytecode.iter().last() {
-            let last_opcode = decode_opcode(*instruction);
-            if last_opcode != opcodes::RETURN {
-                self.top_env_mut().bytecode.write_simple(opcodes::RETURN)?;
-            }
+    /// Methods named after an operator must match the shape of the operator,
+    /// so they can be called when the operator is applied to the type.
+    fn check_operator_sig(&self, func: &vuur_parse::func::FuncDef, sig: &FuncSig) -> Result<()> {
+        let name = func.name.text.as_str();
+        let arity = match operator_arity(name) {
+            Some(arity) => arity,
+            None => return Ok(()),
+        };
+
+        // The receiver is not counted.
+        let arg_count = sig.args.len() - 1;
+        if arg_count != arity {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!(
+                    "operator method '{name}' takes {arity} {}, but {arg_count} {} declared",
+                    plural(arity, "argument", "arguments"),
+                    plural(arg_count, "was", "were")
+                ),
+            )
+            .with_span(func.name.span()));
         }
 
-        // Add function declaration to its parent's scope.
-        let func_id = self.finish_func()?;
-        self.top_env_mut().funcs.push((func.name.text.to_string(), func_id)); // parent
+        if method_operator(name).is_some_and(is_comparison) && !Ty::Bool.accepts(sig.rtn) {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!(
+                    "comparison operator method '{name}' must return bool, found {}",
+                    self.ty_name(sig.rtn)
+                ),
+            )
+            .with_span(func.name.span()));
+        }
 
         Ok(())
     }
 
-    fn compile_if_stmt(&mut self, stmt: &IfStmt) -> Result<()> {
-        self.compile_expr(&stmt.cond)?;
-
-        // If conditional
-        let stub_addr = {
-            let env = self.top_env_mut();
-
-            // OPTI: Check the comparison instruction of the conditional
-            //       expression that was just compiled, and merge it into
-            //       a single cmp+skip instruction.
-            match env.bytecode.last().copied().map(decode_opcode) {
-                Some(opcodes::EQ_I32) => {
-                    env.bytecode.patch_simple(env.prev_addr(), opcodes::SKIP_EQ_I32)?;
-                }
-                Some(_) | None => {
-                    // Skip next instruction if boolean expression evaluates to true.
-                    env.bytecode.write_simple(opcodes::SKIP_1)?;
-                }
-            }
+    /// Resolve the type that a method is declared on.
+    fn resolve_receiver(&self, receiver: &Receiver) -> Result<TypeId> {
+        match self.resolve_type_name(&receiver.ty)? {
+            Ty::Struct(type_id) => Ok(type_id),
+            ty => Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!(
+                    "methods can only be declared on struct types, found {}",
+                    self.ty_name(ty)
+                ),
+            )
+            .with_span(receiver.ty.span())),
+        }
+    }
 
-            // Stub to jump to false case.
-            env.bytecode.write_simple(opcodes::NOOP)?
+    /// The receiver of a method is its first argument.
+    fn compile_func_sig(&mut self, func: &vuur_parse::func::FuncDef) -> Result<FuncSig> {
+        let receiver = func
+            .receiver
+            .as_ref()
+            .map(|receiver| self.resolve_receiver(receiver).map(Ty::Struct));
+
+        let args = receiver
+            .into_iter()
+            .chain(func.args.pairs.iter().map(|pair| self.resolve_type(&pair.item.ty)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let rtn = match &func.rtn {
+            Some(rtn) => self.resolve_type(&rtn.ty)?,
+            None => Ty::Void,
         };
 
+        Ok(FuncSig { args, rtn })
+    }
+
+    fn compile_func_body(&mut self, func: &vuur_parse::func::FuncDef) -> Result<()> {
+        if func.receiver.is_none() && self.funcs.len() > 1 {
+            return self.compile_nested_func(func);
+        }
+
+        // Function declaration should have been added to the lookup table
+        // in a previous pass.
+        let func_id = match &func.receiver {
+            Some(receiver) => {
+                let type_id = self.resolve_receiver(receiver)?;
+                self.chunk
+                    .type_by_id(type_id)
+                    .and_then(|type_def| type_def.method(&func.name.text))
+                    .map(|method| method.func_id)
+                    .expect("method must be declared")
+            }
+            None => {
+                let symbol = self.resolve_func(func.name.text.as_str())?;
+                assert!(
+                    symbol.is_local(),
+                    "function body can only be compiled in the scope where it was declared"
+                );
+                symbol.local().unwrap()
+            }
+        };
+
+        let sig = self.sigs.get(&func_id).cloned().expect("function signature must be declared");
+        let (func_id, _) =
+            self.compile_func_scope(func_id, sig, func.receiver.as_ref(), &func.args, &func.body.stmts)?;
+
+        // Add function declaration to its parent's scope.
+        if func.receiver.is_none() {
+            self.top_env_mut().funcs.push((func.name.text.to_string(), func_id));
+        }
+
+        Ok(())
+    }
+
+    /// Compile a function declared in the body of another function.
+    ///
+    /// The function is a closure stored in a local variable, which is declared
+    /// before the body is compiled so the function can call itself.
+    fn compile_nested_func(&mut self, func: &vuur_parse::func::FuncDef) -> Result<()> {
+        let sig = self.compile_func_sig(func)?;
+        let ty = self.func_ty(sig.clone());
+        let local_id = self
+            .top_env_mut()
+            .insert_local(&func.name.text, ty)
+            .map_err(|err| err.with_span(func.name.span()))?;
+
+        let func_id = self.chunk.add_func_stub();
+        self.sigs.insert(func_id, sig.clone());
+        let (func_id, upvalues) = self.compile_func_scope(func_id, sig, None, &func.args, &func.body.stmts)?;
+        self.compile_closure(func_id, &upvalues, func.name.span())?;
+
+        self.top_env_mut().bytecode.write_k(opcodes::STORE_LOCAL, local_id.0)?;
+
+        Ok(())
+    }
+
+    /// Compile the arguments and body of a function into its own
+    /// environment, and emit it into the chunk.
+    ///
+    /// Returns the variables the function captured, when it's a closure.
+    fn compile_func_scope(
+        &mut self,
+        func_id: FuncId,
+        sig: FuncSig,
+        receiver: Option<&Receiver>,
+        args: &Delimited<FuncArg, Separator>,
+        body: &[DefStmt],
+    ) -> Result<(FuncId, Vec<Capture>)> {
+        // Functions nested in other functions are created at runtime.
+        let is_closure = receiver.is_none() && self.funcs.len() > 1;
+        self.push_func(func_id);
+
+        // Declare function arguments as local variables,
+        // starting with the receiver of a method.
+        let env = self.top_env_mut();
+        env.arity = sig.args.len() as u8;
+        env.rtn = sig.rtn;
+        env.is_closure = is_closure;
+
+        let mut arg_tys = sig.args.into_iter();
+        if let Some(receiver) = receiver {
+            let ty = arg_tys.next().expect("method signature must include receiver");
+            env.insert_local(receiver.name_text(), ty)
+                .map_err(|err| err.with_span(receiver.span()))?;
+        }
+
+        for (arg_pair, ty) in args.pairs.iter().zip(arg_tys) {
+            let arg = &arg_pair.item;
+            env.insert_local(&arg.name.text, ty)
+                .map_err(|err| err.with_span(arg.name.span()))?;
+        }
+
+        // The body is a scope nested in the parameters, so its locals can shadow them.
+        self.top_env_mut().begin_scope();
+        self.compile_body(body)?;
+        self.top_env_mut().end_scope()?;
+
+        // Ensure the function always returns.
+        if let Some(instruction) = self.top_env_mut().bytecode.iter().last() {
+            let last_opcode = decode_opcode(*instruction);
+            if last_opcode != opcodes::RETURN {
+                self.top_env_mut().bytecode.write_simple(opcodes::RETURN)?;
+            }
+        }
+
+        let upvalues = std::mem::take(&mut self.top_env_mut().upvalues);
+        let func_id = self.finish_func()?;
+
+        Ok((func_id, upvalues))
+    }
+
+    /// Create a function value, where the span points to its declaration.
+    ///
+    /// A function that captured variables is created as a closure, which
+    /// is followed by an instruction for each variable it captures.
+    fn compile_closure(&mut self, func_id: FuncId, upvalues: &[Capture], span: Span) -> Result<()> {
+        let env = self.top_env_mut();
+        env.mark_source(span);
+
+        if upvalues.is_empty() {
+            env.bytecode.write_k(opcodes::PUSH_FUNC, func_id.to_u32())?;
+            return Ok(());
+        }
+
+        env.bytecode.write_k(opcodes::CLOSURE, func_id.to_u32())?;
+        for capture in upvalues {
+            match *capture {
+                Capture::Local(local_id) => env.bytecode.write_k(opcodes::CAPTURE_LOCAL, local_id)?,
+                Capture::Upvalue(upvalue) => env.bytecode.write_k(opcodes::CAPTURE_UPVALUE, upvalue)?,
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Compile an anonymous function into its own function in the
+    /// chunk, leaving the function on the stack as a value.
+    fn compile_func_lit(&mut self, lit: &FuncLit) -> Result<Ty> {
+        let args = lit
+            .args
+            .pairs
+            .iter()
+            .map(|pair| self.resolve_type(&pair.item.ty))
+            .collect::<Result<Vec<_>>>()?;
+
+        let rtn = match &lit.rtn {
+            Some(rtn) => self.resolve_type(&rtn.ty)?,
+            None => Ty::Void,
+        };
+
+        let sig = FuncSig { args, rtn };
+        let func_id = self.chunk.add_func_stub();
+        self.sigs.insert(func_id, sig.clone());
+        let (func_id, upvalues) = self.compile_func_scope(func_id, sig.clone(), None, &lit.args, &lit.body.stmts)?;
+        self.compile_closure(func_id, &upvalues, lit.keyword.span())?;
+
+        Ok(self.func_ty(sig))
+    }
+
+    /// Compile the statements of a block in a new lexical scope.
+    fn compile_block(&mut self, stmts: &[DefStmt]) -> Result<()> {
+        self.top_env_mut().begin_scope();
+        self.compile_def_stmts(stmts)?;
+        self.top_env_mut().end_scope()?;
+
+        Ok(())
+    }
+
+    /// Compile a condition, followed by a stub for the jump taken
+    /// when the condition is false.
+    ///
+    /// Returns the address of the stub, to be patched once
+    /// the jump target is known.
+    fn compile_cond(&mut self, cond: &Expr) -> Result<u32> {
+        let ty = self.compile_expr(cond)?;
+
+        // Conditions are never implicitly converted to bool.
+        if !Ty::Bool.accepts(ty) {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("mismatched condition type: expected bool, found {}", self.ty_name(ty)),
+            )
+            .with_span(cond.span()));
+        }
+
+        let env = self.top_env_mut();
+
+        // OPTI: Check the comparison instruction of the conditional
+        //       expression that was just compiled, and merge it into
+        //       a single cmp+skip instruction.
+        match env.bytecode.last().copied().map(decode_opcode) {
+            Some(opcodes::EQ_I32) => {
+                env.bytecode.patch_simple(env.prev_addr(), opcodes::SKIP_EQ_I32)?;
+            }
+            Some(_) | None => {
+                // Skip next instruction if boolean expression evaluates to true.
+                env.bytecode.write_simple(opcodes::SKIP_1)?;
+            }
+        }
+
+        // Stub to jump to false case.
+        Ok(env.bytecode.write_simple(opcodes::NOOP)?)
+    }
+
+    fn compile_if_stmt(&mut self, stmt: &IfStmt) -> Result<()> {
+        // If conditional
+        let stub_addr = self.compile_cond(&stmt.cond)?;
+
         // True block body
-        self.compile_def_stmts(&stmt.body.stmts)?;
+        self.compile_block(&stmt.body.stmts)?;
+
+        // Stub to jump over the else branch, once the true block is done.
+        let end_stub = match &stmt.else_ {
+            ElseStmt::Empty => None,
+            _ => Some(self.top_env_mut().bytecode.write_simple(opcodes::NOOP)?),
+        };
 
         {
             // Target jump when conditional is false.
             let env = self.top_env_mut();
             let after_addr = env.next_addr();
-            env.bytecode
-                .patch_k(stub_addr, opcodes::JUMP, env.bytecode_span.0 + after_addr)?;
+            env.patch_jump(stub_addr, after_addr)?;
         }
 
         match &stmt.else_ {
             ElseStmt::Empty => { /* do nothing */ }
-            ElseStmt::Else { body } => self.compile_def_stmts(&body.stmts)?,
+            ElseStmt::Else { body } => self.compile_block(&body.stmts)?,
             ElseStmt::ElseIf(else_stmt) => self.compile_if_stmt(else_stmt)?,
         }
 
+        if let Some(end_stub) = end_stub {
+            let env = self.top_env_mut();
+            let end_addr = env.next_addr();
+            env.patch_jump(end_stub, end_addr)?;
+        }
+
         Ok(())
     }
 
-    fn compile_expr(&mut self, expr: &Expr) -> Result<()> {
-        match expr {
-            // Number literal becomes a constant with no name.
-            Expr::Num(num) => {
-                let env = self.top_env_mut();
-                let lit = num.value;
+    fn compile_while_stmt(&mut self, stmt: &WhileStmt) -> Result<()> {
+        let start_addr = self.top_env_mut().next_addr();
+        let exit_addr = self.compile_cond(&stmt.cond)?;
 
-                // If the literal is small enough, inline it into an immediate instruction.
-                if lit >= 0 && lit <= INSTRUCTION_A_MAX {
-                    env.bytecode.write_a(opcodes::PUSH_CONST_IMM, lit)?;
-                } else {
-                    // Integer is too large to be inlined into the bytecode.
-                    // Add it to the constant table.
-                    // TODO: Floats
-                    let index = env.add_constant(ConstValue::I32(lit));
+        let env = self.top_env_mut();
+        env.loops.push(LoopEnv {
+            locals: env.locals.len(),
+            ..LoopEnv::default()
+        });
+        self.compile_block(&stmt.body.stmts)?;
 
-                    if index > MAX_CONSTANTS {
-                        return Err(CompileError::new(
-                            ErrorKind::Compiler,
-                            "maximum function constants exceeded",
-                        ));
+        let env = self.top_env_mut();
+        let loop_env = env.loops.pop().expect("loop was pushed before its body");
+
+        // Jump back to evaluate the condition again.
+        env.write_jump(start_addr)?;
+        let end_addr = env.next_addr();
+
+        for addr in std::iter::once(exit_addr).chain(loop_env.breaks) {
+            env.patch_jump(addr, end_addr)?;
+        }
+
+        for addr in loop_env.continues {
+            env.patch_jump(addr, start_addr)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compile a loop over an integer range.
+    ///
+    /// The range's counter and end bound are kept in hidden local
+    /// variables, so iterating doesn't allocate. The counter is
+    /// copied into the loop variable at the start of every iteration.
+    fn compile_for_stmt(&mut self, stmt: &ForStmt) -> Result<()> {
+        let range = match &stmt.iter {
+            Expr::Binary(binary)
+                if matches!(binary.operator.kind, OperatorKind::Range | OperatorKind::RangeExclusive) =>
+            {
+                binary
+            }
+            expr => {
+                let ty = self.compile_expr(expr)?;
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("type {} is not iterable", self.ty_name(ty)),
+                )
+                .with_span(expr.span()));
+            }
+        };
+
+        // Bounds are evaluated once, before the loop starts.
+        for bound in [&range.lhs, &range.rhs] {
+            let ty = self.compile_expr(bound)?;
+            if !Ty::I32.accepts(ty) {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("mismatched range bound type: expected i32, found {}", self.ty_name(ty)),
+                )
+                .with_span(bound.span()));
+            }
+        }
+
+        // The range state and loop variable are only visible to the loop.
+        //
+        // Names of hidden locals can't be spelled in source code, so they never clash.
+        let env = self.top_env_mut();
+        env.begin_scope();
+        let counter = env.insert_local(&format!(".range{}", env.locals.len()), Ty::I32)?;
+        let end = env.insert_local(&format!(".range{}", env.locals.len()), Ty::I32)?;
+        let var = env
+            .insert_local(&stmt.name.text, Ty::I32)
+            .map_err(|err| err.with_span(stmt.name.span()))?;
+
+        env.bytecode.write_k(opcodes::STORE_LOCAL, end.into())?;
+        env.bytecode.write_k(opcodes::STORE_LOCAL, counter.into())?;
+
+        // Skip the loop entirely when the range is empty.
+        let (compare, next) = match range.operator.kind {
+            OperatorKind::Range => (opcodes::LE_I32, opcodes::RANGE_NEXT_INCL_I32),
+            _ => (opcodes::LT_I32, opcodes::RANGE_NEXT_I32),
+        };
+        env.bytecode.write_k(opcodes::LOAD_LOCAL, counter.into())?;
+        env.bytecode.write_k(opcodes::LOAD_LOCAL, end.into())?;
+        env.bytecode.write_simple(compare)?;
+        env.bytecode.write_simple(opcodes::SKIP_1)?;
+        let exit_addr = env.bytecode.write_simple(opcodes::NOOP)?;
+
+        let body_addr = env.next_addr();
+        env.bytecode.write_k(opcodes::LOAD_LOCAL, counter.into())?;
+        env.bytecode.write_k(opcodes::STORE_LOCAL, var.into())?;
+
+        env.loops.push(LoopEnv {
+            locals: env.locals.len(),
+            ..LoopEnv::default()
+        });
+        self.compile_block(&stmt.body.stmts)?;
+
+        let env = self.top_env_mut();
+        let loop_env = env.loops.pop().expect("loop was pushed before its body");
+
+        // Advance the counter, and jump back to the body while the range has values left.
+        //
+        // Every iteration has its own loop variable, so closures capturing it
+        // keep the value from their iteration.
+        let next_addr = env.next_addr();
+        env.close_upvalues(var.0 as usize)?;
+        env.mark_source(range.operator.span());
+        env.bytecode.write_k(next, counter.into())?;
+        env.write_jump(body_addr)?;
+        let end_addr = env.next_addr();
+
+        for addr in std::iter::once(exit_addr).chain(loop_env.breaks) {
+            env.patch_jump(addr, end_addr)?;
+        }
+
+        for addr in loop_env.continues {
+            env.patch_jump(addr, next_addr)?;
+        }
+
+        env.end_scope()?;
+
+        Ok(())
+    }
+
+    /// Compile a `break` or `continue` statement as a jump stub, which
+    /// is added to the stubs selected from the innermost loop.
+    fn compile_loop_jump(&mut self, keyword: &Token, stubs: impl FnOnce(&mut LoopEnv) -> &mut Vec<u32>) -> Result<()> {
+        let env = self.top_env_mut();
+
+        if env.loops.is_empty() {
+            return Err(
+                CompileError::new(ErrorKind::Compiler, format!("'{}' outside of loop", keyword.kind))
+                    .with_span(keyword.span()),
+            );
+        }
+
+        env.mark_source(keyword.span());
+
+        // The jump skips the end of the scopes it leaves.
+        let locals = env.loops.last().expect("loop stack was checked").locals;
+        env.close_upvalues(locals)?;
+
+        let addr = env.bytecode.write_simple(opcodes::NOOP)?;
+        stubs(env.loops.last_mut().expect("loop stack was checked")).push(addr);
+
+        Ok(())
+    }
+
+    /// Compile an expression, leaving its result on the operand stack.
+    ///
+    /// Returns the static type of the result.
+    fn compile_expr(&mut self, expr: &Expr) -> Result<Ty> {
+        let ty = match expr {
+            Expr::Num(num) => self.compile_num(num, num.value)?,
+            Expr::Bool(boolean) => {
+                let env = self.top_env_mut();
+                env.mark_source(boolean.span());
+                let index = env.add_constant(ConstValue::Bool(boolean.value))?;
+                env.bytecode.write_k(opcodes::PUSH_CONST_BOOL, index)?;
+                Ty::Bool
+            }
+            // String literal is stored in the function's string table.
+            Expr::Str(string) => {
+                let env = self.top_env_mut();
+                env.mark_source(string.span());
+                let index = env.add_string(&string.value)?;
+                env.bytecode.write_k(opcodes::PUSH_STR, index as u32)?;
+                Ty::Str
+            }
+            // Interpolated string is built by converting each expression
+            // to a string, and concatenating it with the parts before it.
+            Expr::Interp(interp) => {
+                let mut is_first = true;
+
+                for part in &interp.parts {
+                    match part {
+                        // Empty text between interpolations contributes nothing.
+                        InterpPart::Str(string) if string.value.is_empty() => continue,
+                        InterpPart::Str(string) => {
+                            let env = self.top_env_mut();
+                            env.mark_source(string.span());
+                            let index = env.add_string(&string.value)?;
+                            env.bytecode.write_k(opcodes::PUSH_STR, index as u32)?;
+                        }
+                        InterpPart::Expr(expr) => {
+                            self.compile_expr(expr)?;
+                            self.top_env_mut().bytecode.write_simple(opcodes::TO_STR)?;
+                        }
                     }
 
-                    // Emit
-                    env.bytecode.write_k(opcodes::PUSH_CONST, index as u32)?;
+                    if !is_first {
+                        self.top_env_mut().bytecode.write_simple(opcodes::CONCAT_STR)?;
+                    }
+                    is_first = false;
                 }
+
+                // Interpolations always contain at least one
+                // expression, so the stack has the result.
+                debug_assert!(!is_first, "interpolated string produced no value");
+
+                Ty::Str
             }
             Expr::Unary(unary) => {
-                self.compile_expr(&unary.rhs)?;
+                let ty = self.compile_expr(&unary.rhs)?;
+
+                let opcode = unary_opcode(&unary.operator.kind, ty).ok_or_else(|| {
+                    CompileError::new(
+                        ErrorKind::Compiler,
+                        format!(
+                            "unary operator {:?} cannot be applied to type {}",
+                            unary.operator.kind,
+                            self.ty_name(ty)
+                        ),
+                    )
+                    .with_span(unary.operator.span())
+                })?;
 
-                let scope = self.top_env_mut();
+                let env = self.top_env_mut();
+                env.mark_source(unary.operator.span());
+                env.bytecode.write_simple(opcode)?;
 
-                match unary.operator.kind {
-                    OperatorKind::Neg => {
-                        scope.bytecode.write_simple(opcodes::NEG_I32)?;
+                ty
+            }
+            Expr::Binary(binary) => {
+                let lhs = self.compile_expr(&binary.lhs)?;
+                let rhs = self.compile_expr(&binary.rhs)?;
+
+                // User types overload operators with methods,
+                // while primitives have specialised instructions.
+                if let Some(symbol) = operator_method(&binary.operator.kind) {
+                    if matches!(lhs, Ty::Struct(_)) || matches!(rhs, Ty::Struct(_)) {
+                        return self.compile_operator_call(symbol, binary.operator.span(), lhs, &[rhs]);
                     }
-                    _ => todo!("unary operator kind {:?}", unary.operator.kind),
+                }
+
+                // Primitive operands are never implicitly converted.
+                if lhs != rhs {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        format!(
+                            "mismatched operand types for binary operator {:?}: {} and {}",
+                            binary.operator.kind,
+                            self.ty_name(lhs),
+                            self.ty_name(rhs)
+                        ),
+                    )
+                    .with_span(binary.operator.span()));
+                }
+
+                let opcode = binary_opcode(&binary.operator.kind, lhs).ok_or_else(|| {
+                    CompileError::new(
+                        ErrorKind::Compiler,
+                        format!(
+                            "binary operator {:?} cannot be applied to type {}",
+                            binary.operator.kind,
+                            self.ty_name(lhs)
+                        ),
+                    )
+                    .with_span(binary.operator.span())
+                })?;
+
+                let env = self.top_env_mut();
+                env.mark_source(binary.operator.span());
+                env.bytecode.write_simple(opcode)?;
+
+                if is_comparison(&binary.operator.kind) {
+                    Ty::Bool
+                } else {
+                    lhs
                 }
             }
-            Expr::Binary(binary) => {
-                self.compile_expr(&binary.lhs)?;
-                self.compile_expr(&binary.rhs)?;
-
-                let scope = self.top_env_mut();
-
-                match binary.operator.kind {
-                    OperatorKind::Add => scope.bytecode.write_simple(opcodes::ADD_I32)?,
-                    OperatorKind::Sub => scope.bytecode.write_simple(opcodes::SUB_I32)?,
-                    OperatorKind::Mul => scope.bytecode.write_simple(opcodes::MUL_I32)?,
-                    OperatorKind::Div => scope.bytecode.write_simple(opcodes::DIV_I32)?,
-                    OperatorKind::Equals => scope.bytecode.write_simple(opcodes::EQ_I32)?,
-                    _ => todo!("binary operator kind {:?}", binary.operator.kind),
-                };
+            Expr::Group(group) => self.compile_expr(&group.expr)?,
+            Expr::Assign(assign) => {
+                let symbol = self.resolve_var_ident(&assign.lhs)?;
+                let (load, store, index, local_ty) = var_opcodes(symbol);
+
+                let ty = self.compile_expr_as(&assign.rhs, local_ty)?;
+
+                if !local_ty.accepts(ty) {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        format!(
+                            "mismatched types: cannot assign {} to local variable '{}' of type {}",
+                            self.ty_name(ty),
+                            assign.lhs.text,
+                            self.ty_name(local_ty)
+                        ),
+                    )
+                    .with_span(assign.rhs.span()));
+                }
+
+                // Assignment is an expression, so the stored
+                // value is loaded again as its result.
+                let env = self.top_env_mut();
+                env.mark_source(assign.operator.span());
+                env.bytecode.write_k(store, index)?;
+                env.bytecode.write_k(load, index)?;
+
+                local_ty
             }
-            Expr::Group(group) => {
-                self.compile_expr(&group.expr)?;
+            Expr::NameAccess(access) => self.compile_name_access(&access.ident)?,
+            Expr::MemberAccess(access) => self.compile_member_access(access)?,
+            Expr::MemberAssign(assign) => {
+                let owner = self.compile_member_path(&assign.path)?;
+                let (type_id, field_index, field_ty) = self.resolve_field(owner, &assign.name)?;
+                let ty = self.compile_expr(&assign.rhs)?;
+
+                if !field_ty.accepts(ty) {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        format!(
+                            "mismatched types: cannot assign {} to field '{}' of type {}",
+                            self.ty_name(ty),
+                            assign.name.text,
+                            self.ty_name(field_ty)
+                        ),
+                    )
+                    .with_span(assign.rhs.span()));
+                }
+
+                // Assignment is an expression, so the stored
+                // value is loaded again as its result.
+                let field = encode_field(type_id.to_u32(), field_index);
+                let env = self.top_env_mut();
+                env.mark_source(assign.operator.span());
+                env.bytecode.write_k(opcodes::SET_FIELD, field)?;
+                env.bytecode.write_k(opcodes::GET_FIELD, field)?;
+
+                field_ty
             }
-            Expr::NameAccess(access) => {
-                let local_id =
-                    self.top_env_mut()
-                        .resolve_local(access.ident.text.as_str())
-                        .ok_or_else(|| CompileError {
-                            message: format!("failed to resolve local variable: '{}'", access.ident.text),
-                            kind: ErrorKind::Compiler,
-                        })?;
-                self.top_env_mut().bytecode.write_k(opcodes::LOAD_LOCAL, local_id.0)?;
+            Expr::Index(index) => {
+                let owner = self.compile_expr(&index.lhs)?;
+                self.check_indexable(owner, index.lhs.span())?;
+                let index_ty = self.compile_expr(&index.index)?;
+                self.compile_operator_call("[]", index.span, owner, &[index_ty])?
             }
+            Expr::IndexAssign(assign) => {
+                let owner = self.compile_expr(&assign.lhs)?;
+                self.check_indexable(owner, assign.lhs.span())?;
+                let index_ty = self.compile_expr(&assign.index)?;
+                let ty = self.compile_expr(&assign.rhs)?;
+
+                // The setter decides what the assignment evaluates to.
+                self.compile_operator_call("[]=", assign.operator.span(), owner, &[index_ty, ty])?
+            }
+            Expr::Struct(lit) => self.compile_struct_lit(lit)?,
+            Expr::Func(lit) => self.compile_func_lit(lit)?,
             Expr::Call(call) => {
-                // Caller must prepare arguments on stack
-                for call_arg in &call.args {
-                    match call_arg {
-                        CallArg::Simple(expr) => self.compile_expr(expr)?,
-                        CallArg::Named { .. } => todo!("named call arg"),
-                        CallArg::Block(_) => todo!("block call arg"),
-                    }
-                }
-
                 // TODO: Lookup function by name
                 match &*call.callee {
+                    // A local variable holding a function shadows functions of the same name.
+                    Expr::NameAccess(access) if self.resolve_var(&access.ident.text)?.is_some() => {
+                        self.compile_value_call(&call.callee, &access.ident.text, &call.args)?
+                    }
                     // When the function name is explicitly stated as a string literal,
                     // then the call can simply be statically dispatched.
                     Expr::NameAccess(access) => {
                         let name = &access.ident.text;
-                        match self.resolve_func(name.as_str())? {
+                        let span = access.ident.span();
+                        match self.resolve_func(name.as_str()).map_err(|err| err.with_span(span))? {
                             SymbolScope::Local(func_id) | SymbolScope::Global(func_id) => {
-                                self.top_env_mut().bytecode.write_k(opcodes::CALL, func_id.to_u32())?;
+                                // Arguments take the types of the parameters.
+                                let params = self.sigs.get(&func_id).map(|sig| sig.args.clone()).unwrap_or_default();
+                                let arg_tys = self.compile_call_args(&call.args, &params)?;
+                                let rtn = self
+                                    .check_call_args(name, func_id, &arg_tys, false)
+                                    .map_err(|err| err.with_span(span))?;
+
+                                let env = self.top_env_mut();
+                                env.mark_source(span);
+                                env.bytecode.write_k(opcodes::CALL, func_id.to_u32())?;
+                                rtn
                             }
-                            SymbolScope::NonLocal(_) => todo!("closures"),
+                            SymbolScope::NonLocal(_) => unreachable!("nested functions are resolved as variables"),
                         }
                     }
                     // When the function is namespaced to a struct, the member path needs to be resolved.
-                    Expr::MemberAccess(access) => {
-                        todo!("struct member call: '{access:?}'")
-                    }
+                    Expr::MemberAccess(access) => self.compile_method_call(access, &call.args)?,
                     // When a more complex expression is used as the function name, then
                     // it has to be evaluated at runtime and dispatched dynamically.
-                    expr => {
-                        self.compile_expr(expr)?;
-                        self.top_env_mut().bytecode.write_simple(opcodes::DYN_CALL)?;
-                    }
+                    expr => self.compile_value_call(expr, "<anonymous>", &call.args)?,
                 }
             }
             // Bytecode literal is emitted as is, without any checks.
             Expr::Bytecode(bytecode) => {
                 self.top_env_mut().bytecode.extend(bytecode);
+                Ty::Unknown
             }
-            _ => todo!("{expr:?}"),
+            Expr::Unknown(span) => return Err(syntax_error(*span)),
+        };
+
+        Ok(ty)
+    }
+
+    /// Load a name as a value, which is either a local
+    /// variable or a reference to a declared function.
+    fn compile_name_access(&mut self, ident: &Ident) -> Result<Ty> {
+        if self.resolve_var(ident.text.as_str())?.is_some() {
+            return self.compile_load_var(ident);
         }
 
-        Ok(())
+        match self.resolve_func(ident.text.as_str()) {
+            Ok(SymbolScope::Local(func_id) | SymbolScope::Global(func_id)) => {
+                let sig = self.sigs.get(&func_id).cloned().expect("function signature must be declared");
+
+                let env = self.top_env_mut();
+                env.mark_source(ident.span());
+                env.bytecode.write_k(opcodes::PUSH_FUNC, func_id.to_u32())?;
+
+                Ok(self.func_ty(sig))
+            }
+            Ok(SymbolScope::NonLocal(_)) => unreachable!("nested functions are resolved as variables"),
+            // Report the name as a variable, since that's the common case.
+            Err(_) => self.compile_load_var(ident),
+        }
+    }
+
+    /// Resolve a variable by name, which must be declared.
+    fn resolve_var_ident(&mut self, ident: &Ident) -> Result<SymbolScope<(u32, Ty)>> {
+        self.resolve_var(ident.text.as_str())
+            .map_err(|err| err.with_span(ident.span()))?
+            .ok_or_else(|| {
+                CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("failed to resolve local variable: '{}'", ident.text),
+                )
+                .with_span(ident.span())
+            })
+    }
+
+    /// Load a variable, which is either a local or captured from an enclosing function.
+    fn compile_load_var(&mut self, ident: &Ident) -> Result<Ty> {
+        let symbol = self.resolve_var_ident(ident)?;
+        let (load, _, index, ty) = var_opcodes(symbol);
+
+        let env = self.top_env_mut();
+        env.mark_source(ident.span());
+        env.bytecode.write_k(load, index)?;
+
+        Ok(ty)
+    }
+
+    /// Evaluate the owner of a member, leaving it on the stack.
+    fn compile_member_path(&mut self, path: &MemberPath) -> Result<Ty> {
+        match path {
+            MemberPath::Name(ident) => self.compile_load_var(ident),
+            MemberPath::Path(access) => self.compile_member_access(access),
+        }
+    }
+
+    fn compile_member_access(&mut self, access: &MemberAccess) -> Result<Ty> {
+        let owner = self.compile_member_path(&access.path)?;
+        let (type_id, field_index, field_ty) = self.resolve_field(owner, &access.name)?;
+
+        let env = self.top_env_mut();
+        env.mark_source(access.name.span());
+        env.bytecode
+            .write_k(opcodes::GET_FIELD, encode_field(type_id.to_u32(), field_index))?;
+
+        Ok(field_ty)
+    }
+
+    /// Construct a new object, with the fields that
+    /// aren't given a value left zeroed.
+    fn compile_struct_lit(&mut self, lit: &StructLit) -> Result<Ty> {
+        let ty = self.resolve_type_name(&lit.name)?;
+        let Ty::Struct(type_id) = ty else {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("type {} is not a struct type", self.ty_name(ty)),
+            )
+            .with_span(lit.name.span()));
+        };
+
+        let env = self.top_env_mut();
+        env.mark_source(lit.name.span());
+        env.bytecode.write_k(opcodes::NEW_OBJ, type_id.to_u32())?;
+
+        let mut initialized = Vec::with_capacity(lit.fields.len());
+
+        for field in &lit.fields {
+            let (_, field_index, field_ty) = self.resolve_field(ty, &field.name)?;
+
+            if initialized.contains(&field_index) {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("field '{}' specified more than once", field.name.text),
+                )
+                .with_span(field.name.span()));
+            }
+            initialized.push(field_index);
+
+            let rhs = self.compile_expr(&field.rhs)?;
+            if !field_ty.accepts(rhs) {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!(
+                        "mismatched types: expected {}, found {}",
+                        self.ty_name(field_ty),
+                        self.ty_name(rhs)
+                    ),
+                )
+                .with_span(field.rhs.span()));
+            }
+
+            let env = self.top_env_mut();
+            env.mark_source(field.name.span());
+            env.bytecode
+                .write_k(opcodes::SET_FIELD, encode_field(type_id.to_u32(), field_index))?;
+        }
+
+        Ok(ty)
+    }
+
+    /// Find the field of a struct type by name, returning the
+    /// declaring type's ID, the field's index and its type.
+    fn resolve_field(&self, owner: Ty, name: &Ident) -> Result<(TypeId, u32, Ty)> {
+        let type_def = match owner {
+            Ty::Struct(type_id) => self.chunk.type_by_id(type_id),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("type {} has no fields", self.ty_name(owner)),
+            )
+            .with_span(name.span())
+        })?;
+
+        type_def
+            .fields
+            .iter()
+            .position(|field| field.name == name.text)
+            .map(|index| (type_def.id, index as u32, Ty::from(type_def.fields[index].ty)))
+            .ok_or_else(|| {
+                CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("type {} has no field named '{}'", type_def.name, name.text),
+                )
+                .with_span(name.span())
+            })
+    }
+
+    /// Number literal becomes a constant with no name.
+    fn compile_num(&mut self, num: &NumLit, value: NumValue) -> Result<Ty> {
+        let env = self.top_env_mut();
+        env.mark_source(num.span());
+
+        let (konst, opcode, ty) = match value {
+            // If the literal is small enough, inline it into an immediate instruction.
+            NumValue::I32(lit @ 0..=INSTRUCTION_A_MAX) => {
+                env.bytecode.write_a(opcodes::PUSH_CONST_IMM, lit)?;
+                return Ok(Ty::I32);
+            }
+            // Otherwise the value is too large to be inlined into
+            // the bytecode, and is added to the constant table.
+            NumValue::I32(lit) => (ConstValue::I32(lit), opcodes::PUSH_CONST, Ty::I32),
+            NumValue::I64(lit) => (ConstValue::I64(lit), opcodes::PUSH_CONST_I64, Ty::I64),
+            NumValue::F32(lit) => (ConstValue::F32(lit), opcodes::PUSH_CONST_F32, Ty::F32),
+            NumValue::F64(lit) => (ConstValue::F64(lit), opcodes::PUSH_CONST_F64, Ty::F64),
+        };
+
+        let index = env.add_constant(konst)?;
+        env.bytecode.write_k(opcode, index)?;
+
+        Ok(ty)
+    }
+
+    /// Compile an expression, converting its result to the expected type
+    /// when the conversion is implicit. Returns the type of the result.
+    ///
+    /// A number literal without a suffix takes the expected type, when
+    /// it's the same kind of number. An unknown expected type leaves
+    /// the result as is.
+    fn compile_expr_as(&mut self, expr: &Expr, expected: Ty) -> Result<Ty> {
+        let ty = match (expr, num_ty(expected)) {
+            (Expr::Num(num), Some(num_ty)) => {
+                let value = parse_number_as(&num.text, num_ty)
+                    .map_err(|kind| CompileError::new(ErrorKind::Compiler, kind.to_string()).with_span(num.span()))?;
+                self.compile_num(num, value)?
+            }
+            _ => self.compile_expr(expr)?,
+        };
+
+        match (ty, expected) {
+            // Structure values are converted to the interfaces they satisfy.
+            (Ty::Struct(type_id), Ty::Interface(interface_id)) => {
+                let vtable_id = self
+                    .resolve_vtable(type_id, interface_id)
+                    .map_err(|err| err.with_span(expr.span()))?;
+                self.top_env_mut().bytecode.write_k(opcodes::TO_IFACE, vtable_id)?;
+                Ok(expected)
+            }
+            _ => Ok(ty),
+        }
+    }
+
+    /// Compile call arguments, which the caller prepares on the stack.
+    ///
+    /// Arguments are converted to the types of the parameters where
+    /// needed. The number of arguments is checked by the caller.
+    fn compile_call_args(&mut self, args: &[CallArg], params: &[Ty]) -> Result<Vec<Ty>> {
+        let mut arg_tys = Vec::with_capacity(args.len());
+        for (index, call_arg) in args.iter().enumerate() {
+            let expected = params.get(index).copied().unwrap_or(Ty::Unknown);
+            match call_arg {
+                CallArg::Simple(expr) => arg_tys.push(self.compile_expr_as(expr, expected)?),
+                CallArg::Named { .. } => todo!("named call arg"),
+                CallArg::Block(_) => todo!("block call arg"),
+            }
+        }
+
+        Ok(arg_tys)
+    }
+
+    /// Compile a method call, which is statically dispatched through
+    /// the method table of the receiver's type.
+    ///
+    /// The receiver is passed as the first argument.
+    fn compile_method_call(&mut self, access: &MemberAccess, args: &[CallArg]) -> Result<Ty> {
+        let receiver = self.compile_member_path(&access.path)?;
+        if let Ty::Interface(interface_id) = receiver {
+            return self.compile_interface_call(interface_id, &access.name, args);
+        }
+
+        let (name, func_id) = self.resolve_method(receiver, &access.name)?;
+        let params = self.sigs.get(&func_id).map(|sig| sig.args[1..].to_vec()).unwrap_or_default();
+        let arg_tys = self.compile_call_args(args, &params)?;
+
+        let span = access.name.span();
+        let rtn = self
+            .check_call_args(&name, func_id, &arg_tys, true)
+            .map_err(|err| err.with_span(span))?;
+
+        let env = self.top_env_mut();
+        env.mark_source(span);
+        env.bytecode.write_k(opcodes::CALL, func_id.to_u32())?;
+
+        Ok(rtn)
+    }
+
+    /// Compile a call through a function value, which is
+    /// dispatched at runtime.
+    ///
+    /// The function is evaluated first, and takes the place of
+    /// the receiver below the arguments.
+    fn compile_value_call(&mut self, callee: &Expr, name: &str, args: &[CallArg]) -> Result<Ty> {
+        let ty = self.compile_expr(callee)?;
+        let sig = match ty {
+            Ty::Func(func_ty_id) => self.func_tys[func_ty_id.to_usize()].clone(),
+            _ => {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("type {} is not callable", self.ty_name(ty)),
+                )
+                .with_span(callee.span()));
+            }
+        };
+
+        let arg_tys = self.compile_call_args(args, &sig.args)?;
+
+        let span = callee.span();
+        let rtn = self
+            .check_sig_args(name, &sig, &arg_tys, false)
+            .map_err(|err| err.with_span(span))?;
+
+        let env = self.top_env_mut();
+        env.mark_source(span);
+        env.bytecode
+            .write_k(opcodes::DYN_CALL, encode_dyn_call(sig.args.len() as u32 + 1, 0))?;
+
+        Ok(rtn)
+    }
+
+    /// Compile a method call on an interface value, which is dispatched
+    /// at runtime through the vtable the value was converted with.
+    ///
+    /// The receiver is passed as the first argument.
+    fn compile_interface_call(&mut self, interface_id: InterfaceId, name: &Ident, args: &[CallArg]) -> Result<Ty> {
+        let interface_def = self.chunk.interface_by_id(interface_id).expect("interface must be declared");
+        let slot = interface_def.method_slot(&name.text).ok_or_else(|| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("interface {} has no method named '{}'", interface_def.name, name.text),
+            )
+            .with_span(name.span())
+        })?;
+        let qualified = format!("{}.{}", interface_def.name, name.text);
+        let sig = self.interface_sigs[&interface_id][slot].clone();
+
+        let arg_tys = self.compile_call_args(args, &sig.args[1..])?;
+
+        let span = name.span();
+        let rtn = self
+            .check_sig_args(&qualified, &sig, &arg_tys, true)
+            .map_err(|err| err.with_span(span))?;
+
+        let env = self.top_env_mut();
+        env.mark_source(span);
+        env.bytecode
+            .write_k(opcodes::DYN_CALL, encode_dyn_call(sig.args.len() as u32, slot as u32))?;
+
+        Ok(rtn)
+    }
+
+    /// Find the methods a structure type uses to satisfy an interface,
+    /// returning the index of the vtable that lists them.
+    fn resolve_vtable(&mut self, type_id: TypeId, interface_id: InterfaceId) -> Result<u32> {
+        let type_def = self.chunk.type_by_id(type_id).expect("type must be declared");
+        let interface_def = self.chunk.interface_by_id(interface_id).expect("interface must be declared");
+        let expected_sigs = &self.interface_sigs[&interface_id];
+
+        let mut funcs = Vec::with_capacity(interface_def.methods.len());
+
+        for (method_name, expected) in interface_def.methods.iter().zip(expected_sigs) {
+            let method = type_def.method(method_name).ok_or_else(|| {
+                CompileError::new(
+                    ErrorKind::Compiler,
+                    format!(
+                        "type {} does not satisfy interface {}: missing method '{method_name}'",
+                        type_def.name, interface_def.name
+                    ),
+                )
+            })?;
+
+            // Receivers differ, so only the remaining arguments are compared.
+            let sig = &self.sigs[&method.func_id];
+            if sig.args[1..] != expected.args[1..] || sig.rtn != expected.rtn {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!(
+                        "type {} does not satisfy interface {}: method '{method_name}' has signature {}, expected {}",
+                        type_def.name,
+                        interface_def.name,
+                        self.sig_text(sig),
+                        self.sig_text(expected),
+                    ),
+                ));
+            }
+
+            funcs.push(method.func_id);
+        }
+
+        self.chunk.add_vtable(type_id, interface_id, funcs)
+    }
+
+    /// Method signature as it would be written in source code, without the receiver.
+    fn sig_text(&self, sig: &FuncSig) -> String {
+        let args = sig.args[1..].iter().map(|ty| self.ty_name(*ty)).collect::<Vec<_>>();
+        match sig.rtn {
+            Ty::Void => format!("({})", args.join(", ")),
+            rtn => format!("({}) -> {}", args.join(", "), self.ty_name(rtn)),
+        }
+    }
+
+    /// Find the method of a struct type by name, returning its
+    /// qualified name, like `Vec.Magnitude`, and its function ID.
+    fn resolve_method(&self, owner: Ty, name: &Ident) -> Result<(String, FuncId)> {
+        let type_def = match owner {
+            Ty::Struct(type_id) => self.chunk.type_by_id(type_id),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("type {} has no methods", self.ty_name(owner)),
+            )
+            .with_span(name.span())
+        })?;
+
+        match type_def.method(&name.text) {
+            Some(method) => Ok((format!("{}.{}", type_def.name, method.name), method.func_id)),
+            None => {
+                let message = if type_def.fields.iter().any(|field| field.name == name.text) {
+                    format!("field '{}' of type {} is not a method", name.text, type_def.name)
+                } else {
+                    format!("type {} has no method named '{}'", type_def.name, name.text)
+                };
+                Err(CompileError::new(ErrorKind::Compiler, message).with_span(name.span()))
+            }
+        }
+    }
+
+    /// Compile a call to the method overloading an operator, where the
+    /// receiver and the other operands have already been compiled.
+    ///
+    /// The span points to the operator.
+    fn compile_operator_call(&mut self, symbol: &str, span: Span, owner: Ty, arg_tys: &[Ty]) -> Result<Ty> {
+        let type_def = match owner {
+            Ty::Struct(type_id) => self.chunk.type_by_id(type_id),
+            // The method can't be looked up without knowing which type to look in.
+            Ty::Unknown => {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!("cannot resolve operator '{symbol}' for operand of unknown type"),
+                )
+                .with_span(span));
+            }
+            _ => None,
+        }
+        .ok_or_else(|| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("type {} has no operator method '{symbol}'", self.ty_name(owner)),
+            )
+            .with_span(span)
+        })?;
+
+        let method = type_def.method(symbol).ok_or_else(|| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("type {} has no operator method '{symbol}'", type_def.name),
+            )
+            .with_span(span)
+        })?;
+
+        let name = format!("{}.{}", type_def.name, symbol);
+        let func_id = method.func_id;
+        let rtn = self
+            .check_call_args(&name, func_id, arg_tys, true)
+            .map_err(|err| err.with_span(span))?;
+
+        let env = self.top_env_mut();
+        env.mark_source(span);
+        env.bytecode.write_k(opcodes::CALL, func_id.to_u32())?;
+
+        Ok(rtn)
+    }
+
+    /// Only user types can be subscripted, through their `[]` and `[]=` methods.
+    fn check_indexable(&self, ty: Ty, span: Span) -> Result<()> {
+        match ty {
+            Ty::Struct(_) | Ty::Unknown => Ok(()),
+            _ => Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!("type {} cannot be indexed", self.ty_name(ty)),
+            )
+            .with_span(span)),
+        }
+    }
+
+    /// Check the types of call arguments against the signature
+    /// of the callee, returning the callee's return type.
+    ///
+    /// The receiver of a method call is not counted as an argument,
+    /// so the given argument types must exclude it.
+    fn check_call_args(&self, name: &str, func_id: FuncId, arg_tys: &[Ty], is_method: bool) -> Result<Ty> {
+        let sig = self.sigs.get(&func_id).ok_or_else(|| {
+            CompileError::new(
+                ErrorKind::Compiler,
+                format!("function '{name}' has no declared signature"),
+            )
+        })?;
+
+        self.check_sig_args(name, sig, arg_tys, is_method)
     }
 
-    fn compile_return(&mut self, expr: Option<&Expr>) -> Result<()> {
+    /// Check the types of call arguments against a signature,
+    /// returning the return type of the signature.
+    fn check_sig_args(&self, name: &str, sig: &FuncSig, arg_tys: &[Ty], is_method: bool) -> Result<Ty> {
+        let params = if is_method { &sig.args[1..] } else { &sig.args[..] };
+
+        if params.len() != arg_tys.len() {
+            return Err(CompileError::new(
+                ErrorKind::Compiler,
+                format!(
+                    "function '{name}' takes {} {}, but {} {} given",
+                    params.len(),
+                    plural(params.len(), "argument", "arguments"),
+                    arg_tys.len(),
+                    plural(arg_tys.len(), "was", "were")
+                ),
+            ));
+        }
+
+        for (index, (expected, actual)) in params.iter().zip(arg_tys).enumerate() {
+            if !expected.accepts(*actual) {
+                return Err(CompileError::new(
+                    ErrorKind::Compiler,
+                    format!(
+                        "argument {index} of function '{name}' expects type {}, found {}",
+                        self.ty_name(*expected),
+                        self.ty_name(*actual)
+                    ),
+                ));
+            }
+        }
+
+        Ok(sig.rtn)
+    }
+
+    /// Compile a return statement, where the span
+    /// points to the `return` keyword.
+    fn compile_return(&mut self, span: Span, expr: Option<&Expr>) -> Result<()> {
+        let expected = self.top_env_mut().rtn;
+
         match expr {
             Some(expr) => {
                 // TODO: Support multiple return values
-                self.compile_expr(expr)?;
+                let ty = self.compile_expr_as(expr, expected)?;
+
+                if expected == Ty::Void || !expected.accepts(ty) {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        format!(
+                            "mismatched return type: expected {}, found {}",
+                            self.ty_name(expected),
+                            self.ty_name(ty)
+                        ),
+                    )
+                    .with_span(expr.span()));
+                }
+
                 self.top_env_mut().bytecode.write_k(opcodes::RETURN, 1)?;
             }
             None => {
+                if expected != Ty::Void {
+                    return Err(CompileError::new(
+                        ErrorKind::Compiler,
+                        format!("missing return value: expected {}", self.ty_name(expected)),
+                    )
+                    .with_span(span));
+                }
+
                 self.top_env_mut().bytecode.write_k(opcodes::RETURN, 0)?;
             }
         }
@@ -723,3 +2415,161 @@ impl BytecodeCodegen {
         Ok(())
     }
 }
+
+/// Pick the singular or plural form of a word, to agree with a count.
+fn plural<'a>(count: usize, singular: &'a str, plural: &'a str) -> &'a str {
+    if count == 1 {
+        singular
+    } else {
+        plural
+    }
+}
+
+/// Error for a placeholder node, left by the parser
+/// where it recovered from a syntax error.
+fn syntax_error(span: Span) -> CompileError {
+    CompileError::new(ErrorKind::Compiler, "cannot compile source code with syntax errors").with_span(span)
+}
+
+/// Number type that a literal can take, when the type is a number.
+fn num_ty(ty: Ty) -> Option<NumTy> {
+    match ty {
+        Ty::I32 => Some(NumTy::I32),
+        Ty::I64 => Some(NumTy::I64),
+        Ty::F32 => Some(NumTy::F32),
+        Ty::F64 => Some(NumTy::F64),
+        _ => None,
+    }
+}
+
+/// Select the instructions that load and store a resolved variable,
+/// returning them with the variable's local slot or upvalue index, and type.
+fn var_opcodes(symbol: SymbolScope<(u32, Ty)>) -> (OpCode, OpCode, u32, Ty) {
+    match symbol {
+        SymbolScope::Local((index, ty)) => (opcodes::LOAD_LOCAL, opcodes::STORE_LOCAL, index, ty),
+        SymbolScope::NonLocal((index, ty)) => (opcodes::LOAD_UPVALUE, opcodes::STORE_UPVALUE, index, ty),
+        // The top level of the module isn't executed, so its variables are never resolved.
+        SymbolScope::Global(_) => unreachable!("module variables can't be resolved"),
+    }
+}
+
+/// Select the instruction for a unary operator, specialised for the operand type.
+fn unary_opcode(kind: &OperatorKind, ty: Ty) -> Option<OpCode> {
+    match (kind, ty) {
+        (OperatorKind::Neg, Ty::I32) => Some(opcodes::NEG_I32),
+        (OperatorKind::Neg, Ty::I64) => Some(opcodes::NEG_I64),
+        (OperatorKind::Neg, Ty::F32) => Some(opcodes::NEG_F32),
+        (OperatorKind::Neg, Ty::F64) => Some(opcodes::NEG_F64),
+        (OperatorKind::Not, Ty::Bool) => Some(opcodes::NOT_BOOL),
+        _ => None,
+    }
+}
+
+/// Select the instruction for a binary operator, specialised for the operand type.
+#[rustfmt::skip]
+fn binary_opcode(kind: &OperatorKind, ty: Ty) -> Option<OpCode> {
+    use OperatorKind as O;
+
+    match (kind, ty) {
+        (O::Add, Ty::I32) => Some(opcodes::ADD_I32),
+        (O::Sub, Ty::I32) => Some(opcodes::SUB_I32),
+        (O::Mul, Ty::I32) => Some(opcodes::MUL_I32),
+        (O::Div, Ty::I32) => Some(opcodes::DIV_I32),
+        (O::Equals, Ty::I32) => Some(opcodes::EQ_I32),
+        (O::NotEquals, Ty::I32) => Some(opcodes::NE_I32),
+        (O::Less, Ty::I32) => Some(opcodes::LT_I32),
+        (O::LessEq, Ty::I32) => Some(opcodes::LE_I32),
+        (O::Greater, Ty::I32) => Some(opcodes::GT_I32),
+        (O::GreaterEq, Ty::I32) => Some(opcodes::GE_I32),
+
+        (O::Add, Ty::I64) => Some(opcodes::ADD_I64),
+        (O::Sub, Ty::I64) => Some(opcodes::SUB_I64),
+        (O::Mul, Ty::I64) => Some(opcodes::MUL_I64),
+        (O::Div, Ty::I64) => Some(opcodes::DIV_I64),
+        (O::Equals, Ty::I64) => Some(opcodes::EQ_I64),
+        (O::NotEquals, Ty::I64) => Some(opcodes::NE_I64),
+        (O::Less, Ty::I64) => Some(opcodes::LT_I64),
+        (O::LessEq, Ty::I64) => Some(opcodes::LE_I64),
+        (O::Greater, Ty::I64) => Some(opcodes::GT_I64),
+        (O::GreaterEq, Ty::I64) => Some(opcodes::GE_I64),
+
+        (O::Add, Ty::F32) => Some(opcodes::ADD_F32),
+        (O::Sub, Ty::F32) => Some(opcodes::SUB_F32),
+        (O::Mul, Ty::F32) => Some(opcodes::MUL_F32),
+        (O::Div, Ty::F32) => Some(opcodes::DIV_F32),
+        (O::Equals, Ty::F32) => Some(opcodes::EQ_F32),
+        (O::NotEquals, Ty::F32) => Some(opcodes::NE_F32),
+        (O::Less, Ty::F32) => Some(opcodes::LT_F32),
+        (O::LessEq, Ty::F32) => Some(opcodes::LE_F32),
+        (O::Greater, Ty::F32) => Some(opcodes::GT_F32),
+        (O::GreaterEq, Ty::F32) => Some(opcodes::GE_F32),
+
+        (O::Add, Ty::F64) => Some(opcodes::ADD_F64),
+        (O::Sub, Ty::F64) => Some(opcodes::SUB_F64),
+        (O::Mul, Ty::F64) => Some(opcodes::MUL_F64),
+        (O::Div, Ty::F64) => Some(opcodes::DIV_F64),
+        (O::Equals, Ty::F64) => Some(opcodes::EQ_F64),
+        (O::NotEquals, Ty::F64) => Some(opcodes::NE_F64),
+        (O::Less, Ty::F64) => Some(opcodes::LT_F64),
+        (O::LessEq, Ty::F64) => Some(opcodes::LE_F64),
+        (O::Greater, Ty::F64) => Some(opcodes::GT_F64),
+        (O::GreaterEq, Ty::F64) => Some(opcodes::GE_F64),
+
+        (O::Equals, Ty::Bool) => Some(opcodes::EQ_BOOL),
+        (O::NotEquals, Ty::Bool) => Some(opcodes::NE_BOOL),
+
+        _ => None,
+    }
+}
+
+/// Binary operators that user types can overload, with the names of their methods.
+#[rustfmt::skip]
+const OPERATOR_METHODS: &[(OperatorKind, &str)] = &[
+    (OperatorKind::Add,       "+"),
+    (OperatorKind::Sub,       "-"),
+    (OperatorKind::Mul,       "*"),
+    (OperatorKind::Div,       "/"),
+    (OperatorKind::Mod,       "%"),
+    (OperatorKind::Equals,    "=="),
+    (OperatorKind::NotEquals, "!="),
+    (OperatorKind::Less,      "<"),
+    (OperatorKind::LessEq,    "<="),
+    (OperatorKind::Greater,   ">"),
+    (OperatorKind::GreaterEq, ">="),
+];
+
+/// Name of the method that overloads a binary operator for user types.
+fn operator_method(kind: &OperatorKind) -> Option<&'static str> {
+    OPERATOR_METHODS
+        .iter()
+        .find(|(operator, _)| operator == kind)
+        .map(|(_, name)| *name)
+}
+
+/// Binary operator that a method overloads, when it's named after one.
+fn method_operator(name: &str) -> Option<&'static OperatorKind> {
+    OPERATOR_METHODS
+        .iter()
+        .find(|(_, method)| *method == name)
+        .map(|(operator, _)| operator)
+}
+
+/// Number of arguments, excluding the receiver, that a method named
+/// after an operator must take. Other method names return `None`.
+fn operator_arity(name: &str) -> Option<usize> {
+    match name {
+        "+" | "-" | "*" | "/" | "%" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "[]" => Some(1),
+        "[]=" => Some(2),
+        _ => None,
+    }
+}
+
+/// Checks whether the binary operator compares its operands, resulting in a bool.
+fn is_comparison(kind: &OperatorKind) -> bool {
+    use OperatorKind as O;
+
+    matches!(
+        kind,
+        O::Equals | O::NotEquals | O::Less | O::LessEq | O::Greater | O::GreaterEq
+    )
+}
@@ -1,9 +1,8 @@
 //! Disassembler
 use std::fmt;
 
-use crate::bytecode::{decode_arg_a, decode_arg_k, decode_opcode, opcodes};
+use crate::bytecode::{decode_arg_a, decode_arg_k, decode_dyn_call, decode_field, decode_opcode, decode_u64, opcodes};
 use crate::chunk::Chunk;
-use crate::constants::*;
 use crate::error::Result;
 
 pub fn disassemble<W>(f: &mut W, chunk: &Chunk) -> Result<()>
@@ -11,7 +10,39 @@ where
     W: fmt::Write,
 {
     writeln!(f, "{}", chunk.header)?;
-    writeln!(f, "")?;
+    writeln!(f)?;
+
+    // Field layouts are decided by the VM, so only the declarations are listed.
+    for type_def in &chunk.types {
+        write!(f, "type \"{}\" {}:", type_def.name, type_def.id.to_u32())?;
+        for field in &type_def.fields {
+            write!(f, " {}:{}", field.name, field.ty)?;
+        }
+        writeln!(f)?;
+        for method in &type_def.methods {
+            writeln!(f, "  method \"{}\" {}", method.name, method.func_id.to_u32())?;
+        }
+    }
+    for interface_def in &chunk.interfaces {
+        write!(f, "interface \"{}\" {}:", interface_def.name, interface_def.id.to_u32())?;
+        for method in &interface_def.methods {
+            write!(f, " {method}")?;
+        }
+        writeln!(f)?;
+    }
+    for (vtable_id, vtable) in chunk.vtables.iter().enumerate() {
+        write!(f, "vtable {vtable_id}:")?;
+        for func_id in &vtable.funcs {
+            write!(f, " {}", func_id.to_u32())?;
+        }
+        if let Some(name) = vtable_name(chunk, vtable_id as u32) {
+            write!(f, "\t; {name}")?;
+        }
+        writeln!(f)?;
+    }
+    if !chunk.types.is_empty() || !chunk.interfaces.is_empty() {
+        writeln!(f)?;
+    }
 
     // TODO: Read function definitions
 
@@ -19,11 +50,15 @@ where
     writeln!(f, "      offset  00 08 16 24")?;
     writeln!(f, "------------  -----------")?;
 
+    // Function that the current instruction belongs to.
+    let mut current_func = None;
+
     let mut ip = 0;
     while ip < chunk.code.len() {
         if let Some(func) = chunk.funcs.iter().find(|f| f.bytecode_span.0 == ip as u32) {
             let func_id = func.id.map(|id| id.to_usize()).unwrap_or(0);
             writeln!(f, "\nfunc \"...\" {func_id}:")?;
+            current_func = Some(func);
         }
 
         let instruction = chunk.code[ip];
@@ -43,14 +78,147 @@ where
             opcodes::DIV_I32 => write!(f, "div.i32")?,
             opcodes::NEG_I32 => write!(f, "neg.i32")?,
             opcodes::EQ_I32 => write!(f, "eq.i32")?,
-            opcodes::PUSH_CONST => write!(f, "pushk\t{}", decode_arg_k(instruction))?,
+            opcodes::ADD_I64 => write!(f, "add.i64")?,
+            opcodes::SUB_I64 => write!(f, "sub.i64")?,
+            opcodes::MUL_I64 => write!(f, "mul.i64")?,
+            opcodes::DIV_I64 => write!(f, "div.i64")?,
+            opcodes::NEG_I64 => write!(f, "neg.i64")?,
+            opcodes::EQ_I64 => write!(f, "eq.i64")?,
+            opcodes::ADD_F32 => write!(f, "add.f32")?,
+            opcodes::SUB_F32 => write!(f, "sub.f32")?,
+            opcodes::MUL_F32 => write!(f, "mul.f32")?,
+            opcodes::DIV_F32 => write!(f, "div.f32")?,
+            opcodes::NEG_F32 => write!(f, "neg.f32")?,
+            opcodes::EQ_F32 => write!(f, "eq.f32")?,
+            opcodes::ADD_F64 => write!(f, "add.f64")?,
+            opcodes::SUB_F64 => write!(f, "sub.f64")?,
+            opcodes::MUL_F64 => write!(f, "mul.f64")?,
+            opcodes::DIV_F64 => write!(f, "div.f64")?,
+            opcodes::NEG_F64 => write!(f, "neg.f64")?,
+            opcodes::EQ_F64 => write!(f, "eq.f64")?,
+            opcodes::NE_I32 => write!(f, "ne.i32")?,
+            opcodes::LT_I32 => write!(f, "lt.i32")?,
+            opcodes::LE_I32 => write!(f, "le.i32")?,
+            opcodes::GT_I32 => write!(f, "gt.i32")?,
+            opcodes::GE_I32 => write!(f, "ge.i32")?,
+            opcodes::NE_I64 => write!(f, "ne.i64")?,
+            opcodes::LT_I64 => write!(f, "lt.i64")?,
+            opcodes::LE_I64 => write!(f, "le.i64")?,
+            opcodes::GT_I64 => write!(f, "gt.i64")?,
+            opcodes::GE_I64 => write!(f, "ge.i64")?,
+            opcodes::NE_F32 => write!(f, "ne.f32")?,
+            opcodes::LT_F32 => write!(f, "lt.f32")?,
+            opcodes::LE_F32 => write!(f, "le.f32")?,
+            opcodes::GT_F32 => write!(f, "gt.f32")?,
+            opcodes::GE_F32 => write!(f, "ge.f32")?,
+            opcodes::NE_F64 => write!(f, "ne.f64")?,
+            opcodes::LT_F64 => write!(f, "lt.f64")?,
+            opcodes::LE_F64 => write!(f, "le.f64")?,
+            opcodes::GT_F64 => write!(f, "gt.f64")?,
+            opcodes::GE_F64 => write!(f, "ge.f64")?,
+            opcodes::EQ_BOOL => write!(f, "eq.bool")?,
+            opcodes::NE_BOOL => write!(f, "ne.bool")?,
+            opcodes::NOT_BOOL => write!(f, "not.bool")?,
+            opcodes::PUSH_CONST
+            | opcodes::PUSH_CONST_I64
+            | opcodes::PUSH_CONST_F32
+            | opcodes::PUSH_CONST_F64
+            | opcodes::PUSH_CONST_BOOL => {
+                let konst_idx = decode_arg_k(instruction) as usize;
+                let constants = current_func.map(|func| func.constants.as_slice()).unwrap_or_default();
+                let word = |offset: usize| constants.get(konst_idx + offset).copied();
+
+                match opcode {
+                    opcodes::PUSH_CONST => write!(f, "pushk.i32\t{konst_idx}")?,
+                    opcodes::PUSH_CONST_I64 => write!(f, "pushk.i64\t{konst_idx}")?,
+                    opcodes::PUSH_CONST_F32 => write!(f, "pushk.f32\t{konst_idx}")?,
+                    opcodes::PUSH_CONST_BOOL => write!(f, "pushk.bool\t{konst_idx}")?,
+                    _ => write!(f, "pushk.f64\t{konst_idx}")?,
+                }
+
+                // Annotate with the constant's value, when it can be found.
+                match (opcode, word(0), word(1)) {
+                    (opcodes::PUSH_CONST, Some(low), _) => write!(f, "\t; {}", low as i32)?,
+                    (opcodes::PUSH_CONST_F32, Some(low), _) => write!(f, "\t; {:?}", f32::from_bits(low))?,
+                    (opcodes::PUSH_CONST_BOOL, Some(low), _) => write!(f, "\t; {}", low != 0)?,
+                    (opcodes::PUSH_CONST_I64, Some(low), Some(high)) => {
+                        write!(f, "\t; {}", decode_u64([low, high]) as i64)?
+                    }
+                    (opcodes::PUSH_CONST_F64, Some(low), Some(high)) => {
+                        write!(f, "\t; {:?}", f64::from_bits(decode_u64([low, high])))?
+                    }
+                    _ => {}
+                }
+            }
             opcodes::PUSH_CONST_IMM => write!(f, "push.i32.im\t{}", decode_arg_a(instruction))?,
+            opcodes::PUSH_STR => {
+                let string_idx = decode_arg_k(instruction);
+                write!(f, "push.str\t{string_idx}")?;
+                if let Some(string) = current_func.and_then(|func| func.strings.get(string_idx as usize)) {
+                    write!(f, "\t; {string:?}")?;
+                }
+            }
+            opcodes::TO_STR => write!(f, "to.str")?,
+            opcodes::CONCAT_STR => write!(f, "concat.str")?,
             opcodes::LOAD_LOCAL => write!(f, "load.local\t{}", decode_arg_k(instruction))?,
             opcodes::STORE_LOCAL => write!(f, "store.local\t{}", decode_arg_k(instruction))?,
+            opcodes::LOAD_UPVALUE => write!(f, "load.upvalue\t{}", decode_arg_k(instruction))?,
+            opcodes::STORE_UPVALUE => write!(f, "store.upvalue\t{}", decode_arg_k(instruction))?,
+            opcodes::CLOSE_UPVALUE => write!(f, "close.upvalue\t{}", decode_arg_k(instruction))?,
             opcodes::FUNC => write!(f, "function")?,
-            opcodes::SKIP_1 => write!(f, "skip.i32.1")?,
+            opcodes::PUSH_FUNC => write!(f, "push.func\t{}", decode_arg_k(instruction))?,
+            opcodes::CLOSURE => write!(f, "closure\t{}", decode_arg_k(instruction))?,
+            opcodes::CAPTURE_LOCAL => write!(f, "capture.local\t{}", decode_arg_k(instruction))?,
+            opcodes::CAPTURE_UPVALUE => write!(f, "capture.upvalue\t{}", decode_arg_k(instruction))?,
+            opcodes::NEW_OBJ => {
+                let type_id = decode_arg_k(instruction);
+                write!(f, "new.obj\t{type_id}")?;
+                if let Some(type_def) = chunk.types.get(type_id as usize) {
+                    write!(f, "\t; {}", type_def.name)?;
+                }
+            }
+            opcodes::GET_FIELD | opcodes::SET_FIELD => {
+                let mnemonic = if opcode == opcodes::GET_FIELD {
+                    "get.field"
+                } else {
+                    "set.field"
+                };
+                let (type_id, field_index) = decode_field(decode_arg_k(instruction));
+                write!(f, "{mnemonic}\t{field_index}")?;
+                if let Some(type_def) = chunk.types.get(type_id as usize) {
+                    if let Some(field) = type_def.fields.get(field_index as usize) {
+                        write!(f, "\t; {}.{}", type_def.name, field.name)?;
+                    }
+                }
+            }
+            opcodes::TO_IFACE => {
+                let vtable_id = decode_arg_k(instruction);
+                write!(f, "to.iface\t{vtable_id}")?;
+                if let Some(name) = vtable_name(chunk, vtable_id) {
+                    write!(f, "\t; {name}")?;
+                }
+            }
+            opcodes::SKIP_1 => write!(f, "skip.true")?,
             opcodes::SKIP_EQ_I32 => write!(f, "skip.eq.i32")?,
-            opcodes::CALL => write!(f, "call \t{}", decode_arg_k(instruction))?,
+            opcodes::RANGE_NEXT_I32 => write!(f, "range.next.i32\t{}", decode_arg_k(instruction))?,
+            opcodes::RANGE_NEXT_INCL_I32 => write!(f, "range.next.incl.i32\t{}", decode_arg_k(instruction))?,
+            opcodes::CALL => {
+                let func_id = decode_arg_k(instruction);
+                write!(f, "call \t{func_id}")?;
+
+                // Methods are the only functions with a known name.
+                let method = chunk.types.iter().find_map(|type_def| {
+                    let method = type_def.methods.iter().find(|m| m.func_id.to_u32() == func_id)?;
+                    Some((type_def, method))
+                });
+                if let Some((type_def, method)) = method {
+                    write!(f, "\t; {}.{}", type_def.name, method.name)?;
+                }
+            }
+            opcodes::DYN_CALL => {
+                let (arity, slot) = decode_dyn_call(decode_arg_k(instruction));
+                write!(f, "dyn.call\t{slot} {arity}")?;
+            }
             opcodes::RETURN => write!(f, "return\t{}", decode_arg_k(instruction))?,
             opcodes::JUMP => write!(
                 f,
@@ -61,13 +229,21 @@ where
             _ => write!(f, "UNKNOWN")?,
         }
 
-        write!(f, "\n")?;
+        writeln!(f)?;
         ip += 1;
     }
 
     Ok(())
 }
 
+/// Names of the types that a vtable converts between, like `Circle as Shape`.
+fn vtable_name(chunk: &Chunk, vtable_id: u32) -> Option<String> {
+    let vtable = chunk.vtable_by_id(vtable_id)?;
+    let type_def = chunk.type_by_id(vtable.type_id)?;
+    let interface_def = chunk.interface_by_id(vtable.interface_id)?;
+    Some(format!("{} as {}", type_def.name, interface_def.name))
+}
+
 #[rustfmt::skip]
 fn write_instruction_hex<W>(f: &mut W, offset: usize, instruction: u32) -> fmt::Result
 where
@@ -83,17 +259,21 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::bytecode::{opcodes::*, WriteBytecode};
+    use crate::bytecode::{encode_dyn_call, encode_field, opcodes::*, WriteBytecode};
     use crate::chunk::ChunkHeader;
+    use crate::constants::*;
+    use crate::typedef::{FieldDef, FieldTy};
 
     #[test]
     fn test_basic_disassemble() {
         let mut buf = String::new();
-        let mut chunk = Chunk::default();
-        chunk.header = ChunkHeader {
-            version: CHUNK_VERSION,
-            endianess: CHUNK_ENDIAN_LIT,
-            size_t: CHUNK_SIZE_32,
+        let mut chunk = Chunk {
+            header: ChunkHeader {
+                version: CHUNK_VERSION,
+                endianess: CHUNK_ENDIAN_LIT,
+                size_t: CHUNK_SIZE_32,
+            },
+            ..Chunk::default()
         };
         let code = chunk.code_mut();
         code.write_a(PUSH_CONST, 0).unwrap();
@@ -105,4 +285,103 @@ mod test {
 
         println!("{}", buf);
     }
+
+    #[test]
+    fn test_field_names() {
+        let mut chunk = Chunk::default();
+        chunk
+            .add_type(
+                "Vec".to_string(),
+                vec![
+                    FieldDef {
+                        name: "x".to_string(),
+                        ty: FieldTy::F32,
+                    },
+                    FieldDef {
+                        name: "y".to_string(),
+                        ty: FieldTy::F32,
+                    },
+                ],
+            )
+            .unwrap();
+
+        let code = chunk.code_mut();
+        code.write_k(NEW_OBJ, 0).unwrap();
+        code.write_k(GET_FIELD, encode_field(0, 1)).unwrap();
+        // Type isn't declared in the chunk.
+        code.write_k(SET_FIELD, encode_field(1, 0)).unwrap();
+
+        let mut buf = String::new();
+        disassemble(&mut buf, &chunk).expect("failed to disassemble binary chunk");
+
+        let lines = buf.lines().filter(|line| line.starts_with("  0x")).collect::<Vec<_>>();
+        assert!(lines[0].ends_with("new.obj\t0\t; Vec"), "{}", lines[0]);
+        assert!(lines[1].ends_with("get.field\t1\t; Vec.y"), "{}", lines[1]);
+        assert!(lines[2].ends_with("set.field\t0"), "{}", lines[2]);
+    }
+
+    #[test]
+    fn test_method_names() {
+        let mut chunk = Chunk::default();
+        let type_id = chunk
+            .add_type(
+                "Vec".to_string(),
+                vec![FieldDef {
+                    name: "x".to_string(),
+                    ty: FieldTy::F32,
+                }],
+            )
+            .unwrap();
+        let func_id = chunk.add_func_stub();
+        chunk.add_method(type_id, "Length".to_string(), func_id).unwrap();
+
+        let code = chunk.code_mut();
+        code.write_k(CALL, func_id.to_u32()).unwrap();
+        code.write_k(CALL, 0).unwrap();
+
+        let mut buf = String::new();
+        disassemble(&mut buf, &chunk).expect("failed to disassemble binary chunk");
+
+        assert!(buf.contains("  method \"Length\" 1\n"), "{buf}");
+        let lines = buf.lines().filter(|line| line.starts_with("  0x")).collect::<Vec<_>>();
+        assert!(lines[0].ends_with("call \t1\t; Vec.Length"), "{}", lines[0]);
+        assert!(lines[1].ends_with("call \t0"), "{}", lines[1]);
+    }
+
+    #[test]
+    fn test_vtable_names() {
+        let mut chunk = Chunk::default();
+        let type_id = chunk
+            .add_type(
+                "Circle".to_string(),
+                vec![FieldDef {
+                    name: "radius".to_string(),
+                    ty: FieldTy::F32,
+                }],
+            )
+            .unwrap();
+        let func_id = chunk.add_func_stub();
+        chunk.add_method(type_id, "Area".to_string(), func_id).unwrap();
+        let interface_id = chunk.add_interface("Shape".to_string(), vec!["Area".to_string()]).unwrap();
+        let vtable_id = chunk.add_vtable(type_id, interface_id, vec![func_id]).unwrap();
+
+        // Converting the same types again reuses the vtable.
+        assert_eq!(
+            chunk.add_vtable(type_id, interface_id, vec![func_id]).unwrap(),
+            vtable_id
+        );
+
+        let code = chunk.code_mut();
+        code.write_k(TO_IFACE, vtable_id).unwrap();
+        code.write_k(DYN_CALL, encode_dyn_call(1, 0)).unwrap();
+
+        let mut buf = String::new();
+        disassemble(&mut buf, &chunk).expect("failed to disassemble binary chunk");
+
+        assert!(buf.contains("interface \"Shape\" 0: Area\n"), "{buf}");
+        assert!(buf.contains("vtable 0: 1\t; Circle as Shape\n"), "{buf}");
+        let lines = buf.lines().filter(|line| line.starts_with("  0x")).collect::<Vec<_>>();
+        assert!(lines[0].ends_with("to.iface\t0\t; Circle as Shape"), "{}", lines[0]);
+        assert!(lines[1].ends_with("dyn.call\t0 1"), "{}", lines[1]);
+    }
 }
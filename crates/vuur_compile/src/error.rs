@@ -1,11 +1,15 @@
 use std::fmt;
 
+use vuur_lexer::span::Span;
+
 pub type Result<T> = std::result::Result<T, CompileError>;
 
 #[derive(Debug)]
 pub struct CompileError {
     pub message: String,
     pub kind: ErrorKind,
+    /// Source code where the error originates, if known.
+    pub span: Option<Span>,
 }
 
 #[derive(Debug)]
@@ -26,8 +30,15 @@ impl CompileError {
         Self {
             kind,
             message: message.to_string(),
+            span: None,
         }
     }
+
+    /// Attach the source code where the error originates.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
 }
 
 impl fmt::Display for CompileError {
@@ -57,6 +68,7 @@ impl From<std::io::Error> for CompileError {
         Self {
             message: "unexpected IO error".to_owned(),
             kind: ErrorKind::Io(err),
+            span: None,
         }
     }
 }
@@ -66,12 +78,13 @@ impl From<std::fmt::Error> for CompileError {
         Self {
             message: "unexpected formatting error".to_owned(),
             kind: ErrorKind::Fmt(err),
+            span: None,
         }
     }
 }
 
-impl<T> Into<self::Result<T>> for CompileError {
-    fn into(self) -> self::Result<T> {
-        Err(self)
+impl<T> From<CompileError> for self::Result<T> {
+    fn from(val: CompileError) -> Self {
+        Err(val)
     }
 }
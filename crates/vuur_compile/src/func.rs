@@ -1,6 +1,7 @@
 use std::num::NonZeroU32;
+use std::rc::Rc;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct FuncId(pub(crate) NonZeroU32);
 
@@ -15,7 +16,7 @@ impl FuncId {
     }
 
     pub fn to_u32(self) -> u32 {
-        self.0.get() as u32
+        self.0.get()
     }
 }
 
@@ -30,4 +31,10 @@ pub struct FuncDef {
     /// Number of operand stack slots required for this
     /// function's arguments.
     pub arity: u8,
+    /// Scalar constants, indexed by the argument of a constant push instruction.
+    ///
+    /// 64-bit values take up two consecutive slots, with the low bits first.
+    pub constants: Vec<u32>,
+    /// String constants, indexed by the argument of a string push instruction.
+    pub strings: Vec<Rc<str>>,
 }
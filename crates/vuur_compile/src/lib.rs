@@ -7,11 +7,14 @@ mod disasm;
 mod error;
 mod func;
 mod limits;
+mod typedef;
+mod types;
 
-pub use self::chunk::Chunk;
+pub use self::chunk::{Chunk, ChunkHeader};
 pub use self::disasm::disassemble;
 pub use self::error::*;
-pub use self::func::FuncDef;
+pub use self::func::{FuncDef, FuncId};
+pub use self::typedef::{FieldDef, FieldTy, InterfaceDef, InterfaceId, MethodDef, TypeDef, TypeId, VTable};
 
 pub fn compile(module: &vuur_parse::module::VuurModule) -> Result<Chunk> {
     let mut gen = codegen::BytecodeCodegen::new();
@@ -10,3 +10,23 @@ pub const MAX_STRINGS: usize = 0xFFFFFF;
 
 /// Maximum number of local variables allowed in a scope.
 pub const MAX_LOCALS: usize = 0xFFFFFF;
+
+/// Maximum number of variables a function can capture from enclosing functions.
+/// Limited by 24-bit instruction argument.
+pub const MAX_UPVALUES: usize = 0xFFFFFF;
+
+/// Maximum number of types allowed in a chunk.
+/// Limited by the type ID in the argument of field instructions.
+pub const MAX_TYPES: usize = 0xFFFF;
+
+/// Maximum number of methods allowed in an interface type.
+/// Limited by the vtable slot in the argument of dynamic call instructions.
+pub const MAX_INTERFACE_METHODS: usize = 0x1_0000;
+
+/// Maximum number of vtables allowed in a chunk.
+/// Limited by 24-bit instruction argument.
+pub const MAX_VTABLES: usize = 0xFFFFFF;
+
+/// Maximum number of fields allowed in a struct type.
+/// Limited by the field index in the argument of field instructions.
+pub const MAX_FIELDS: usize = 0x100;
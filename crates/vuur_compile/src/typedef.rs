@@ -0,0 +1,125 @@
+//! Type declarations, describing the layout of objects.
+use std::fmt;
+
+use crate::func::FuncId;
+
+/// Index of a type declaration in the chunk's type table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct TypeId(pub(crate) u32);
+
+impl TypeId {
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn to_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Index of an interface declaration in the chunk's interface table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct InterfaceId(pub(crate) u32);
+
+impl InterfaceId {
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn to_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Structure type declaration.
+///
+/// The VM uses this descriptor to lay out the fields of objects
+/// when the chunk is loaded.
+#[derive(Debug)]
+pub struct TypeDef {
+    pub id: TypeId,
+    pub name: String,
+    /// Fields in declaration order, which is the order of their field indices.
+    pub fields: Vec<FieldDef>,
+    /// Methods with this type as their receiver, in declaration order.
+    pub methods: Vec<MethodDef>,
+}
+
+impl TypeDef {
+    /// Find a method by name.
+    pub fn method(&self, name: &str) -> Option<&MethodDef> {
+        self.methods.iter().find(|method| method.name == name)
+    }
+}
+
+/// Field declaration of a structure type.
+#[derive(Debug)]
+pub struct FieldDef {
+    pub name: String,
+    pub ty: FieldTy,
+}
+
+/// Method declaration of a structure type.
+///
+/// Methods are functions that take the receiver as their first
+/// argument, so calls are dispatched statically to the function.
+#[derive(Debug)]
+pub struct MethodDef {
+    pub name: String,
+    pub func_id: FuncId,
+}
+
+/// Interface type declaration.
+///
+/// Structure types satisfy an interface by declaring methods with
+/// the same names and signatures, without referring to the interface.
+#[derive(Debug)]
+pub struct InterfaceDef {
+    pub id: InterfaceId,
+    pub name: String,
+    /// Method names in declaration order, which is the order of their vtable slots.
+    pub methods: Vec<String>,
+}
+
+impl InterfaceDef {
+    /// Find the vtable slot of a method by name.
+    pub fn method_slot(&self, name: &str) -> Option<usize> {
+        self.methods.iter().position(|method| method == name)
+    }
+}
+
+/// Table of the methods a structure type uses to satisfy an interface.
+///
+/// Interface values refer to the vtable of their structure type,
+/// so method calls on them are dispatched at runtime.
+#[derive(Debug)]
+pub struct VTable {
+    pub type_id: TypeId,
+    pub interface_id: InterfaceId,
+    /// Functions implementing the interface's methods, indexed by slot.
+    pub funcs: Vec<FuncId>,
+}
+
+/// Type of value stored in an object field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldTy {
+    Bool,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+impl fmt::Display for FieldTy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldTy::Bool => write!(f, "bool"),
+            FieldTy::I32 => write!(f, "i32"),
+            FieldTy::I64 => write!(f, "i64"),
+            FieldTy::F32 => write!(f, "f32"),
+            FieldTy::F64 => write!(f, "f64"),
+        }
+    }
+}
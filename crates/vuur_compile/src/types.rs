@@ -0,0 +1,100 @@
+//! Static type information used during code generation.
+use std::fmt;
+
+use crate::typedef::{FieldTy, InterfaceId, TypeId};
+
+/// Type of a value, as known by the compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Ty {
+    /// Absence of a value, for example the result
+    /// of calling a function without a return type.
+    Void,
+    Bool,
+    I32,
+    I64,
+    F32,
+    F64,
+    Str,
+    /// Structure type declared in the chunk's type table.
+    Struct(TypeId),
+    /// Interface type declared in the chunk's interface table.
+    ///
+    /// Structure values are converted to it when the
+    /// compiler finds that they satisfy the interface.
+    Interface(InterfaceId),
+    /// Function type, with its signature interned by the code generator
+    /// so that functions with the same signature have the same type.
+    Func(FuncTyId),
+    /// Type that can't be determined statically, like that of
+    /// inlined bytecode. Accepted wherever a type is expected.
+    Unknown,
+}
+
+impl Ty {
+    /// Resolve a builtin type from its name.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bool" => Some(Ty::Bool),
+            "int" | "i32" => Some(Ty::I32),
+            "i64" => Some(Ty::I64),
+            "float" | "f32" => Some(Ty::F32),
+            "f64" => Some(Ty::F64),
+            "str" => Some(Ty::Str),
+            _ => None,
+        }
+    }
+
+    /// Checks whether a value of the `other` type can be used
+    /// where a value of this type is expected.
+    pub(crate) fn accepts(self, other: Ty) -> bool {
+        self == other || self == Ty::Unknown || other == Ty::Unknown
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Void => write!(f, "void"),
+            Ty::Bool => write!(f, "bool"),
+            Ty::I32 => write!(f, "i32"),
+            Ty::I64 => write!(f, "i64"),
+            Ty::F32 => write!(f, "f32"),
+            Ty::F64 => write!(f, "f64"),
+            Ty::Str => write!(f, "str"),
+            // Structure types are displayed by name through the code generator.
+            Ty::Struct(type_id) => write!(f, "struct#{}", type_id.to_u32()),
+            Ty::Interface(interface_id) => write!(f, "interface#{}", interface_id.to_u32()),
+            Ty::Func(func_ty_id) => write!(f, "func#{}", func_ty_id.0),
+            Ty::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl From<FieldTy> for Ty {
+    fn from(ty: FieldTy) -> Self {
+        match ty {
+            FieldTy::Bool => Ty::Bool,
+            FieldTy::I32 => Ty::I32,
+            FieldTy::I64 => Ty::I64,
+            FieldTy::F32 => Ty::F32,
+            FieldTy::F64 => Ty::F64,
+        }
+    }
+}
+
+/// Argument and return types of a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FuncSig {
+    pub(crate) args: Vec<Ty>,
+    pub(crate) rtn: Ty,
+}
+
+/// Index of a function signature in the code generator's table of function types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct FuncTyId(pub(crate) u32);
+
+impl FuncTyId {
+    pub(crate) fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+}
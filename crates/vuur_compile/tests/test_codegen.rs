@@ -1,8 +1,11 @@
 use std::{fs::File, io::Write};
 
+use vuur_compile::bytecode::{decode_arg_k, decode_opcode, opcodes};
 use vuur_compile::{compile, disassemble};
+use vuur_lexer::span::SourceFile;
 
 // #[test]
+#[allow(dead_code)]
 fn test_basic_arithmetic() {
     const SRC: &[&str] = &["1 + 2", "-4 + 3 * 2"];
 
@@ -18,8 +21,353 @@ fn test_basic_arithmetic() {
         let mut file = File::create("./test_chunk.bin").unwrap();
         let mut buf = Vec::new();
         chunk.encode(&mut buf).unwrap();
-        file.write(&buf).expect("write chunk binary file");
+        file.write_all(&buf).expect("write chunk binary file");
         file.flush().expect("flush chunk binary file");
         drop(file);
     }
 }
+
+#[test]
+fn test_type_errors() {
+    const SRC: &[(&str, &str)] = &[
+        (
+            "func Main() -> int { return 1 + 2.0 }",
+            "mismatched operand types for binary operator Add: i32 and f32",
+        ),
+        (
+            "func Main() -> int { return 1i64 }",
+            "mismatched return type: expected i32, found i64",
+        ),
+        (
+            "func Main() { return 1 }",
+            "mismatched return type: expected void, found i32",
+        ),
+        (
+            "func Main() -> f64 {\n    var x = 1.5\n    return x\n}",
+            "mismatched return type: expected f64, found f32",
+        ),
+        (
+            "func Main() -> str { return -\"a\" }",
+            "unary operator Neg cannot be applied to type str",
+        ),
+        (
+            "func F(a: f32) {}\nfunc Main() { F(1) }",
+            "argument 0 of function 'F' expects type f32, found i32",
+        ),
+        (
+            "func F(a: f32) {}\nfunc Main() { F() }",
+            "function 'F' takes 1 argument, but 0 were given",
+        ),
+        ("func Main(a: num) {}", "unknown type 'num'"),
+        (
+            "func Main() {\n    if 2 {}\n}",
+            "mismatched condition type: expected bool, found i32",
+        ),
+        (
+            "func Main() -> bool { return true < false }",
+            "binary operator Less cannot be applied to type bool",
+        ),
+        ("func Main() {\n    break\n}", "'break' outside of loop"),
+        (
+            "func F() {\n    continue\n}\nfunc Main() {\n    while true {\n        F()\n    }\n}",
+            "'continue' outside of loop",
+        ),
+        (
+            "func Main() {\n    for c in \"abc\" {\n    }\n}",
+            "type str is not iterable",
+        ),
+        (
+            "func Main() {\n    for i in 0..1i64 {\n    }\n}",
+            "mismatched range bound type: expected i32, found i64",
+        ),
+        (
+            "func Main() {\n    var x = 1\n    x = 2.0\n}",
+            "mismatched types: cannot assign f32 to local variable 'x' of type i32",
+        ),
+        (
+            "func Main() -> i32 {\n    if true {\n        var y = 1\n    }\n    return y\n}",
+            "failed to resolve local variable: 'y'",
+        ),
+        (
+            "func Main() {\n    var x = 1\n    var x = 2\n}",
+            "local variable 'x' already declared in this scope",
+        ),
+        (
+            "func F(a: i32, a: i32) {}\nfunc Main() {}",
+            "local variable 'a' already declared in this scope",
+        ),
+        (
+            "func Main() {\n    var x: i32 = 1.5\n}",
+            "mismatched types: expected i32, found f32",
+        ),
+        (
+            "func Main() {\n    var x: i64 = 1.5\n}",
+            "mismatched types: expected i64, found f32",
+        ),
+        ("func Main() {\n    var x: num\n}", "unknown type 'num'"),
+        (
+            "type Name = struct { text: str }\nfunc Main() {}",
+            "type str cannot be used as a struct field",
+        ),
+        (
+            "type Vec = struct { x: f32, x: f32 }\nfunc Main() {}",
+            "field 'x' already declared in struct type 'Vec'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\ntype Vec = struct { y: f32 }\nfunc Main() {}",
+            "type with name 'Vec' already declared",
+        ),
+        ("type Num = i32\nfunc Main() {}", "type aliases are not supported"),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main(v: Vec) -> i32 {\n    return v\n}",
+            "mismatched return type: expected i32, found Vec",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var v = Vec { x: 1 }\n}",
+            "mismatched types: expected f32, found i32",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var v = Vec { x: 1.0, x: 2.0 }\n}",
+            "field 'x' specified more than once",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() -> f32 {\n    var v = Vec { x: 1.0 }\n    return v.y\n}",
+            "type Vec has no field named 'y'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var v: Vec\n    v.x = 1\n}",
+            "mismatched types: cannot assign i32 to field 'x' of type f32",
+        ),
+        ("func Main() {\n    var a = 1\n    a.x = 2\n}", "type i32 has no fields"),
+        ("func Main() {\n    var v = Nope { x: 1 }\n}", "unknown type 'Nope'"),
+        (
+            "func Main() {\n    var v = i32 { x: 1 }\n}",
+            "type i32 is not a struct type",
+        ),
+        (
+            "func (n: i32) Double() -> i32 {\n    return n * 2\n}\nfunc Main() {}",
+            "methods can only be declared on struct types, found i32",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (*Vec) Len() {}\nfunc (v: *Vec) Len() {}\nfunc Main() {}",
+            "method 'Len' already declared for type 'Vec'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (v: Vec) Len() {}\nfunc Main() {}",
+            "method receiver must be a pointer, like '*Vec'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var v: Vec\n    v.Len()\n}",
+            "type Vec has no method named 'Len'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var v: Vec\n    v.x()\n}",
+            "field 'x' of type Vec is not a method",
+        ),
+        (
+            "func Main() {\n    var a = 1\n    a.Len()\n}",
+            "type i32 has no methods",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (v: *Vec) Scale(s: f32) {}\nfunc Main() {\n    var v: Vec\n    v.Scale()\n}",
+            "function 'Vec.Scale' takes 1 argument, but 0 were given",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (v: *Vec) Scale(s: f32) {}\nfunc Main() {\n    var v: Vec\n    v.Scale(2)\n}",
+            "argument 0 of function 'Vec.Scale' expects type f32, found i32",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (*Vec) Len() -> f32 {\n    return vec.x\n}\nfunc Main() {}",
+            "failed to resolve local variable: 'vec'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (a: *Vec) +(b: Vec, c: Vec) -> Vec {\n    return a\n}\nfunc Main() {}",
+            "operator method '+' takes 1 argument, but 2 were declared",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (a: *Vec) []=(i: i32) {}\nfunc Main() {}",
+            "operator method '[]=' takes 2 arguments, but 1 was declared",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (a: *Vec) <(b: Vec) -> f32 {\n    return a.x\n}\nfunc Main() {}",
+            "comparison operator method '<' must return bool, found f32",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var v: Vec\n    v + v\n}",
+            "type Vec has no operator method '+'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (a: *Vec) *(s: f32) -> Vec {\n    return a\n}\nfunc Main() {\n    var v: Vec\n    2.0 * v\n}",
+            "type f32 has no operator method '*'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc (a: *Vec) *(s: f32) -> Vec {\n    return a\n}\nfunc Main() {\n    var v: Vec\n    v * 2\n}",
+            "argument 0 of function 'Vec.*' expects type f32, found i32",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var v: Vec\n    v[0]\n}",
+            "type Vec has no operator method '[]'",
+        ),
+        (
+            "func Main() {\n    var a = 1\n    a[0] = 2\n}",
+            "type i32 cannot be indexed",
+        ),
+        (
+            "type Shape = interface { Area() -> f32 }\ntype Box = struct { w: f32 }\nfunc Main() {\n    var s: Shape = Box { w: 1.0 }\n}",
+            "type Box does not satisfy interface Shape: missing method 'Area'",
+        ),
+        (
+            "type Shape = interface { Area() -> f32 }\ntype Box = struct { w: f32 }\nfunc (b: *Box) Area() -> i32 {\n    return 1\n}\nfunc Main() {\n    var s: Shape = Box { w: 1.0 }\n}",
+            "type Box does not satisfy interface Shape: method 'Area' has signature () -> i32, expected () -> f32",
+        ),
+        (
+            "type Shape = interface { Area() -> f32 }\ntype Box = struct { w: f32 }\nfunc Draw(s: Shape) {}\nfunc Main() {\n    Draw(Box { w: 1.0 })\n}",
+            "type Box does not satisfy interface Shape: missing method 'Area'",
+        ),
+        (
+            "type Shape = interface { Area() -> f32 }\nfunc Main(s: Shape) {\n    s.Perimeter()\n}",
+            "interface Shape has no method named 'Perimeter'",
+        ),
+        (
+            "type Shape = interface { Area() -> f32 }\nfunc Main(s: Shape) -> f32 {\n    return s.Area(2.0)\n}",
+            "function 'Shape.Area' takes 0 arguments, but 1 was given",
+        ),
+        (
+            "type Shape = interface { Area() -> f32, Area() -> f32 }\nfunc Main() {}",
+            "method 'Area' already declared in interface type 'Shape'",
+        ),
+        (
+            "type Shape = interface { Area() -> f32 }\ntype Shape = struct { x: f32 }\nfunc Main() {}",
+            "type with name 'Shape' already declared",
+        ),
+        (
+            "type Shape = interface { Area() -> f32 }\nfunc Main() {\n    var s: Shape\n}",
+            "type Shape has no zero value",
+        ),
+        (
+            "type Shape = interface { Area() -> Nope }\nfunc Main() {}",
+            "unknown type 'Nope'",
+        ),
+        (
+            "func Main() {\n    var f: func(i32) -> i32 = func(x: f32) -> i32 { return 1 }\n}",
+            "mismatched types: expected func(i32) -> i32, found func(f32) -> i32",
+        ),
+        (
+            "func Main() {\n    var f: func(i32)\n}",
+            "type func(i32) has no zero value",
+        ),
+        (
+            "func Main() {\n    var x = 1\n    x(2)\n}",
+            "type i32 is not callable",
+        ),
+        (
+            "func Main() {\n    var f = func(x: i32) {}\n    f(1, 2)\n}",
+            "function 'f' takes 1 argument, but 2 were given",
+        ),
+        (
+            "func Main() {\n    var f = func() -> i32 { return x }\n    var x = 1\n}",
+            "failed to resolve local variable: 'x'",
+        ),
+        (
+            "type Vec = struct { x: f32 }\nfunc Main() {\n    var s = 2.0\n    func (v: *Vec) Scale() -> f32 {\n        return v.x * s\n    }\n}",
+            "failed to resolve local variable: 's'",
+        ),
+        (
+            "func Main() {\n    var f = func() {\n        var g: f32 = 1.0\n        func h() -> i32 {\n            return g\n        }\n    }\n}",
+            "mismatched return type: expected i32, found f32",
+        ),
+    ];
+
+    for (source, expected) in SRC {
+        let module = vuur_parse::parse_str(source).unwrap();
+        match compile(&module) {
+            Ok(_) => panic!("expected type error compiling: {source}"),
+            Err(err) => assert_eq!(err.message, *expected),
+        }
+    }
+}
+
+#[test]
+fn test_error_location() {
+    let source = "func Add(a: f32, b: i32) -> f32 {\n    return a + b\n}\nfunc Main() {\n    Nope()\n}";
+    let file = SourceFile::new("test.vu", source);
+
+    let module = vuur_parse::parse_str(source).unwrap();
+    let err = compile(&module).err().expect("expected type error");
+    assert_eq!(file.location(err.span.unwrap().start).to_string(), "test.vu:2:14");
+
+    let module = vuur_parse::parse_str(&source.replace("b: i32", "b: f32")).unwrap();
+    let err = compile(&module).err().expect("expected unresolved function");
+    assert_eq!(file.location(err.span.unwrap().start).to_string(), "test.vu:5:5");
+
+    // Mismatched return types point at the returned expression.
+    let source = "func Half(a: f32) -> i32 {\n    return a / 2.0\n}";
+    let module = vuur_parse::parse_str(source).unwrap();
+    let err = compile(&module).err().expect("expected mismatched return type");
+    let span = err.span.unwrap();
+    assert_eq!(
+        &source[span.start.to_u32() as usize..span.end.to_u32() as usize],
+        "a / 2.0"
+    );
+}
+
+/// Functions nested in a function body are emitted into the chunk
+/// before it, so the jumps of the outer function must target its
+/// own position in the chunk.
+#[test]
+fn test_jump_after_nested_func() {
+    // The module body is a function that contains Main.
+    const MAIN: &str = "func Main() -> i32 {\n    return 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9\n}\n";
+    const SRC: &[(&str, usize)] = &[
+        ("if 1 < 2 {\n    Main()\n} else {\n    Main()\n}", 2),
+        ("while 1 < 2 {\n    Main()\n    break\n}", 3),
+        ("for i in 0..3 {\n    Main()\n}", 2),
+    ];
+
+    for (source, jump_count) in SRC {
+        let module = vuur_parse::parse_str(&format!("{MAIN}{source}")).unwrap();
+        let chunk = compile(&module).unwrap();
+
+        let entrypoint = chunk.entrypoint().expect("module entrypoint");
+        let main = chunk.func_by_id(entrypoint.to_u32()).expect("entrypoint function");
+        let body = main.bytecode_span.1..chunk.code().len() as u32;
+
+        let jumps = chunk.code()[body.start as usize..]
+            .iter()
+            .filter(|instruction| decode_opcode(**instruction) == opcodes::JUMP)
+            .map(|instruction| decode_arg_k(*instruction))
+            .collect::<Vec<_>>();
+
+        assert_eq!(jumps.len(), *jump_count, "unexpected jumps in '{source}'");
+        for target in jumps {
+            assert!(
+                body.contains(&target),
+                "jump target {target} outside of module body {body:?} in '{source}'"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_local_slots_reused() {
+    let source = r#"
+func Main(a: i32) {
+    if true {
+        var b = 1
+        var c = 2
+    }
+    for i in 0...2 {
+        var d = 3
+    }
+    var e = 4
+}
+"#;
+
+    let module = vuur_parse::parse_str(source).unwrap();
+    let chunk = compile(&module).unwrap();
+    let main = chunk.entrypoint().and_then(|id| chunk.func_by_id(id.to_u32())).unwrap();
+
+    // Peak is reached in the loop, with the range's counter and end,
+    // the loop variable and `d`. The argument isn't counted.
+    assert_eq!(main.local_count, 4);
+}
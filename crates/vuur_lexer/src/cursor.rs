@@ -44,7 +44,6 @@ impl<'a> Cursor<'a> {
     }
 
     /// Peek two characters ahead without advancing the cursor.
-    #[allow(dead_code)]
     pub(crate) fn peek2(&self) -> char {
         let mut iter = self.chars.clone();
         iter.next();
@@ -65,6 +64,15 @@ impl<'a> Cursor<'a> {
             .unwrap_or_else(|| BytePos(self.orig_size))
     }
 
+    /// Indicates whether there are no characters left to peek.
+    ///
+    /// Unlike comparing [`Cursor::peek`] to [`EOF_CHAR`], this doesn't
+    /// confuse an explicit `'\0'` in the source with the end.
+    pub(crate) fn is_peek_end(&self) -> bool {
+        let mut iter = self.chars.clone();
+        iter.next().is_none()
+    }
+
     /// Original length of source text before cursor moved.
     pub(crate) fn original_length(&self) -> u32 {
         self.orig_size
@@ -139,8 +147,8 @@ mod test {
 
     #[test]
     fn test_eof() {
-        assert_eq!(Cursor::from_str("").at_end(), true);
-        assert_eq!(Cursor::from_str("abc").at_end(), false);
+        assert!(Cursor::from_str("").at_end());
+        assert!(!Cursor::from_str("abc").at_end());
 
         // Exhausted cursor must return EOF
         let mut cursor = Cursor::from_str("a");
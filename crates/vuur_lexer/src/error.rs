@@ -0,0 +1,65 @@
+//! Lexical errors.
+use std::fmt;
+
+use crate::span::BytePos;
+
+/// Error encountered while scanning a token.
+///
+/// The lexer does not stop on errors. Instead it emits a token
+/// of kind [`TokenKind::Error`](crate::TokenKind::Error) covering
+/// the offending source text, so the parser can decide how to
+/// report it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    /// Byte position where the offending token starts.
+    pub offset: BytePos,
+    /// Size in bytes of the offending token.
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// String literal reached the end of the source
+    /// without a closing quote.
+    UnterminatedString,
+    /// Backslash followed by a character that is not
+    /// a known escape sequence.
+    InvalidEscape,
+    /// Malformed `\u{...}` escape, or one that does not
+    /// encode a valid unicode scalar value.
+    InvalidUnicodeEscape,
+    /// Malformed number literal, like a prefix without
+    /// digits or a digit that is invalid for the radix.
+    InvalidNumber,
+    /// Number literal has an unknown type suffix, or one
+    /// that does not fit the literal.
+    InvalidNumberSuffix,
+    /// Number literal does not fit in its type.
+    NumberOverflow,
+    /// Block comment reached the end of the source
+    /// without a matching closing `*/`.
+    UnterminatedComment,
+}
+
+impl std::error::Error for LexError {}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.kind, f)
+    }
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnterminatedString => write!(f, "unterminated string"),
+            Self::InvalidEscape => write!(f, "invalid escape sequence in string"),
+            Self::InvalidUnicodeEscape => write!(f, "invalid unicode escape sequence in string"),
+            Self::InvalidNumber => write!(f, "invalid number literal"),
+            Self::InvalidNumberSuffix => write!(f, "invalid suffix for number literal"),
+            Self::NumberOverflow => write!(f, "number literal out of range for its type"),
+            Self::UnterminatedComment => write!(f, "unterminated block comment"),
+        }
+    }
+}
@@ -1,10 +1,13 @@
 //! Lexical analysis.
 mod cursor;
+mod error;
+pub mod number;
 pub mod span;
 mod token;
-mod unescape;
+pub mod unescape;
 
 use cursor::{Cursor, EOF_CHAR};
+pub use error::{LexError, LexErrorKind};
 use span::BytePos;
 pub use token::{Keyword, Token, TokenKind};
 
@@ -17,6 +20,12 @@ pub struct Lexer<'a> {
     /// Start absolute byte position of the current token
     /// in the source.
     start_pos: BytePos,
+    /// Count of open parentheses for each string interpolation
+    /// that is currently being lexed, innermost last.
+    ///
+    /// When the count of the innermost interpolation drops to zero,
+    /// the closing parenthesis resumes the enclosing string.
+    interpolations: Vec<u32>,
 }
 
 impl<'a> Lexer<'a> {
@@ -38,6 +47,7 @@ impl<'a> Lexer<'a> {
             source,
             cursor,
             start_pos,
+            interpolations: Vec::new(),
         }
     }
 
@@ -79,14 +89,42 @@ impl<'a> Lexer<'a> {
 
         if !self.cursor.at_end() {
             match self.cursor.current() {
-                '(' => self.make_token(TokenKind::LeftParen),
-                ')' => self.make_token(TokenKind::RightParen),
+                '(' => {
+                    if let Some(count) = self.interpolations.last_mut() {
+                        *count += 1;
+                    }
+                    self.make_token(TokenKind::LeftParen)
+                }
+                ')' => {
+                    if let Some(count) = self.interpolations.last_mut() {
+                        *count -= 1;
+                        if *count == 0 {
+                            // End of the interpolated expression,
+                            // so continue lexing the string.
+                            self.interpolations.pop();
+                            return self.consume_string();
+                        }
+                    }
+                    self.make_token(TokenKind::RightParen)
+                }
                 '[' => self.make_token(TokenKind::LeftBracket),
                 ']' => self.make_token(TokenKind::RightBracket),
                 '{' => self.make_token(TokenKind::LeftBrace),
                 '}' => self.make_token(TokenKind::RightBrace),
-                '.' => self.make_token(TokenKind::Dot),
-                // '"' => self.consume_string(TokenKind::String),
+                '.' => {
+                    if self.cursor.peek() == '.' {
+                        self.cursor.bump();
+                        if self.cursor.peek() == '.' {
+                            self.cursor.bump();
+                            self.make_token(TokenKind::Ellipses)
+                        } else {
+                            self.make_token(TokenKind::DotDot)
+                        }
+                    } else {
+                        self.make_token(TokenKind::Dot)
+                    }
+                }
+                '"' => self.consume_string(),
                 '+' => self.make_token(TokenKind::Add),
                 '-' => {
                     if self.cursor.peek() == '>' {
@@ -96,17 +134,41 @@ impl<'a> Lexer<'a> {
                         self.make_token(TokenKind::Sub)
                     }
                 }
-                '=' => {
-                    if self.cursor.peek() == '=' {
+                '=' => self.make_token_if('=', TokenKind::EqEq, TokenKind::Eq),
+                '*' => self.make_token_if('*', TokenKind::StarStar, TokenKind::Mul),
+                '/' => match self.cursor.peek() {
+                    '/' => self.consume_line_comment(),
+                    '*' => self.consume_block_comment(),
+                    _ => self.make_token(TokenKind::Div),
+                },
+                '%' => self.make_token(TokenKind::Mod),
+                '!' => self.make_token_if('=', TokenKind::NotEq, TokenKind::Bang),
+                '~' => self.make_token(TokenKind::Tilde),
+                '^' => self.make_token(TokenKind::Caret),
+                '&' => self.make_token_if('&', TokenKind::AmpAmp, TokenKind::Ampersand),
+                '|' => self.make_token_if('|', TokenKind::PipePipe, TokenKind::Pipe),
+                '<' => match self.cursor.peek() {
+                    '=' => {
                         self.cursor.bump();
-                        self.make_token(TokenKind::EqEq)
-                    } else {
-                        self.make_token(TokenKind::Eq)
+                        self.make_token(TokenKind::LessEq)
                     }
-                }
-                '*' => self.make_token(TokenKind::Mul),
-                '/' => self.make_token(TokenKind::Div),
-                '&' => self.make_token(TokenKind::Ampersand),
+                    '<' => {
+                        self.cursor.bump();
+                        self.make_token(TokenKind::ShiftLeft)
+                    }
+                    _ => self.make_token(TokenKind::Less),
+                },
+                '>' => match self.cursor.peek() {
+                    '=' => {
+                        self.cursor.bump();
+                        self.make_token(TokenKind::GreaterEq)
+                    }
+                    '>' => {
+                        self.cursor.bump();
+                        self.make_token(TokenKind::ShiftRight)
+                    }
+                    _ => self.make_token(TokenKind::Greater),
+                },
                 ',' => self.make_token(TokenKind::Comma),
                 ':' => self.make_token(TokenKind::Colon),
                 ';' => self.make_token(TokenKind::Semicolon),
@@ -155,7 +217,7 @@ impl<'a> Lexer<'a> {
     ///
     /// Also prepare the cursor for the next iteration.
     fn make_token(&mut self, kind: TokenKind) -> Token {
-        let start = self.start_pos.0 as u32;
+        let start = self.start_pos.0;
         let end = self.cursor.peek_offset().0;
 
         // start and end can be equal, and a token can have 0 size.
@@ -178,6 +240,17 @@ impl<'a> Lexer<'a> {
 
         token
     }
+
+    /// Build a two character token when the next character matches
+    /// the given one, otherwise a single character token.
+    fn make_token_if(&mut self, next: char, double: TokenKind, single: TokenKind) -> Token {
+        if self.cursor.peek() == next {
+            self.cursor.bump();
+            self.make_token(double)
+        } else {
+            self.make_token(single)
+        }
+    }
 }
 
 /// Methods for consuming specific tokens.
@@ -206,13 +279,177 @@ impl<'a> Lexer<'a> {
     }
 
     /// Consumes a number literal.
+    ///
+    /// The literal is scanned loosely, taking any letters and digits
+    /// that follow, and then validated by [`number::parse_number`].
+    /// The value is converted later by the parser, which also checks
+    /// its range, since a preceding minus can be part of the literal.
     fn consume_number(&mut self) -> Token {
         debug_assert!(Self::is_digit(self.cursor.current()));
 
-        while Self::is_digit(self.cursor.peek()) {
+        let is_decimal = !(self.cursor.current() == '0' && matches!(self.cursor.peek(), 'x' | 'b' | 'o'));
+
+        self.consume_letters_or_digits();
+
+        if is_decimal {
+            // Fraction, but not a range `1..2` or member access `1.foo`.
+            if self.cursor.peek() == '.' && Self::is_digit(self.cursor.peek2()) {
+                self.cursor.bump();
+                self.consume_letters_or_digits();
+            }
+
+            // Signed exponent, where the unsigned exponent has
+            // already been consumed with the letters.
+            if matches!(self.cursor.current(), 'e' | 'E')
+                && matches!(self.cursor.peek(), '+' | '-')
+                && Self::is_digit(self.cursor.peek2())
+            {
+                self.cursor.bump();
+                self.consume_letters_or_digits();
+            }
+        }
+
+        let start = self.start_pos.0 as usize;
+        let end = self.cursor.peek_offset().0 as usize;
+        match number::parse_number(&self.source[start..end]) {
+            Ok(_) | Err(LexErrorKind::NumberOverflow) => self.make_token(TokenKind::Number),
+            Err(kind) => self.make_token(TokenKind::Error(kind)),
+        }
+    }
+
+    fn consume_letters_or_digits(&mut self) {
+        while Self::is_letter_or_digit(self.cursor.peek()) {
+            self.cursor.bump();
+        }
+    }
+
+    /// Consumes a double quoted string literal.
+    ///
+    /// The whole literal, including both quotes, becomes a single
+    /// token. Escape sequences are validated here, but converted
+    /// to their characters later by the parser.
+    ///
+    /// A string containing interpolated expressions `%(expr)` is
+    /// split into multiple tokens, with the tokens of each expression
+    /// lexed in between as usual.
+    ///
+    /// ```text
+    /// "a %(b) c %(d) e"
+    ///
+    /// Interpolated  "a %(
+    /// Ident         b
+    /// Interpolated  ) c %(
+    /// Ident         d
+    /// String        ) e"
+    /// ```
+    ///
+    /// Every part of the string thus starts with a single delimiter
+    /// character, either the opening quote or the closing parenthesis
+    /// of the previous interpolation, so this is also the entry point
+    /// for resuming the string after an interpolated expression.
+    fn consume_string(&mut self) -> Token {
+        debug_assert!(matches!(self.cursor.current(), '"' | ')'));
+
+        let kind = loop {
+            if self.cursor.is_peek_end() {
+                // Source ran out before the closing quote. The error
+                // token covers everything from the opening delimiter.
+                return self.make_token(TokenKind::Error(LexErrorKind::UnterminatedString));
+            }
+
+            match self.cursor.peek() {
+                '"' => {
+                    self.cursor.bump();
+                    break TokenKind::String;
+                }
+                '%' if self.cursor.peek2() == '(' => {
+                    self.cursor.bump();
+                    self.cursor.bump();
+                    break TokenKind::Interpolated;
+                }
+                '\\' => {
+                    // Skip over the escaped character so an escaped
+                    // quote doesn't terminate the string.
+                    self.cursor.bump();
+                    if !self.cursor.is_peek_end() {
+                        self.cursor.bump();
+                    }
+                }
+                _ => {
+                    self.cursor.bump();
+                }
+            }
+        };
+
+        // Contents of the string without the delimiters.
+        let start = self.start_pos.0 as usize + 1;
+        let end = match kind {
+            TokenKind::Interpolated => {
+                // The opening parenthesis of the interpolation
+                // is counted so its closing parenthesis can
+                // be matched later.
+                self.interpolations.push(1);
+                self.cursor.offset().0 as usize - 1
+            }
+            _ => self.cursor.offset().0 as usize,
+        };
+
+        match unescape::unescape(&self.source[start..end]) {
+            Ok(_) => self.make_token(kind),
+            Err(kind) => self.make_token(TokenKind::Error(kind)),
+        }
+    }
+
+    /// Consumes a line comment, up to but excluding the newline.
+    ///
+    /// Three slashes start a doc comment, but four or
+    /// more are considered a regular comment.
+    fn consume_line_comment(&mut self) -> Token {
+        debug_assert_eq!(self.cursor.current(), '/');
+        self.cursor.bump(); // second slash
+
+        let kind = if self.cursor.peek() == '/' && self.cursor.peek2() != '/' {
+            TokenKind::DocComment
+        } else {
+            TokenKind::Comment
+        };
+
+        while !self.cursor.is_peek_end() && !matches!(self.cursor.peek(), '\n' | '\r') {
+            self.cursor.bump();
+        }
+
+        self.make_token(kind)
+    }
+
+    /// Consumes a block comment, including any
+    /// block comments nested inside of it.
+    fn consume_block_comment(&mut self) -> Token {
+        debug_assert_eq!(self.cursor.current(), '/');
+        self.cursor.bump(); // asterisk
+
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.cursor.is_peek_end() {
+                // The error token starts where the outermost comment opens.
+                return self.make_token(TokenKind::Error(LexErrorKind::UnterminatedComment));
+            }
+
             self.cursor.bump();
+            match (self.cursor.current(), self.cursor.peek()) {
+                ('/', '*') => {
+                    self.cursor.bump();
+                    depth += 1;
+                }
+                ('*', '/') => {
+                    self.cursor.bump();
+                    depth -= 1;
+                }
+                _ => {}
+            }
         }
-        self.make_token(TokenKind::Number)
+
+        self.make_token(TokenKind::Comment)
     }
 
     fn consume_ident(&mut self) -> Token {
@@ -226,7 +463,6 @@ impl<'a> Lexer<'a> {
         let start = self.start_pos.0 as usize;
         let end = self.cursor.peek_offset().0 as usize;
         let fragment = &self.source[start..end];
-        // println!("fragment: '{}'", unescape::unescape_str(fragment));
 
         let token_kind = match Keyword::try_from(fragment) {
             Ok(keyword) => TokenKind::Keyword(keyword),
@@ -255,7 +491,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn is_digit(c: char) -> bool {
-        matches!(c, '0'..='9')
+        c.is_ascii_digit()
     }
 
     fn is_letter(c: char) -> bool {
@@ -366,11 +602,252 @@ mod test {
             (TokenKind::EOF,        22, 0), // outside string
         ];
 
-        for (token, exp) in lexer.into_iter().zip(expected.into_iter()) {
+        for (token, exp) in lexer.into_iter().zip(expected) {
             println!("{:?} == {:?}", token, exp);
             assert_eq!(token.kind, exp.0);
             assert_eq!(token.offset.to_u32(), exp.1);
             assert_eq!(token.size, exp.2);
         }
     }
+
+    #[test]
+    fn test_string() {
+        let source = r#"a = "hello \"world\"\n" + "\u{1F525}""#;
+        let lexer = Lexer::from_source(source);
+
+        #[rustfmt::skip]
+        let expected: Vec<(TokenKind, &str)> = vec![
+            (TokenKind::Ident,      "a"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Eq,         "="),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::String,     r#""hello \"world\"\n""#),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Add,        "+"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::String,     r#""\u{1F525}""#),
+            (TokenKind::EOF,        ""),
+        ];
+
+        let tokens = lexer.into_iter().collect::<Vec<_>>();
+        assert_eq!(tokens.len(), expected.len());
+        for (token, exp) in tokens.iter().zip(expected) {
+            assert_eq!(token.kind, exp.0);
+            assert_eq!(token.fragment(source), exp.1);
+        }
+    }
+
+    #[test]
+    fn test_string_errors() {
+        // (source, error kind, offset, size)
+        #[rustfmt::skip]
+        let cases: &[(&str, LexErrorKind, u32, u32)] = &[
+            (r#"x "abc"#,         LexErrorKind::UnterminatedString,   2, 4),
+            (r#"x "abc\""#,       LexErrorKind::UnterminatedString,   2, 6),
+            (r#"x "a\qc" y"#,     LexErrorKind::InvalidEscape,        2, 6),
+            (r#"x "\u{110000}""#, LexErrorKind::InvalidUnicodeEscape, 2, 12),
+        ];
+
+        for (source, kind, offset, size) in cases {
+            let mut lexer = Lexer::from_source(source);
+            assert_eq!(lexer.next_token().kind, TokenKind::Ident);
+            assert_eq!(lexer.next_token().kind, TokenKind::Whitespace);
+
+            let token = lexer.next_token();
+            assert_eq!(token.kind, TokenKind::Error(*kind), "lexing {source:?}");
+            let error = token.error().expect("lexical error");
+            assert_eq!(error.kind, *kind);
+            assert_eq!(error.offset.to_u32(), *offset);
+            assert_eq!(error.size, *size);
+        }
+
+        // Lexer recovers after the offending string.
+        let mut lexer = Lexer::from_source(r#""\q" b"#);
+        assert!(lexer.next_token().error().is_some());
+        assert_eq!(lexer.next_token().kind, TokenKind::Whitespace);
+        assert_eq!(lexer.next_token().kind, TokenKind::Ident);
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        // (source, expected tokens)
+        #[rustfmt::skip]
+        let cases: &[(&str, &[(TokenKind, &str)])] = &[
+            (r#""a %(b) c %(d) e""#, &[
+                (TokenKind::Interpolated, r#""a %("#),
+                (TokenKind::Ident,        "b"),
+                (TokenKind::Interpolated, ") c %("),
+                (TokenKind::Ident,        "d"),
+                (TokenKind::String,       r#") e""#),
+                (TokenKind::EOF,          ""),
+            ]),
+            // Parentheses inside the expression don't end the interpolation.
+            (r#""%(f(1) * (2))""#, &[
+                (TokenKind::Interpolated, r#""%("#),
+                (TokenKind::Ident,        "f"),
+                (TokenKind::LeftParen,    "("),
+                (TokenKind::Number,       "1"),
+                (TokenKind::RightParen,   ")"),
+                (TokenKind::Whitespace,   " "),
+                (TokenKind::Mul,          "*"),
+                (TokenKind::Whitespace,   " "),
+                (TokenKind::LeftParen,    "("),
+                (TokenKind::Number,       "2"),
+                (TokenKind::RightParen,   ")"),
+                (TokenKind::String,       r#")""#),
+                (TokenKind::EOF,          ""),
+            ]),
+            // Nested interpolation.
+            (r#""a %("b %(c)") d""#, &[
+                (TokenKind::Interpolated, r#""a %("#),
+                (TokenKind::Interpolated, r#""b %("#),
+                (TokenKind::Ident,        "c"),
+                (TokenKind::String,       r#")""#),
+                (TokenKind::String,       r#") d""#),
+                (TokenKind::EOF,          ""),
+            ]),
+            // Escaped delimiter, and percent sign not followed by a parenthesis.
+            (r#""100\%(x) %""#, &[
+                (TokenKind::String,       r#""100\%(x) %""#),
+                (TokenKind::EOF,          ""),
+            ]),
+            // Parentheses outside of a string are unaffected.
+            (r#"("%(a)")"#, &[
+                (TokenKind::LeftParen,    "("),
+                (TokenKind::Interpolated, r#""%("#),
+                (TokenKind::Ident,        "a"),
+                (TokenKind::String,       r#")""#),
+                (TokenKind::RightParen,   ")"),
+                (TokenKind::EOF,          ""),
+            ]),
+        ];
+
+        for (source, expected) in cases {
+            let tokens = Lexer::from_source(source).into_iter().collect::<Vec<_>>();
+            let actual = tokens.iter().map(|t| (t.kind, t.fragment(source))).collect::<Vec<_>>();
+            assert_eq!(actual, *expected, "lexing {source:?}");
+        }
+
+        // String continuing after an interpolation must still be terminated.
+        let source = r#""a %(b) c"#;
+        let tokens = Lexer::from_source(source).into_iter().collect::<Vec<_>>();
+        let error = tokens[2].error().expect("lexical error");
+        assert_eq!(error.kind, LexErrorKind::UnterminatedString);
+        assert_eq!(error.offset.to_u32(), 6);
+        assert_eq!(error.size, 3);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let source = "1_000 0xFFi64 1.5e-3-2.0f32 1.foo 3e+ 0b12 99999999999";
+        let lexer = Lexer::from_source(source);
+
+        #[rustfmt::skip]
+        let expected: Vec<(TokenKind, &str)> = vec![
+            (TokenKind::Number,     "1_000"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Number,     "0xFFi64"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Number,     "1.5e-3"),
+            (TokenKind::Sub,        "-"),
+            (TokenKind::Number,     "2.0f32"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Number,     "1"),
+            (TokenKind::Dot,        "."),
+            (TokenKind::Ident,      "foo"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Error(LexErrorKind::InvalidNumber),  "3e"),
+            (TokenKind::Add,        "+"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Error(LexErrorKind::InvalidNumber),  "0b12"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Number,     "99999999999"),
+            (TokenKind::EOF,        ""),
+        ];
+
+        let tokens = lexer.into_iter().collect::<Vec<_>>();
+        let actual = tokens.iter().map(|t| (t.kind, t.fragment(source))).collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_operators() {
+        let source = "!= ! < <= << > >= >> && & || | ^ ~ % ** * 1..2 ...";
+        let lexer = Lexer::from_source(source);
+
+        #[rustfmt::skip]
+        let expected: Vec<TokenKind> = vec![
+            TokenKind::NotEq, TokenKind::Bang,
+            TokenKind::Less, TokenKind::LessEq, TokenKind::ShiftLeft,
+            TokenKind::Greater, TokenKind::GreaterEq, TokenKind::ShiftRight,
+            TokenKind::AmpAmp, TokenKind::Ampersand,
+            TokenKind::PipePipe, TokenKind::Pipe,
+            TokenKind::Caret, TokenKind::Tilde, TokenKind::Mod,
+            TokenKind::StarStar, TokenKind::Mul,
+            TokenKind::Number, TokenKind::DotDot, TokenKind::Number,
+            TokenKind::Ellipses,
+            TokenKind::EOF,
+        ];
+
+        let actual = lexer
+            .into_iter()
+            .map(|t| t.kind)
+            .filter(|kind| *kind != TokenKind::Whitespace)
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_comments() {
+        let source = "a // line\n/// doc\n//// not doc\nb /* block /* nested */ */ / c/**/";
+        let lexer = Lexer::from_source(source);
+
+        #[rustfmt::skip]
+        let expected: Vec<(TokenKind, &str)> = vec![
+            (TokenKind::Ident,      "a"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Comment,    "// line"),
+            (TokenKind::Newline,    "\n"),
+            (TokenKind::DocComment, "/// doc"),
+            (TokenKind::Newline,    "\n"),
+            (TokenKind::Comment,    "//// not doc"),
+            (TokenKind::Newline,    "\n"),
+            (TokenKind::Ident,      "b"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Comment,    "/* block /* nested */ */"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Div,        "/"),
+            (TokenKind::Whitespace, " "),
+            (TokenKind::Ident,      "c"),
+            (TokenKind::Comment,    "/**/"),
+            (TokenKind::EOF,        ""),
+        ];
+
+        let tokens = lexer.into_iter().collect::<Vec<_>>();
+        let actual = tokens.iter().map(|t| (t.kind, t.fragment(source))).collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unterminated_comment() {
+        // (source, offset, size)
+        #[rustfmt::skip]
+        let cases: &[(&str, u32, u32)] = &[
+            ("x /* abc",                  2, 6),
+            ("x /* a /* b */ c",          2, 14),
+            ("x /*/",                     2, 3),
+        ];
+
+        for (source, offset, size) in cases {
+            let mut lexer = Lexer::from_source(source);
+            assert_eq!(lexer.next_token().kind, TokenKind::Ident);
+            assert_eq!(lexer.next_token().kind, TokenKind::Whitespace);
+
+            let error = lexer.next_token().error().expect("lexical error");
+            assert_eq!(error.kind, LexErrorKind::UnterminatedComment, "lexing {source:?}");
+            assert_eq!(error.offset.to_u32(), *offset);
+            assert_eq!(error.size, *size);
+            assert_eq!(lexer.next_token().kind, TokenKind::EOF);
+        }
+    }
 }
@@ -0,0 +1,237 @@
+//! Number literals.
+use crate::error::LexErrorKind;
+
+/// Value of a number literal, typed by its suffix.
+///
+/// Integers without a suffix are `i32`, and numbers with a fraction
+/// or exponent are `f32`. A literal without a suffix can take another
+/// type of the same kind with [`parse_number_as`], which the compiler
+/// does when the literal's context expects that type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumValue {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+/// Type of a number value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumTy {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+/// Formats the value as a number literal that
+/// converts back to the same value and type.
+impl std::fmt::Display for NumValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NumValue::I32(value) => write!(f, "{value}"),
+            NumValue::I64(value) => write!(f, "{value}i64"),
+            NumValue::F32(value) => write!(f, "{value:?}"),
+            NumValue::F64(value) => write!(f, "{value:?}f64"),
+        }
+    }
+}
+
+/// Convert the text of a number literal to its value.
+///
+/// Supports `0x`, `0b` and `0o` prefixed integers, decimal
+/// floats with exponents, `_` digit separators and the type
+/// suffixes `i32`, `i64`, `f32` and `f64`.
+///
+/// A leading `-` is part of the value, so the most negative
+/// integer of a type can be written as a literal.
+///
+/// ```
+/// # use vuur_lexer::number::{parse_number, NumValue};
+/// assert_eq!(parse_number("0xFF"), Ok(NumValue::I32(255)));
+/// assert_eq!(parse_number("1_000i64"), Ok(NumValue::I64(1000)));
+/// assert_eq!(parse_number("1.5e-3f64"), Ok(NumValue::F64(1.5e-3)));
+/// assert_eq!(parse_number("-2147483648"), Ok(NumValue::I32(i32::MIN)));
+/// ```
+pub fn parse_number(s: &str) -> Result<NumValue, LexErrorKind> {
+    parse_typed_number(s, None)
+}
+
+/// Convert the text of a number literal to its value, giving a literal
+/// without a suffix the type `ty` instead of its default type.
+///
+/// Integer literals only take integer types, and float literals only
+/// take float types. Otherwise the literal keeps its default type.
+///
+/// ```
+/// # use vuur_lexer::number::{parse_number_as, NumTy, NumValue};
+/// assert_eq!(parse_number_as("5", NumTy::I64), Ok(NumValue::I64(5)));
+/// assert_eq!(parse_number_as("0.1", NumTy::F64), Ok(NumValue::F64(0.1)));
+/// assert_eq!(parse_number_as("0.5", NumTy::I32), Ok(NumValue::F32(0.5)));
+/// assert_eq!(parse_number_as("5i32", NumTy::I64), Ok(NumValue::I32(5)));
+/// ```
+pub fn parse_number_as(s: &str, ty: NumTy) -> Result<NumValue, LexErrorKind> {
+    parse_typed_number(s, Some(ty))
+}
+
+fn parse_typed_number(s: &str, ty: Option<NumTy>) -> Result<NumValue, LexErrorKind> {
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", s),
+    };
+
+    let (radix, rest) = match s.get(..2) {
+        Some("0x") => (16, &s[2..]),
+        Some("0b") => (2, &s[2..]),
+        Some("0o") => (8, &s[2..]),
+        _ => (10, s),
+    };
+
+    let (digits, suffix, is_float) = if radix == 10 {
+        split_decimal(rest)?
+    } else {
+        let end = rest.find(|c: char| !(c.is_digit(radix) || c == '_')).unwrap_or(rest.len());
+        let (digits, suffix) = rest.split_at(end);
+        (digits, suffix, false)
+    };
+
+    // Separators are only allowed between digits.
+    if !digits.starts_with(|c: char| c.is_digit(radix)) {
+        return Err(LexErrorKind::InvalidNumber);
+    }
+
+    // Suffix starting with a digit is a digit out of range for the radix.
+    if suffix.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(LexErrorKind::InvalidNumber);
+    }
+
+    let digits = format!("{sign}{}", digits.replace('_', ""));
+
+    let suffix = match (suffix, is_float, ty) {
+        ("", false, Some(NumTy::I64)) => "i64",
+        ("", true, Some(NumTy::F64)) => "f64",
+        _ => suffix,
+    };
+
+    match (suffix, is_float) {
+        ("" | "i32", false) => parse_int(&digits, radix)
+            .and_then(|value| i32::try_from(value).map_err(|_| LexErrorKind::NumberOverflow))
+            .map(NumValue::I32),
+        ("i64", false) => parse_int(&digits, radix).map(NumValue::I64),
+        ("", true) | ("f32", _) if radix == 10 => match digits.parse::<f32>() {
+            Ok(value) if value.is_finite() => Ok(NumValue::F32(value)),
+            Ok(_) => Err(LexErrorKind::NumberOverflow),
+            Err(_) => Err(LexErrorKind::InvalidNumber),
+        },
+        ("f64", _) if radix == 10 => match digits.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(NumValue::F64(value)),
+            Ok(_) => Err(LexErrorKind::NumberOverflow),
+            Err(_) => Err(LexErrorKind::InvalidNumber),
+        },
+        _ => Err(LexErrorKind::InvalidNumberSuffix),
+    }
+}
+
+/// Split a decimal number into its digits, including the fraction
+/// and exponent, and its suffix.
+fn split_decimal(s: &str) -> Result<(&str, &str, bool), LexErrorKind> {
+    let bytes = s.as_bytes();
+    let is_digit = |index: usize| bytes.get(index).map(|b| b.is_ascii_digit() || *b == b'_') == Some(true);
+
+    let mut end = 0;
+    let mut is_float = false;
+
+    while is_digit(end) {
+        end += 1;
+    }
+
+    // Fraction
+    if bytes.get(end) == Some(&b'.') {
+        is_float = true;
+        end += 1;
+        while is_digit(end) {
+            end += 1;
+        }
+    }
+
+    // Exponent
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        is_float = true;
+        end += 1;
+        if matches!(bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+
+        let exponent_start = end;
+        while is_digit(end) {
+            end += 1;
+        }
+        if !s[exponent_start..end].starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(LexErrorKind::InvalidNumber);
+        }
+    }
+
+    Ok((&s[..end], &s[end..], is_float))
+}
+
+fn parse_int(digits: &str, radix: u32) -> Result<i64, LexErrorKind> {
+    i64::from_str_radix(digits, radix).map_err(|err| match err.kind() {
+        std::num::IntErrorKind::PosOverflow | std::num::IntErrorKind::NegOverflow => LexErrorKind::NumberOverflow,
+        _ => LexErrorKind::InvalidNumber,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        use NumValue as N;
+
+        #[rustfmt::skip]
+        let cases: &[(&str, Result<NumValue, LexErrorKind>)] = &[
+            ("0",                 Ok(N::I32(0))),
+            ("123",               Ok(N::I32(123))),
+            ("1_000_000",         Ok(N::I32(1_000_000))),
+            ("0xFF",              Ok(N::I32(255))),
+            ("0xdead_beefi64",    Ok(N::I64(0xdead_beef))),
+            ("0b1010",            Ok(N::I32(10))),
+            ("0o777",             Ok(N::I32(0o777))),
+            ("10i64",             Ok(N::I64(10))),
+            ("10i32",             Ok(N::I32(10))),
+            ("10f32",             Ok(N::F32(10.0))),
+            ("1.5",               Ok(N::F32(1.5))),
+            ("2.0f32",            Ok(N::F32(2.0))),
+            ("2.5f64",            Ok(N::F64(2.5))),
+            ("1.5e-3",            Ok(N::F32(1.5e-3))),
+            ("1e10f64",           Ok(N::F64(1e10))),
+            ("1_0.2_5E+2",        Ok(N::F32(1025.0))),
+            ("2147483647",        Ok(N::I32(i32::MAX))),
+            ("2147483648",        Err(LexErrorKind::NumberOverflow)),
+            ("0x1_0000_0000",     Err(LexErrorKind::NumberOverflow)),
+            ("9223372036854775808i64", Err(LexErrorKind::NumberOverflow)),
+            ("-2147483648",       Ok(N::I32(i32::MIN))),
+            ("-2147483649",       Err(LexErrorKind::NumberOverflow)),
+            ("-9223372036854775808i64", Ok(N::I64(i64::MIN))),
+            ("-0x10",             Ok(N::I32(-16))),
+            ("-1.5e-3",           Ok(N::F32(-1.5e-3))),
+            ("-_1",               Err(LexErrorKind::InvalidNumber)),
+            ("1e39",              Err(LexErrorKind::NumberOverflow)),
+            ("0x",                Err(LexErrorKind::InvalidNumber)),
+            ("0x_",               Err(LexErrorKind::InvalidNumber)),
+            ("0b102",             Err(LexErrorKind::InvalidNumber)),
+            ("1e",                Err(LexErrorKind::InvalidNumber)),
+            ("1e+",               Err(LexErrorKind::InvalidNumber)),
+            ("10u8",              Err(LexErrorKind::InvalidNumberSuffix)),
+            ("1.5i32",            Err(LexErrorKind::InvalidNumberSuffix)),
+            ("0x10f64",           Ok(N::I32(0x10f64))),
+            ("0o7f32",            Err(LexErrorKind::InvalidNumberSuffix)),
+            ("0b1i",              Err(LexErrorKind::InvalidNumberSuffix)),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(parse_number(source), *expected, "parsing {source:?}");
+        }
+    }
+}
@@ -1,5 +1,9 @@
+use std::fmt;
+
+use unicode_width::UnicodeWidthStr;
+
 /// Absolute byte position of a character in source code.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BytePos(pub(crate) u32);
 
 impl BytePos {
@@ -24,12 +28,209 @@ impl std::fmt::Display for BytePos {
     }
 }
 
+/// Range of source code, from the `start` byte up to,
+/// but excluding, the `end` byte.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: BytePos,
+    pub end: BytePos,
+}
+
+impl Span {
+    pub fn new(start: BytePos, end: BytePos) -> Self {
+        debug_assert!(start <= end, "span must not end before it starts");
+        Self { start, end }
+    }
+
+    /// Zero sized span at the given position.
+    pub fn empty(pos: BytePos) -> Self {
+        Self { start: pos, end: pos }
+    }
+
+    /// Span covering both this span and the other,
+    /// including any source code in between.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Size of the span in bytes.
+    #[inline]
+    pub fn size(self) -> u32 {
+        self.end.0 - self.start.0
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// Syntax element that originates from a range of source code.
+pub trait Spanned {
+    fn span(&self) -> Span;
+}
+
+/// Line and column of a character in source code.
+///
+/// Lines and columns start at 1. The column is the display width
+/// of the text preceding the character on its line, so a wide
+/// character like `漢` takes up two columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Pos {
     pub offset: BytePos,
     pub column: u16,
     pub line: u16,
 }
 
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Identifier of a source file in a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceId(u32);
+
+/// Source text with a name, like a file path, and the
+/// positions where its lines start.
+#[derive(Debug)]
+pub struct SourceFile {
+    name: String,
+    text: String,
+    /// Byte offset of the first character of each line.
+    lines: Vec<BytePos>,
+}
+
+impl SourceFile {
+    pub fn new(name: impl ToString, text: impl ToString) -> Self {
+        let text = text.to_string();
+
+        let lines = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .map(|index| BytePos(index as u32))
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            text,
+            lines,
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    #[inline]
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Line and column of the given byte position.
+    ///
+    /// Positions past the end of the text are clamped to the end,
+    /// which is where the end-of-file token points.
+    pub fn pos(&self, offset: BytePos) -> Pos {
+        let offset = offset.min(BytePos(self.text.len() as u32));
+
+        // Index of the last line that starts at or before the offset.
+        let line_index = self.lines.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.lines[line_index].0 as usize;
+
+        // Offset may point into the middle of a multi-byte character.
+        let mut end = offset.0 as usize;
+        while !self.text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let width = self.text[line_start..end].width();
+
+        Pos {
+            offset,
+            column: (width + 1).min(u16::MAX as usize) as u16,
+            line: (line_index + 1).min(u16::MAX as usize) as u16,
+        }
+    }
+
+    /// Text of the line with the given number, starting at 1,
+    /// without its line ending.
+    pub fn line_text(&self, line: u16) -> Option<&str> {
+        let index = (line as usize).checked_sub(1)?;
+        let start = self.lines.get(index)?.0 as usize;
+        let end = self.lines.get(index + 1).map(|pos| pos.0 as usize).unwrap_or(self.text.len());
+
+        let text = &self.text[start..end];
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        Some(text)
+    }
+
+    /// Location of the given byte position, for use in diagnostics.
+    pub fn location(&self, offset: BytePos) -> Location<'_> {
+        Location {
+            name: self.name(),
+            pos: self.pos(offset),
+        }
+    }
+}
+
+/// Position in a named source, displayed as `file:line:column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'a> {
+    pub name: &'a str,
+    pub pos: Pos,
+}
+
+impl<'a> fmt::Display for Location<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.name, self.pos)
+    }
+}
+
+/// Collection of source files that are being compiled together.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named source text to the map.
+    pub fn add_file(&mut self, name: impl ToString, text: impl ToString) -> SourceId {
+        let id = SourceId(self.files.len() as u32);
+        self.files.push(SourceFile::new(name, text));
+        id
+    }
+
+    #[inline]
+    pub fn file(&self, id: SourceId) -> Option<&SourceFile> {
+        self.files.get(id.0 as usize)
+    }
+
+    /// Location of a byte position in the given source file.
+    pub fn location(&self, id: SourceId, offset: BytePos) -> Option<Location<'_>> {
+        self.file(id).map(|file| file.location(offset))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -38,5 +239,64 @@ mod test {
     fn test_span_type_sizes() {
         assert_eq!(std::mem::size_of::<BytePos>(), 4);
         assert_eq!(std::mem::size_of::<Pos>(), 8);
+        assert_eq!(std::mem::size_of::<Span>(), 8);
+    }
+
+    #[test]
+    fn test_span_to() {
+        let a = Span::new(BytePos(4), BytePos(7));
+        let b = Span::new(BytePos(10), BytePos(12));
+
+        assert_eq!(a.to(b), Span::new(BytePos(4), BytePos(12)));
+        assert_eq!(b.to(a), Span::new(BytePos(4), BytePos(12)));
+        assert_eq!(a.to(b).size(), 8);
+        assert!(Span::empty(BytePos(3)).is_empty());
+    }
+
+    #[test]
+    fn test_source_pos() {
+        let file = SourceFile::new("test.vu", "func Main() {\r\n  return \"漢字\" + x\n}");
+
+        let cases: &[(u32, (u16, u16))] = &[
+            (0, (1, 1)),
+            (5, (1, 6)),
+            (13, (1, 14)),
+            (15, (2, 1)),
+            (17, (2, 3)),
+            // after the wide characters
+            (33, (2, 17)),
+            (37, (3, 1)),
+            // end-of-file
+            (38, (3, 2)),
+            (100, (3, 2)),
+        ];
+
+        for (offset, (line, column)) in cases.iter().copied() {
+            let pos = file.pos(BytePos(offset));
+            assert_eq!(
+                (pos.line, pos.column),
+                (line, column),
+                "unexpected position for offset {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_source_lines() {
+        let mut map = SourceMap::new();
+        let _ = map.add_file("empty.vu", "");
+        let id = map.add_file("test.vu", "a\r\nb\n\nc");
+        let file = map.file(id).unwrap();
+
+        assert_eq!(file.line_count(), 4);
+        assert_eq!(file.line_text(0), None);
+        assert_eq!(file.line_text(1), Some("a"));
+        assert_eq!(file.line_text(2), Some("b"));
+        assert_eq!(file.line_text(3), Some(""));
+        assert_eq!(file.line_text(4), Some("c"));
+        assert_eq!(file.line_text(5), None);
+
+        let location = map.location(id, BytePos(3)).unwrap();
+        assert_eq!(location.to_string(), "test.vu:2:1");
     }
 }
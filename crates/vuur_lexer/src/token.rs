@@ -1,6 +1,7 @@
 //! Tokens.
 
-use crate::span::BytePos;
+use crate::error::{LexError, LexErrorKind};
+use crate::span::{BytePos, Span, Spanned};
 
 #[derive(Debug)]
 pub struct Token {
@@ -24,10 +25,23 @@ pub enum TokenKind {
     Sub,          // -
     Mul,          // *
     Div,          // /
+    Mod,          // %
     StarStar,     // **
     Eq,           // =
     EqEq,         // ==
     NotEq,        // !=
+    Less,         // <
+    LessEq,       // <=
+    Greater,      // >
+    GreaterEq,    // >=
+    ShiftLeft,    // <<
+    ShiftRight,   // >>
+    Bang,         // !
+    Tilde,        // ~
+    Caret,        // ^
+    Pipe,         // |
+    PipePipe,     // ||
+    AmpAmp,       // &&
     ThinArrow,    // ->
     Ampersand,    // &
     Comma,        // ,
@@ -39,7 +53,22 @@ pub enum TokenKind {
     Keyword(Keyword),
     /// Number Literal
     Number,
+    /// String literal, including its quotes.
+    ///
+    /// Also the last part of an interpolated string, in
+    /// which case it starts with a closing parenthesis.
+    String,
+    /// Part of a string literal that is followed by an
+    /// interpolated expression, including the `%(` delimiter.
+    Interpolated,
 
+    /// Line comment `// ...` or nestable block comment `/* ... */`.
+    ///
+    /// Comments are trivia that the parser skips.
+    Comment,
+    /// Documentation line comment `/// ...` that
+    /// is attached to the declaration following it.
+    DocComment,
     /// Spaces and tabs.
     Whitespace,
     /// Line-feed and optionally a carriage return
@@ -48,19 +77,28 @@ pub enum TokenKind {
     EOF,
     /// Unknown character was encoutered in the source.
     Unknown,
+    /// Malformed token that the lexer could not scan.
+    Error(LexErrorKind),
 }
 
 /// Reserved identifiers.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Keyword {
+    Break,     // loop exit statement
+    Continue,  // loop next iteration statement
     Else,      // if conditional else statement
+    False,     // boolean literal
+    For,       // iterator loop statement
     Func,      // function declaration statement
     If,        // if conditional statement
+    In,        // iterator loop source
     Interface, // interface type declaration
     Return,    // block return statement
     Struct,    // struct type declaration
+    True,      // boolean literal
     Type,      // type declaration statement
     Var,       // variable declaration statement
+    While,     // conditional loop statement
 }
 
 impl Token {
@@ -71,6 +109,25 @@ impl Token {
         let end = start + self.size as usize;
         &source[start..end]
     }
+
+    /// Lexical error described by this token, if it
+    /// is an error token.
+    pub fn error(&self) -> Option<LexError> {
+        match self.kind {
+            TokenKind::Error(kind) => Some(LexError {
+                kind,
+                offset: self.offset,
+                size: self.size,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Spanned for Token {
+    fn span(&self) -> Span {
+        Span::new(self.offset, BytePos(self.offset.0 + self.size))
+    }
 }
 
 /// Formatting token kind to a human readable description
@@ -99,10 +156,23 @@ impl std::fmt::Display for TokenKind {
             T::Sub              => write!(f, "-"),
             T::Mul              => write!(f, "*"),
             T::Div              => write!(f, "/"),
+            T::Mod              => write!(f, "%"),
             T::StarStar         => write!(f, "**"),
             T::Eq               => write!(f, "="),
             T::EqEq             => write!(f, "=="),
             T::NotEq            => write!(f, "!="),
+            T::Less             => write!(f, "<"),
+            T::LessEq           => write!(f, "<="),
+            T::Greater          => write!(f, ">"),
+            T::GreaterEq        => write!(f, ">="),
+            T::ShiftLeft        => write!(f, "<<"),
+            T::ShiftRight       => write!(f, ">>"),
+            T::Bang             => write!(f, "!"),
+            T::Tilde            => write!(f, "~"),
+            T::Caret            => write!(f, "^"),
+            T::Pipe             => write!(f, "|"),
+            T::PipePipe         => write!(f, "||"),
+            T::AmpAmp           => write!(f, "&&"),
             T::ThinArrow        => write!(f, "->"),
             T::Ampersand        => write!(f, "&"),
             T::Comma            => write!(f, ","),
@@ -111,20 +181,28 @@ impl std::fmt::Display for TokenKind {
             T::Ident            => write!(f, "identifier"),
             T::Keyword(keyword) => std::fmt::Display::fmt(keyword, f),
             T::Number           => write!(f, "number"),
-            // T::String           => write!(f, "string"),
-            // T::Interpolated     => write!(f, "interpolated"),
-            // T::CommentLine      => write!(f, "//"),
-            // T::CommentLeft      => write!(f, "/*"),
-            // T::CommentRight     => write!(f, "*/"),
-            // T::Comment          => write!(f, "comment"),
+            T::String           => write!(f, "string"),
+            T::Interpolated     => write!(f, "interpolated"),
+            T::Comment          => write!(f, "comment"),
+            T::DocComment       => write!(f, "doc comment"),
             T::Whitespace       => write!(f, "whitespace"),
             T::Newline          => write!(f, "newline"),
             T::EOF              => write!(f, "end-of-file"),
             T::Unknown          => write!(f, "unknown"),
+            T::Error(kind)      => std::fmt::Display::fmt(kind, f),
         }
     }
 }
 
+impl TokenKind {
+    /// Indicates whether the token kind carries no meaning
+    /// for the parser, and can be skipped.
+    #[inline]
+    pub fn is_trivia(self) -> bool {
+        matches!(self, TokenKind::Comment)
+    }
+}
+
 impl<'a> TryFrom<&'a str> for Keyword {
     type Error = ();
 
@@ -132,19 +210,21 @@ impl<'a> TryFrom<&'a str> for Keyword {
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         use Keyword as K;
         match value {
+            "break"       => Ok(K::Break),
+            "continue"    => Ok(K::Continue),
             "else"        => Ok(K::Else),
+            "false"       => Ok(K::False),
+            "for"         => Ok(K::For),
             "func"        => Ok(K::Func),
             "if"          => Ok(K::If),
+            "in"          => Ok(K::In),
             "interface"   => Ok(K::Interface),
             "return"      => Ok(K::Return),
             "struct"      => Ok(K::Struct),
+            "true"        => Ok(K::True),
             "type"        => Ok(K::Type),
-            // "break"      => Ok(K::Break),
             // "class"      => Ok(K::Class),
             // "construct"  => Ok(K::Construct),
-            // "continue"   => Ok(K::Continue),
-            // "false"      => Ok(K::False),
-            // "for"        => Ok(K::For),
             // "foreign"    => Ok(K::Foreign),
             // "if"         => Ok(K::If),
             // "import"     => Ok(K::Import),
@@ -153,9 +233,8 @@ impl<'a> TryFrom<&'a str> for Keyword {
             // "static"     => Ok(K::Static),
             // "super"      => Ok(K::Super),
             // "this"       => Ok(K::This),
-            // "true"       => Ok(K::True),
             "var"        => Ok(K::Var),
-            // "while"      => Ok(K::While),
+            "while"       => Ok(K::While),
             _ => Err(()),
         }
     }
@@ -166,19 +245,21 @@ impl std::fmt::Display for Keyword {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         use Keyword as K;
         match self {
+            K::Break        => write!(f, "break"),
+            K::Continue     => write!(f, "continue"),
             K::Else         => write!(f, "else"),
+            K::False        => write!(f, "false"),
+            K::For          => write!(f, "for"),
             K::Func         => write!(f, "func"),
             K::If           => write!(f, "if"),
+            K::In           => write!(f, "in"),
             K::Interface    => write!(f, "interface"),
             K::Return       => write!(f, "return"),
             K::Struct       => write!(f, "struct"),
+            K::True         => write!(f, "true"),
             K::Type         => write!(f, "type"),
-            // K::Break        => write!(f, "break"),
             // K::Class        => write!(f, "class"),
             // K::Construct    => write!(f, "construct"),
-            // K::Continue     => write!(f, "continue"),
-            // K::False        => write!(f, "false"),
-            // K::For          => write!(f, "for"),
             // K::Foreign      => write!(f, "foreign"),
             // K::If           => write!(f, "if"),
             // K::Import       => write!(f, "import"),
@@ -187,9 +268,8 @@ impl std::fmt::Display for Keyword {
             // K::Static       => write!(f, "static"),
             // K::Super        => write!(f, "super"),
             // K::This         => write!(f, "this"),
-            // K::True         => write!(f, "true"),
             K::Var          => write!(f, "var"),
-            // K::While        => write!(f, "while"),
+            K::While        => write!(f, "while"),
         }
     }
 }
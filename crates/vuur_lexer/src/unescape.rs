@@ -1,15 +1,92 @@
-#[allow(dead_code)]
-pub fn unescape_str(s: &str) -> String {
-    let mut buf = String::new();
+//! String escape sequences.
+use crate::error::LexErrorKind;
 
-    for c in s.chars() {
-        match c {
-            '\t' => buf.push_str("\\t"),
-            '\n' => buf.push_str("\\n"),
-            '\r' => buf.push_str("\\r"),
-            _ => buf.push(c),
+/// Convert the escape sequences in the contents of a
+/// string literal to the characters they represent.
+///
+/// The given text must not include the surrounding quotes.
+///
+/// ```
+/// # use vuur_lexer::unescape::unescape;
+/// assert_eq!(unescape(r#"a\tb\"c\u{1F525}"#), Ok("a\tb\"c🔥".to_string()));
+/// ```
+pub fn unescape(s: &str) -> Result<String, LexErrorKind> {
+    let mut buf = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            buf.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => buf.push('\n'),
+            Some('t') => buf.push('\t'),
+            Some('r') => buf.push('\r'),
+            Some('0') => buf.push('\0'),
+            Some('\\') => buf.push('\\'),
+            Some('"') => buf.push('"'),
+            Some('%') => buf.push('%'),
+            Some('u') => buf.push(unescape_unicode(&mut chars)?),
+            Some(_) | None => return Err(LexErrorKind::InvalidEscape),
         }
     }
 
-    buf
+    Ok(buf)
+}
+
+/// Parse the `{XXXX}` part of a unicode escape sequence,
+/// consisting of 1 to 6 hexadecimal digits.
+fn unescape_unicode(chars: &mut std::str::Chars) -> Result<char, LexErrorKind> {
+    if chars.next() != Some('{') {
+        return Err(LexErrorKind::InvalidUnicodeEscape);
+    }
+
+    let mut value: u32 = 0;
+    let mut digits = 0;
+
+    loop {
+        match chars.next() {
+            Some('}') if digits > 0 => break,
+            Some(c) if digits < 6 => {
+                let digit = c.to_digit(16).ok_or(LexErrorKind::InvalidUnicodeEscape)?;
+                value = value * 16 + digit;
+                digits += 1;
+            }
+            _ => return Err(LexErrorKind::InvalidUnicodeEscape),
+        }
+    }
+
+    char::from_u32(value).ok_or(LexErrorKind::InvalidUnicodeEscape)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unescape() {
+        #[rustfmt::skip]
+        let cases: &[(&str, Result<&str, LexErrorKind>)] = &[
+            ("abc",            Ok("abc")),
+            (r"a\nb",          Ok("a\nb")),
+            (r"\t\r\0",        Ok("\t\r\0")),
+            (r#"\\ \""#,       Ok("\\ \"")),
+            (r"100\%(x)",      Ok("100%(x)")),
+            (r"\u{41}",        Ok("A")),
+            (r"\u{1F525}!",    Ok("🔥!")),
+            (r"\q",            Err(LexErrorKind::InvalidEscape)),
+            (r"abc\",          Err(LexErrorKind::InvalidEscape)),
+            (r"\u41",          Err(LexErrorKind::InvalidUnicodeEscape)),
+            (r"\u{}",          Err(LexErrorKind::InvalidUnicodeEscape)),
+            (r"\u{1234567}",   Err(LexErrorKind::InvalidUnicodeEscape)),
+            (r"\u{D800}",      Err(LexErrorKind::InvalidUnicodeEscape)),
+            (r"\u{zz}",        Err(LexErrorKind::InvalidUnicodeEscape)),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(unescape(source), expected.map(str::to_string), "unescaping {source:?}");
+        }
+    }
 }
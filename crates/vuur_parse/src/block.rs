@@ -1,24 +1,67 @@
+use vuur_lexer::span::{Span, Spanned};
 use vuur_lexer::TokenKind;
 
-use crate::{stmt::DefStmt, syntax_err, Parse};
+use crate::{stmt::DefStmt, stream::TokenStream, syntax_err, Parse, ParseResult};
 
 #[derive(Debug)]
 pub struct Block {
     pub stmts: Vec<DefStmt>,
+    /// From the opening brace up to and including the closing brace.
+    pub span: Span,
 }
 
 impl Parse for Block {
     type Output = Self;
 
-    fn parse(input: &mut crate::stream::TokenStream) -> crate::ParseResult<Self::Output> {
-        println!("Block::parse; start");
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        log::trace!("Block::parse; start");
 
         use TokenKind as T;
 
+        let Block { stmts, span } = Block::parse_braces(input)?;
+        input.ignore_many(T::Whitespace);
+
+        let token = input.peek();
+        match token.map(|t| t.kind) {
+            Some(T::Keyword(_)) => {
+                // Block can be terminated with a keyword, for cases like `else`.
+                // Do not consume so next parser can be chosen.
+                log::trace!("Block::parse; end; keyword");
+                Ok(Block { stmts, span })
+            }
+            Some(T::Newline | T::Semicolon | T::EOF) | None => {
+                // Valid block termination
+                //
+                log::trace!("Block::parse; end; punctuation");
+                input.next_token();
+                Ok(Block { stmts, span })
+            }
+
+            Some(kind) => Err(syntax_err(
+                token.expect("token kind was peeked"),
+                format!(
+                    "unexpected token {}; block closing brace must be followed by newline, semicolon or eof.",
+                    kind
+                ),
+            )),
+        }
+    }
+}
+
+impl Block {
+    /// Parse the statements between braces, without
+    /// the token that terminates a block statement.
+    ///
+    /// Used where the block is part of an expression, like the
+    /// body of an anonymous function, which can be followed by
+    /// the rest of the expression.
+    pub(crate) fn parse_braces(input: &mut TokenStream) -> ParseResult<Block> {
+        use TokenKind as T;
+
         // TODO: Single line block containing one expression
 
         input.ignore_many(T::Whitespace);
-        input.consume(T::LeftBrace)?;
+        let left_brace = input.consume(T::LeftBrace)?;
 
         let mut stmts = vec![];
 
@@ -29,35 +72,24 @@ impl Parse for Block {
                     input.next_token();
                     continue;
                 }
-                T::RightBrace => break,
-                _ => stmts.push(DefStmt::parse(input)?),
+                // Missing closing brace is reported below.
+                T::RightBrace | T::EOF => break,
+                _ => stmts.push(DefStmt::parse_or_recover(input)),
             }
         }
 
-        println!("Block::parse; statements end");
-        input.ignore_many(T::Whitespace);
-        input.consume(T::RightBrace)?;
+        log::trace!("Block::parse; statements end");
         input.ignore_many(T::Whitespace);
+        let right_brace = input.consume(T::RightBrace)?;
 
-        match input.peek().map(|t| t.kind) {
-            Some(T::Keyword(_)) => {
-                // Block can be terminated with a keyword, for cases like `else`.
-                // Do not consume so next parser can be chosen.
-                println!("Block::parse; end; keyword");
-                Ok(Block { stmts })
-            }
-            Some(T::Newline | T::Semicolon | T::EOF) | None => {
-                // Valid block termination
-                //
-                println!("Block::parse; end; punctuation");
-                input.next_token();
-                Ok(Block { stmts })
-            }
+        let span = left_brace.span().to(right_brace.span());
 
-            Some(kind) => Err(syntax_err(format!(
-                "unexpected token {}; block closing brace must be followed by newline, semicolon or eof.",
-                kind
-            ))),
-        }
+        Ok(Block { stmts, span })
+    }
+}
+
+impl Spanned for Block {
+    fn span(&self) -> Span {
+        self.span
     }
 }
@@ -1,5 +1,6 @@
 //! Conditionals
 
+use vuur_lexer::span::{Span, Spanned};
 use vuur_lexer::{Keyword, TokenKind};
 
 use crate::{block::Block, expr::Expr, stream::TokenStream, Parse, ParseResult};
@@ -9,6 +10,9 @@ pub struct IfStmt {
     pub cond: Expr,
     pub body: Block,
     pub else_: ElseStmt,
+    /// From the `if` keyword up to the end of the last body,
+    /// including any `else` branches.
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -22,7 +26,7 @@ impl Parse for IfStmt {
     type Output = Self;
 
     fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
-        println!("IfStmt::parse");
+        log::trace!("IfStmt::parse");
 
         use Keyword as K;
         use TokenKind as T;
@@ -30,7 +34,7 @@ impl Parse for IfStmt {
         input.ignore_many(T::Whitespace);
 
         // keyword
-        input.consume(T::Keyword(K::If))?;
+        let keyword = input.consume(T::Keyword(K::If))?;
         input.ignore_many(T::Whitespace);
 
         // conditional expression
@@ -41,30 +45,49 @@ impl Parse for IfStmt {
         let body = Block::parse(input)?;
         input.ignore_many(T::Whitespace);
 
-        println!("IfStmt::parse; TokenKind={:?}", input.peek().map(|t| t.kind));
+        log::trace!("IfStmt::parse; TokenKind={:?}", input.peek().map(|t| t.kind));
         input.reset_peek();
 
         // optional else
         let else_ = if let Some(T::Keyword(K::Else)) = input.peek().map(|t| t.kind) {
-            println!("IfStmt::parse; else_");
+            log::trace!("IfStmt::parse; else_");
             input.next_token();
             input.ignore_many(T::Whitespace);
 
             // first peek advances an internal peek token to second lookahead character
             if let Some(T::Keyword(K::If)) = input.peek().map(|t| t.kind) {
-                println!("IfStmt::parse; else_,if");
+                log::trace!("IfStmt::parse; else_,if");
                 IfStmt::parse(input).map(Box::new).map(ElseStmt::ElseIf)?
             } else {
-                println!("IfStmt::parse; else_,body");
+                log::trace!("IfStmt::parse; else_,body");
                 ElseStmt::Else {
                     body: Block::parse(input)?,
                 }
             }
         } else {
-            println!("IfStmt::parse; no else_");
+            log::trace!("IfStmt::parse; no else_");
+            // Leave the lookahead on the statement following the if-statement.
+            input.reset_peek();
             ElseStmt::Empty
         };
 
-        Ok(IfStmt { cond, body, else_ })
+        let span = match &else_ {
+            ElseStmt::Empty => keyword.span().to(body.span()),
+            ElseStmt::Else { body: else_body } => keyword.span().to(else_body.span()),
+            ElseStmt::ElseIf(if_stmt) => keyword.span().to(if_stmt.span()),
+        };
+
+        Ok(IfStmt {
+            cond,
+            body,
+            else_,
+            span,
+        })
+    }
+}
+
+impl Spanned for IfStmt {
+    fn span(&self) -> Span {
+        self.span
     }
 }
@@ -1,11 +1,18 @@
 //! Delimited list
 
+use vuur_lexer::span::{Span, Spanned};
+
 use crate::stream::TokenStream;
 use crate::{Parse, ParseError, ParseResult};
 
 #[derive(Debug)]
 pub struct Delimited<T: Parse<Output = T>, U: Parse<Output = U>> {
     pub pairs: Vec<Pair<T, U>>,
+    /// From the first item up to the last delimiter, or item when
+    /// the list has no trailing delimiter.
+    ///
+    /// An empty list has a zero sized span where it would start.
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -16,8 +23,8 @@ pub struct Pair<T, U> {
 
 impl<T, U> Parse for Delimited<T, U>
 where
-    T: Parse<Output = T>,
-    U: Parse<Output = U>,
+    T: Parse<Output = T> + Spanned,
+    U: Parse<Output = U> + Spanned,
 {
     type Output = Self;
 
@@ -48,7 +55,12 @@ where
             }
         }
 
-        Ok(Delimited { pairs })
+        let span = match (pairs.first(), pairs.last()) {
+            (Some(first), Some(last)) => first.span().to(last.span()),
+            _ => Span::empty(input.pos()),
+        };
+
+        Ok(Delimited { pairs, span })
     }
 }
 
@@ -63,3 +75,26 @@ where
         todo!("Pair")
     }
 }
+
+impl<T, U> Spanned for Delimited<T, U>
+where
+    T: Parse<Output = T>,
+    U: Parse<Output = U>,
+{
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<T, U> Spanned for Pair<T, U>
+where
+    T: Spanned,
+    U: Spanned,
+{
+    fn span(&self) -> Span {
+        match &self.delimiter {
+            Some(delimiter) => self.item.span().to(delimiter.span()),
+            None => self.item.span(),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Documentation comments.
+use vuur_lexer::span::{Span, Spanned};
+use vuur_lexer::{Token, TokenKind};
+
+use crate::stream::TokenStream;
+use crate::{Parse, ParseResult};
+
+/// Documentation comment attached to a declaration.
+///
+/// ```not-rust
+/// /// Adds two numbers
+/// /// together.
+/// func Add(a: int, b: int) -> int {
+///     return a + b
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DocComment {
+    /// Token of each comment line.
+    pub lines: Vec<Token>,
+    /// Text of the comment with the `///` markers removed,
+    /// and the lines joined by newlines.
+    pub text: String,
+    /// From the first line up to the end of the last line.
+    pub span: Span,
+}
+
+impl Parse for DocComment {
+    /// Declarations are not required to have documentation.
+    type Output = Option<Self>;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use TokenKind as T;
+
+        let mut lines = vec![];
+        let mut text = String::new();
+
+        while input.peek_kind() == Some(T::DocComment) {
+            let token = input.next_token().expect("peeked doc comment");
+            let fragment = input.token_fragment(&token);
+
+            // Strip the marker and the conventional space after it.
+            let line = fragment.trim_start_matches('/');
+            let line = line.strip_prefix(' ').unwrap_or(line);
+
+            if !lines.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(line.trim_end());
+            lines.push(token);
+
+            // The comment is separated from the next line, and the
+            // declaration it documents, by newlines and indentation.
+            input.ignore_while(|kind| matches!(kind, T::Newline | T::Whitespace));
+        }
+        input.reset_peek();
+
+        match (lines.first(), lines.last()) {
+            (Some(first), Some(last)) => {
+                let span = first.span().to(last.span());
+                Ok(Some(DocComment { lines, text, span }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Spanned for DocComment {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
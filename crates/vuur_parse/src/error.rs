@@ -1,22 +1,148 @@
+//! Reporting of parsing errors.
+use std::fmt::{self, Write};
+
+use unicode_width::UnicodeWidthStr;
+use vuur_lexer::span::{Pos, SourceFile};
+
 use crate::ParseError;
 
+/// Whether diagnostics are rendered with terminal colors.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    #[default]
+    Never,
+    /// Style the output using ANSI escape codes.
+    Always,
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
 /// Pretty format parsing error.
-pub fn format_error(source: &str, err: ParseError) -> String {
+///
+/// The report points at the offending token in the source file,
+/// in the style of `rustc`.
+///
+/// ```text
+/// error: encountered token 'newline', expected ')'
+///  --> main.vu:1:15
+///   |
+/// 1 | var x = (1 + 2
+///   |               ^
+///   |
+///   = note: expected ')', found 'newline'
+/// ```
+pub fn format_error(file: &SourceFile, err: &ParseError, color: ColorChoice) -> String {
     let mut s = String::new();
+    write_error(&mut s, file, err, color).expect("writing to string cannot fail");
+    s
+}
 
-    // TODO: Error must have a token with a byte pos and size.
-    // TODO: Scan string to count columns and rows.
+fn write_error(f: &mut impl Write, file: &SourceFile, err: &ParseError, color: ColorChoice) -> fmt::Result {
+    let paint = |style: &'static str, text: &str| match color {
+        ColorChoice::Never => text.to_string(),
+        ColorChoice::Always => format!("{style}{text}{RESET}"),
+    };
 
-    s
+    let span = err.span();
+    let start = file.pos(span.start);
+    let end = file.pos(span.end);
+    let line = file.line_text(start.line).unwrap_or_default();
+
+    // The underline is cut off at the end of the first line, but
+    // is at least one column wide so it's visible for zero sized
+    // spans, like the end-of-file.
+    let width = if end.line == start.line {
+        end.column - start.column
+    } else {
+        (line.width() + 1).saturating_sub(start.column as usize) as u16
+    };
+    let underline = "^".repeat(width.max(1) as usize);
+
+    let gutter = " ".repeat(start.line.to_string().len());
+    let bar = paint(BLUE, "|");
+
+    writeln!(
+        f,
+        "{}{} {}",
+        paint(RED, "error"),
+        paint(BOLD, ":"),
+        paint(BOLD, &err.to_string())
+    )?;
+    writeln!(f, "{gutter}{} {}", paint(BLUE, "-->"), file.location(span.start))?;
+    writeln!(f, "{gutter} {bar}")?;
+    writeln!(f, "{} {bar} {line}", paint(BLUE, &start.line.to_string()))?;
+    writeln!(
+        f,
+        "{gutter} {bar} {}{}",
+        " ".repeat(start.column as usize - 1),
+        paint(RED, &underline)
+    )?;
+
+    let note = match err {
+        ParseError::Syntax { found, .. } => Some(format!("found '{found}'")),
+        ParseError::Token(err) => Some(format!("expected '{}', found '{}'", err.expected, err.encountered)),
+        // The message of a lexical error already describes the offending token.
+        ParseError::Lex(_) => None,
+    };
+
+    if let Some(note) = note {
+        writeln!(f, "{gutter} {bar}")?;
+        writeln!(f, "{gutter} {} {}: {note}", paint(BLUE, "="), paint(BOLD, "note"))?;
+    }
+
+    Ok(())
 }
 
+/// Parsing error with the location in the source file where it occurred.
+#[derive(Debug)]
 pub struct Error {
     pub kind: ParseError,
     pub(crate) inner: Box<ErrorInner>,
 }
 
+#[derive(Debug)]
 pub(crate) struct ErrorInner {
-    span: vuur_lexer::span::Pos,
-    filepath: Option<String>,
-    cause: Option<Box<dyn std::error::Error + 'static>>,
+    pos: Pos,
+    filepath: String,
+}
+
+impl Error {
+    pub fn new(kind: ParseError, file: &SourceFile) -> Self {
+        let inner = Box::new(ErrorInner {
+            pos: file.pos(kind.span().start),
+            filepath: file.name().to_string(),
+        });
+
+        Self { kind, inner }
+    }
+
+    /// Line and column of the offending token.
+    #[inline]
+    pub fn pos(&self) -> Pos {
+        self.inner.pos
+    }
+
+    #[inline]
+    pub fn filepath(&self) -> &str {
+        self.inner.filepath.as_str()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.filepath(), self.pos(), self.kind)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ParseError::Syntax { .. } => None,
+            ParseError::Token(err) => Some(err),
+            ParseError::Lex(err) => Some(err),
+        }
+    }
 }
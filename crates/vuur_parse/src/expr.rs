@@ -1,13 +1,18 @@
 //! Expression parsing
 
-use std::convert::Infallible;
-
-use vuur_lexer::{Keyword, Token, TokenKind};
+use smol_str::SmolStr;
+use vuur_lexer::number::{parse_number, NumValue};
+use vuur_lexer::span::{Span, Spanned};
+use vuur_lexer::unescape::unescape;
+use vuur_lexer::{Keyword, LexError, Token, TokenKind};
 
 use crate::block::Block;
+use crate::delim::Delimited;
+use crate::func::{FuncArg, FuncRtn, Separator};
 use crate::ident::Ident;
 use crate::stream::TokenStream;
-use crate::{syntax_err, Parse, ParseResult};
+use crate::ty::Type;
+use crate::{eof_err, syntax_err, Parse, ParseError, ParseResult};
 
 /// Token precedence.
 #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
@@ -31,8 +36,9 @@ enum Precedence {
     Term = 14,         // + -
     Factor = 15,       // * / %
     Unary = 16,        // - ! ~
-    Call = 17,         // . () []
-    Primary = 18,
+    Exponent = 17,     // **
+    Call = 18,         // . () []
+    Primary = 19,
 }
 
 impl Precedence {
@@ -49,45 +55,55 @@ impl Precedence {
         match kind {
             T::Number | T::Ident => Precedence::Lowest,
             T::Add | T::Sub => Precedence::Term,
-            T::Mul | T::Div => Precedence::Factor,
+            T::Mul | T::Div | T::Mod => Precedence::Factor,
+            T::StarStar => Precedence::Exponent,
             T::Eq => Precedence::Assignment,
-            T::EqEq => Precedence::Equality,
+            T::PipePipe => Precedence::LogicalOr,
+            T::AmpAmp => Precedence::LogicalAnd,
+            T::EqEq | T::NotEq => Precedence::Equality,
+            T::Less | T::LessEq | T::Greater | T::GreaterEq => Precedence::Comparison,
+            T::Pipe => Precedence::BitwiseOr,
+            T::Caret => Precedence::BitwiseXor,
+            T::Ampersand => Precedence::BitwiseAnd,
+            T::ShiftLeft | T::ShiftRight => Precedence::BitwiseShift,
+            T::DotDot | T::Ellipses => Precedence::Range,
             T::Dot | T::LeftParen | T::LeftBracket => Precedence::Call,
             // Terminators
             T::RightParen | T::RightBracket => Precedence::None,
             T::Comma => Precedence::None,
+            // Strings also close interpolated expressions.
+            T::String | T::Interpolated => Precedence::None,
             _ => Precedence::None,
         }
     }
 }
 
-impl TryFrom<i32> for Precedence {
-    type Error = Infallible;
-
+impl From<i32> for Precedence {
     #[rustfmt::skip]
-    fn try_from(value: i32) -> Result<Self, Self::Error> {
+    fn from(value: i32) -> Self {
         use Precedence as P;
         match value {
-            0  => Ok(P::None),
-            1  => Ok(P::Lowest),
-            2  => Ok(P::Assignment),
-            3  => Ok(P::Conditional),
-            4  => Ok(P::LogicalOr),
-            5  => Ok(P::LogicalAnd),
-            6  => Ok(P::Equality),
-            7  => Ok(P::Is),
-            8  => Ok(P::Comparison),
-            9  => Ok(P::BitwiseOr),
-            10 => Ok(P::BitwiseXor),
-            11 => Ok(P::BitwiseAnd),
-            12 => Ok(P::BitwiseShift),
-            13 => Ok(P::Range),
-            14 => Ok(P::Term),
-            15 => Ok(P::Factor),
-            16 => Ok(P::Unary),
-            17 => Ok(P::Call),
-            18 => Ok(P::Primary),
-            _  => Ok(P::None),
+            0  => P::None,
+            1  => P::Lowest,
+            2  => P::Assignment,
+            3  => P::Conditional,
+            4  => P::LogicalOr,
+            5  => P::LogicalAnd,
+            6  => P::Equality,
+            7  => P::Is,
+            8  => P::Comparison,
+            9  => P::BitwiseOr,
+            10 => P::BitwiseXor,
+            11 => P::BitwiseAnd,
+            12 => P::BitwiseShift,
+            13 => P::Range,
+            14 => P::Term,
+            15 => P::Factor,
+            16 => P::Unary,
+            17 => P::Exponent,
+            18 => P::Call,
+            19 => P::Primary,
+            _  => P::None,
         }
     }
 }
@@ -102,12 +118,11 @@ impl std::ops::Add<i32> for Precedence {
     type Output = Precedence;
 
     fn add(self, rhs: i32) -> Self::Output {
-        Precedence::try_from(self.as_i32() + rhs).unwrap()
+        Precedence::from(self.as_i32() + rhs)
     }
 }
 
 /// Associativity is the precedence tie-breaker.
-#[allow(dead_code)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Associativity {
     Left,
@@ -117,10 +132,12 @@ enum Associativity {
 impl Associativity {
     /// Determine the associativity of the given token kind.
     fn of(token_ty: TokenKind) -> Associativity {
-        if token_ty == TokenKind::Eq {
-            Associativity::Right
-        } else {
-            Associativity::Left
+        match token_ty {
+            // a = (b = c)
+            TokenKind::Eq => Associativity::Right,
+            // a ** (b ** c)
+            TokenKind::StarStar => Associativity::Right,
+            _ => Associativity::Left,
         }
     }
 
@@ -131,17 +148,27 @@ impl Associativity {
 
 #[derive(Debug)]
 pub enum Expr {
-    Unknown,
+    /// Expression that could not be parsed.
+    Unknown(Span),
     Unary(UnaryOp),
     Binary(BinaryOp),
     Assign(Assign),
     Num(NumLit),
+    Bool(BoolLit),
+    Str(StrLit),
+    Interp(StrInterp),
     Group(Group),
     NameAccess(NameAccess),
     MemberAccess(MemberAccess),
     MemberAssign(MemberAssign),
+    Index(Index),
+    IndexAssign(IndexAssign),
+    Struct(StructLit),
+    Func(FuncLit),
     Call(Call),
     /// Raw inlined bytecode.
+    ///
+    /// Does not originate from source code, so its span is empty.
     Bytecode(Vec<u32>),
 }
 
@@ -153,21 +180,88 @@ pub enum OperatorKind {
     Sub,
     Mul,
     Div,
+    Mod,
+    Pow,
     Assign,
     Equals,
+    NotEquals,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    /// Logical not `!`
+    Not,
+    /// Logical and `&&`
+    And,
+    /// Logical or `||`
+    Or,
+    /// Bitwise not `~`
+    BitNot,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    /// Inclusive range `..`
+    Range,
+    /// Exclusive range `...`
+    RangeExclusive,
 }
 
 /// Number literal.
 #[derive(Debug)]
 pub struct NumLit {
     pub token: Token,
-    pub value: i32,
+    /// Value typed by the literal's suffix.
+    pub value: NumValue,
+    /// Source text of the literal, so a literal without
+    /// a suffix can be converted again to another type.
+    pub text: SmolStr,
+}
+
+/// Boolean literal, either `true` or `false`.
+#[derive(Debug)]
+pub struct BoolLit {
+    pub token: Token,
+    pub value: bool,
+}
+
+/// String literal.
+#[derive(Debug)]
+pub struct StrLit {
+    pub token: Token,
+    /// Contents of the string, with quotes removed
+    /// and escape sequences converted.
+    pub value: String,
+}
+
+/// String literal with interpolated expressions.
+///
+/// ```not-rust
+/// "hello %(name), you have %(count) items"
+/// ```
+#[derive(Debug)]
+pub struct StrInterp {
+    /// String fragments and expressions in source order.
+    pub parts: Vec<InterpPart>,
+    pub span: Span,
+}
+
+/// Part of an interpolated string.
+#[derive(Debug)]
+pub enum InterpPart {
+    /// Text between interpolations.
+    Str(StrLit),
+    /// Expression enclosed in `%( )`.
+    Expr(Expr),
 }
 
 /// Grouped expression between parentises "(expr)"
 #[derive(Debug)]
 pub struct Group {
     pub expr: Box<Expr>,
+    /// Includes the parentheses.
+    pub span: Span,
 }
 
 /// Arithmetic operator
@@ -183,6 +277,7 @@ pub struct UnaryOp {
     // pub operator: Token,
     pub operator: Operator,
     pub rhs: Box<Expr>,
+    pub span: Span,
 }
 
 /// Arithmetic operation with an expression on either side.
@@ -191,6 +286,7 @@ pub struct BinaryOp {
     pub operator: Operator,
     pub lhs: Box<Expr>,
     pub rhs: Box<Expr>,
+    pub span: Span,
 }
 
 /// Assignment expression.
@@ -205,6 +301,7 @@ pub struct Assign {
     pub operator: Token,
     pub lhs: Ident,
     pub rhs: Box<Expr>,
+    pub span: Span,
 }
 
 /// Variable accessed/read.
@@ -254,6 +351,9 @@ pub struct MemberAccess {
     pub delim: Token,
     pub path: MemberPath,
     pub name: Ident,
+    /// From the start of the path up to the end of the name,
+    /// so a chain covers all of its parts.
+    pub span: Span,
 }
 
 /// Member setter assignment.
@@ -271,6 +371,73 @@ pub struct MemberAssign {
     pub name: Ident,
     pub operator: Token,
     pub rhs: Box<Expr>,
+    pub span: Span,
+}
+
+/// Subscript of a value.
+///
+/// ```not-rust
+/// foo[1]
+/// ```
+#[derive(Debug)]
+pub struct Index {
+    pub lhs: Box<Expr>,
+    pub index: Box<Expr>,
+    /// From the start of the subscripted expression up to the closing bracket.
+    pub span: Span,
+}
+
+/// Subscript setter assignment.
+///
+/// ```not-rust
+/// foo[1] = 42
+/// ```
+#[derive(Debug)]
+pub struct IndexAssign {
+    pub lhs: Box<Expr>,
+    pub index: Box<Expr>,
+    pub operator: Token,
+    pub rhs: Box<Expr>,
+    pub span: Span,
+}
+
+/// Structure literal, constructing an object.
+///
+/// ```not-rust
+/// Vec { x: 1.0, y: 2.0 }
+/// ```
+///
+/// Fields are separated by commas, newlines, or both.
+#[derive(Debug)]
+pub struct StructLit {
+    /// Name of the structure type.
+    pub name: Ident,
+    pub fields: Vec<FieldInit>,
+    /// From the type name up to the closing brace.
+    pub span: Span,
+}
+
+/// Field value of a structure literal.
+#[derive(Debug)]
+pub struct FieldInit {
+    pub name: Ident,
+    pub rhs: Expr,
+    pub span: Span,
+}
+
+/// Anonymous function, which evaluates to a function value.
+///
+/// ```not-rust
+/// func(a: i32) -> i32 { return a * 2 }
+/// ```
+#[derive(Debug)]
+pub struct FuncLit {
+    pub keyword: Token,
+    pub args: Delimited<FuncArg, Separator>,
+    pub rtn: Option<FuncRtn>,
+    pub body: Block,
+    /// From the `func` keyword up to the closing brace of the body.
+    pub span: Span,
 }
 
 /// Call to a function.
@@ -283,6 +450,8 @@ pub struct Call {
     /// Expression that evaluates to a callable.
     pub callee: Box<Expr>,
     pub args: Vec<CallArg>,
+    /// From the start of the callee up to the closing parenthesis.
+    pub span: Span,
 }
 
 /// Call argument.
@@ -320,16 +489,30 @@ impl Parse for Expr {
     }
 }
 
+impl Expr {
+    /// Parse an expression, recovering from syntax errors.
+    ///
+    /// On error the parser skips ahead to the next statement. The error
+    /// is recorded in the token stream, and the skipped source code is
+    /// replaced with an [`Expr::Unknown`] placeholder.
+    pub(crate) fn parse_or_recover(input: &mut TokenStream) -> Expr {
+        input.ignore_many(TokenKind::Whitespace);
+        let start = input.pos();
+
+        Expr::parse(input).unwrap_or_else(|err| Expr::Unknown(input.recover(start, err)))
+    }
+}
+
 /// Recursive parsing methods
 impl Expr {
     /// Entrypoint for the top-down precedence parser.
     ///
     /// The implementation is a straight forward Pratt parser.
     fn parse_precedence(input: &mut TokenStream, precedence: Precedence) -> ParseResult<Expr> {
-        println!("Expr::parse_precedence(_, {:?})", precedence);
+        log::trace!("Expr::parse_precedence(_, {:?})", precedence);
 
         input.ignore_many(TokenKind::Whitespace);
-        let token = input.next_token().ok_or_else(|| syntax_err("unexpected end-of-file"))?;
+        let token = input.next_token().ok_or_else(|| eof_err(input))?;
 
         let mut left = Self::parse_prefix(input, token)?;
 
@@ -348,26 +531,18 @@ impl Expr {
 
             // There is no expression right of the last one, so we just return what we have.
             if input.peek().map(|t| t.kind).is_none() {
-                println!("Expr::parse_precedence; end-of-expression");
+                log::trace!("Expr::parse_precedence; end-of-expression");
                 return Ok(left);
             }
 
-            let token = input.next_token().ok_or_else(|| syntax_err("expression expected"))?;
+            let token = input.next_token().ok_or_else(|| eof_err(input))?;
             left = Self::parse_infix(input, left, token)?;
 
             input.ignore_many(TokenKind::Whitespace);
         }
 
         input.reset_peek();
-        println!(
-            "Expr::parse_precedence; precedence high; {} <= {}; peek() -> {:?}",
-            precedence,
-            input.peek().map(|t| Precedence::of(t.kind)).unwrap_or(Precedence::None),
-            {
-                input.reset_peek();
-                input.peek().map(|t| t.kind)
-            }
-        );
+        log::trace!("Expr::parse_precedence; end; precedence {}", precedence);
         Ok(left)
     }
 
@@ -378,17 +553,33 @@ impl Expr {
         use Keyword as K;
         use TokenKind as T;
 
-        println!("Expr::parse_prefix(_, {:?})", token.kind);
+        log::trace!("Expr::parse_prefix(_, {:?})", token.kind);
 
         match token.kind {
             T::Number => Expr::parse_number_literal(input, token).map(Expr::Num),
-            T::LeftParen => Expr::parse_group(input).map(Expr::Group),
+            T::Keyword(K::True) => Ok(Expr::Bool(BoolLit { token, value: true })),
+            T::Keyword(K::False) => Ok(Expr::Bool(BoolLit { token, value: false })),
+            T::String => Expr::parse_string_literal(input, token).map(Expr::Str),
+            T::Interpolated => Expr::parse_interpolation(input, token).map(Expr::Interp),
+            T::LeftParen => Expr::parse_group(input, token).map(Expr::Group),
             T::Ident => Expr::parse_postfix(input, token),
-            T::Keyword(K::Func) => todo!("anonymous function"),
-            T::Sub => {
+            T::Keyword(K::Func) => Expr::parse_func_lit(input, token).map(Expr::Func),
+            T::Sub if Expr::is_negative_literal(input, &token) => {
+                // Fold the minus into the number literal.
+                let number = input.next_token().ok_or_else(|| eof_err(input))?;
+                let token = Token {
+                    offset: token.offset,
+                    size: token.size + number.size,
+                    kind: T::Number,
+                };
+                Expr::parse_number_literal(input, token).map(Expr::Num)
+            }
+            T::Sub | T::Bang | T::Tilde => {
                 // Negate
                 let kind = match token.kind {
                     T::Sub => OperatorKind::Neg,
+                    T::Bang => OperatorKind::Not,
+                    T::Tilde => OperatorKind::BitNot,
                     _ => unreachable!("all outer match cases must be covered in inner match"),
                 };
 
@@ -396,14 +587,19 @@ impl Expr {
 
                 Expr::parse_precedence(input, Precedence::Unary)
                     .map(|right| UnaryOp {
+                        span: operator.span().to(right.span()),
                         operator,
                         rhs: Box::new(right),
                     })
                     .map(Expr::Unary)
             }
+            // Lexer could not make sense of the source text.
+            T::Error(_) => Err(ParseError::Lex(
+                token.error().expect("error token must carry lexical error"),
+            )),
             // When this match fails, it means there is no parselet for the token, meaning
             // some invalid token is in an unexpected position.
-            _ => Err(syntax_err("expression expected")),
+            _ => Err(syntax_err(&token, "expression expected")),
         }
     }
 
@@ -413,7 +609,7 @@ impl Expr {
     fn parse_infix(input: &mut TokenStream, left: Expr, token: Token) -> ParseResult<Expr> {
         use TokenKind as T;
 
-        println!("Expr::parse_infix(_, {:?})", token.kind);
+        log::trace!("Expr::parse_infix(_, {:?})", token.kind);
 
         let precedence = Precedence::of(token.kind);
 
@@ -436,21 +632,59 @@ impl Expr {
 
         match token.kind {
             // Binary Operators
-            T::Add | T::Sub | T::Mul | T::Div | T::Eq | T::EqEq => {
+            T::Add
+            | T::Sub
+            | T::Mul
+            | T::Div
+            | T::Mod
+            | T::StarStar
+            | T::Eq
+            | T::EqEq
+            | T::NotEq
+            | T::Less
+            | T::LessEq
+            | T::Greater
+            | T::GreaterEq
+            | T::AmpAmp
+            | T::PipePipe
+            | T::Ampersand
+            | T::Pipe
+            | T::Caret
+            | T::ShiftLeft
+            | T::ShiftRight
+            | T::DotDot
+            | T::Ellipses => {
                 // FIXME: Does `Eq` for assignment belong here when it's covered by postfix?
                 let kind = match token.kind {
                     T::Add => OperatorKind::Add,
                     T::Sub => OperatorKind::Sub,
                     T::Mul => OperatorKind::Mul,
                     T::Div => OperatorKind::Div,
+                    T::Mod => OperatorKind::Mod,
+                    T::StarStar => OperatorKind::Pow,
                     T::Eq => OperatorKind::Assign,
                     T::EqEq => OperatorKind::Equals,
+                    T::NotEq => OperatorKind::NotEquals,
+                    T::Less => OperatorKind::Less,
+                    T::LessEq => OperatorKind::LessEq,
+                    T::Greater => OperatorKind::Greater,
+                    T::GreaterEq => OperatorKind::GreaterEq,
+                    T::AmpAmp => OperatorKind::And,
+                    T::PipePipe => OperatorKind::Or,
+                    T::Ampersand => OperatorKind::BitAnd,
+                    T::Pipe => OperatorKind::BitOr,
+                    T::Caret => OperatorKind::BitXor,
+                    T::ShiftLeft => OperatorKind::ShiftLeft,
+                    T::ShiftRight => OperatorKind::ShiftRight,
+                    T::DotDot => OperatorKind::Range,
+                    T::Ellipses => OperatorKind::RangeExclusive,
                     _ => unreachable!("all outer match cases must be covered in inner match"),
                 };
 
                 let operator = Operator { kind, token };
 
                 let binary_op = BinaryOp {
+                    span: left.span().to(right.span()),
                     operator,
                     lhs: Box::new(left),
                     rhs: Box::new(right),
@@ -458,26 +692,121 @@ impl Expr {
 
                 Ok(Expr::Binary(binary_op))
             }
-            _ => Err(syntax_err("infix expression expected")),
+            _ => Err(syntax_err(&token, "infix expression expected")),
         }
     }
 }
 
 impl Expr {
+    /// Check whether the minus token negates only the number literal
+    /// directly following it, so it can be folded into the literal.
+    ///
+    /// The literal is then range checked with its sign, which allows
+    /// the most negative integers to be written. Operators that bind
+    /// tighter than negation, like in `-2 ** 2`, prevent the fold.
+    fn is_negative_literal(input: &mut TokenStream, minus: &Token) -> bool {
+        use TokenKind as T;
+
+        input.reset_peek();
+
+        let is_adjacent = match input.peek() {
+            Some(token) => token.kind == T::Number && token.offset.to_u32() == minus.offset.to_u32() + minus.size,
+            None => false,
+        };
+
+        let mut next = input.peek_kind();
+        while next == Some(T::Whitespace) {
+            next = input.peek_kind();
+        }
+
+        input.reset_peek();
+
+        is_adjacent && next.map(Precedence::of).unwrap_or(Precedence::None) <= Precedence::Unary
+    }
+
     fn parse_number_literal(input: &mut TokenStream, token: Token) -> ParseResult<NumLit> {
-        // TODO: Different number formats. binary, octal, decimal, hex, scientific
+        // The lexer has validated the literal's form, but its range is
+        // checked here, after a preceding minus has been folded in.
+        let text = SmolStr::from(input.token_fragment(&token));
+        let value = parse_number(&text).map_err(|kind| LexError {
+            kind,
+            offset: token.offset,
+            size: token.size,
+        })?;
+        Ok(NumLit { token, value, text })
+    }
+
+    /// Parse a string literal, or the last part of an interpolated string.
+    fn parse_string_literal(input: &mut TokenStream, token: Token) -> ParseResult<StrLit> {
+        // Strip the opening quote, or closing parenthesis of
+        // the previous interpolation, and the closing quote.
+        Expr::parse_string_fragment(input, token, 1)
+    }
+
+    /// Parse a string literal containing interpolated expressions.
+    ///
+    /// The lexer splits the string into parts at each interpolation,
+    /// with the expression's tokens in between. The last part is a
+    /// plain string token.
+    fn parse_interpolation(input: &mut TokenStream, token: Token) -> ParseResult<StrInterp> {
+        use TokenKind as T;
+
+        let start = token.span();
+
+        // Strip the trailing "%(" delimiter.
+        let mut parts = vec![InterpPart::Str(Expr::parse_string_fragment(input, token, 2)?)];
+
+        loop {
+            parts.push(InterpPart::Expr(Expr::parse(input)?));
+
+            input.ignore_many(T::Whitespace);
+            let token = input.next_token().ok_or_else(|| eof_err(input))?;
+
+            match token.kind {
+                T::Interpolated => {
+                    parts.push(InterpPart::Str(Expr::parse_string_fragment(input, token, 2)?));
+                }
+                T::String => {
+                    let end = token.span();
+                    parts.push(InterpPart::Str(Expr::parse_string_literal(input, token)?));
+                    return Ok(StrInterp {
+                        parts,
+                        span: start.to(end),
+                    });
+                }
+                T::Error(_) => {
+                    return Err(ParseError::Lex(
+                        token.error().expect("error token must carry lexical error"),
+                    ))
+                }
+                _ => return Err(syntax_err(&token, "expected ')' to close string interpolation")),
+            }
+        }
+    }
+
+    /// Convert the text of a string token, excluding its single character
+    /// opening delimiter and the closing delimiter of the given size.
+    fn parse_string_fragment(input: &mut TokenStream, token: Token, end_size: usize) -> ParseResult<StrLit> {
         let fragment = input.token_fragment(&token);
-        let value = i32::from_str_radix(fragment, 10)
-            .map_err(|err| syntax_err(format!("failed to parse number literal: {}", err)))?;
-        Ok(NumLit { token, value })
+
+        // Lexer guarantees the delimiters and escape sequences are valid.
+        debug_assert!(fragment.len() > end_size, "string literal must be delimited");
+        let value = unescape(&fragment[1..fragment.len() - end_size]).map_err(|kind| LexError {
+            kind,
+            offset: token.offset,
+            size: token.size,
+        })?;
+
+        Ok(StrLit { token, value })
     }
 
     /// Parse expression contained in parentheses.
-    fn parse_group(input: &mut TokenStream) -> ParseResult<Group> {
-        println!("Expr::parse_group(_)");
+    fn parse_group(input: &mut TokenStream, left_paren: Token) -> ParseResult<Group> {
+        log::trace!("Expr::parse_group(_)");
         let expr = Box::new(Expr::parse(input)?);
-        input.consume(TokenKind::RightParen)?;
-        Ok(Group { expr })
+        let right_paren = input.consume(TokenKind::RightParen)?;
+        let span = left_paren.span().to(right_paren.span());
+        Ok(Group { expr, span })
     }
 
     /// Parse a postfix expression, triggered by encoutering a variable name.
@@ -487,10 +816,11 @@ impl Expr {
     ///
     /// - dot delimited member access
     /// - function call
+    /// - subscript
     fn parse_postfix(input: &mut TokenStream, token: Token) -> ParseResult<Expr> {
         use TokenKind as T;
 
-        println!("Expr::parse_name(_, {:?})", token.kind);
+        log::trace!("Expr::parse_name(_, {:?})", token.kind);
         debug_assert_eq!(token.kind, T::Ident, "expected identifier");
 
         // Start the parser with an initial expression.
@@ -513,62 +843,111 @@ impl Expr {
                     //
                     // When we encounter an equality token we need to "rewind"
                     // and change the previous expression.
+                    let operator = input.consume(T::Eq)?;
                     match expr {
                         Expr::NameAccess(NameAccess { ident }) => {
                             // Simple assignment where the LHS is a variable name.
                             // foobar = 42
-                            let operator = input.consume(T::Eq)?;
                             let lhs = ident;
                             let rhs = Expr::parse(input).map(Box::new)?;
-                            Expr::Assign(Assign { operator, lhs, rhs })
+                            let span = lhs.span().to(rhs.span());
+                            Expr::Assign(Assign {
+                                operator,
+                                lhs,
+                                rhs,
+                                span,
+                            })
                         }
-                        Expr::MemberAccess(MemberAccess { delim, path, name }) => {
+                        Expr::MemberAccess(MemberAccess {
+                            delim,
+                            path,
+                            name,
+                            span: path_span,
+                        }) => {
                             // Member setter where the LHS is a member of an object.
                             // foo.bar.baz = 42
-                            let operator = input.consume(T::Eq)?;
                             let rhs = Expr::parse(input).map(Box::new)?;
+                            let span = path_span.to(rhs.span());
                             Expr::MemberAssign(MemberAssign {
                                 path,
                                 delim,
                                 name,
                                 operator,
                                 rhs,
+                                span,
+                            })
+                        }
+                        Expr::Index(Index {
+                            lhs,
+                            index,
+                            span: index_span,
+                        }) => {
+                            // Subscript setter where the LHS is an element of a value.
+                            // foo[1] = 42
+                            let rhs = Expr::parse(input).map(Box::new)?;
+                            let span = index_span.to(rhs.span());
+                            Expr::IndexAssign(IndexAssign {
+                                lhs,
+                                index,
+                                operator,
+                                rhs,
+                                span,
                             })
                         }
                         _ => {
                             // Previous expression is a type that is not
                             // supported as the LHS of an assignment.
-                            return Err(syntax_err("lhs of assignment must be identifier or member path"));
+                            return Err(syntax_err(
+                                &operator,
+                                "lhs of assignment must be identifier, member path or subscript",
+                            ));
                         }
                     }
                 }
-                Some(T::LeftBracket) => todo!("parse subscript"),
+                Some(T::LeftBrace) if matches!(expr, Expr::NameAccess(_)) && Expr::is_struct_lit(input) => {
+                    let Expr::NameAccess(NameAccess { ident }) = expr else {
+                        unreachable!("struct literal must start with type name");
+                    };
+                    Expr::parse_struct_lit(input, ident).map(Expr::Struct)?
+                }
+                Some(T::LeftBracket) => {
+                    input.consume(T::LeftBracket)?;
+                    let index = Expr::parse(input).map(Box::new)?;
+                    input.ignore_many(TokenKind::Whitespace);
+                    let right_bracket = input.consume(T::RightBracket)?;
+                    let lhs = Box::new(expr);
+                    let span = lhs.span().to(right_bracket.span());
+                    Expr::Index(Index { lhs, index, span })
+                }
                 Some(T::LeftParen) => {
-                    println!("Expr::parse_name(_, _) - parse call");
+                    log::trace!("Expr::parse_name(_, _) - parse call");
                     input.consume(T::LeftParen)?;
                     input.ignore_many(TokenKind::Whitespace);
                     let args = Expr::parse_call_arguments(input)?;
                     let callee = Box::new(expr);
                     input.ignore_many(TokenKind::Whitespace);
-                    input.consume(T::RightParen)?;
-                    Expr::Call(Call { callee, args })
+                    let right_paren = input.consume(T::RightParen)?;
+                    let span = callee.span().to(right_paren.span());
+                    Expr::Call(Call { callee, args, span })
                 }
                 Some(T::Dot) => {
                     let delim = input.consume(T::Dot)?;
                     let lhs = match expr {
                         Expr::NameAccess(NameAccess { ident }) => MemberPath::Name(ident),
                         Expr::MemberAccess(member_access) => MemberPath::Path(Box::new(member_access)),
-                        _ => return Err(syntax_err("member access not valid")),
+                        _ => return Err(syntax_err(&delim, "member access not valid")),
                     };
                     let rhs = Ident::parse(input)?;
+                    let span = lhs.span().to(rhs.span());
                     Expr::MemberAccess(MemberAccess {
                         delim,
                         path: lhs,
                         name: rhs,
+                        span,
                     })
                 }
                 Some(_) | None => {
-                    println!("Expr::parse_name(_, _) - end");
+                    log::trace!("Expr::parse_name(_, _) - end");
                     // End
                     break;
                 }
@@ -578,13 +957,133 @@ impl Expr {
         Ok(expr)
     }
 
-    /// Parse a variable name.
-    ///
-    /// Depending on what follows the variable's identifier, the bare
-    /// name can be part of the following:
+    /// Checks whether the upcoming opening brace starts a structure literal,
+    /// rather than a block following an expression, like the condition of `if`.
     ///
-    /// - dot delimited member access
-    /// - function call
+    /// The first field of a literal starts with `name:`, which
+    /// can't be the start of a statement.
+    fn is_struct_lit(input: &mut TokenStream) -> bool {
+        use TokenKind as T;
+
+        input.reset_peek();
+
+        let mut is_struct_lit = false;
+        if input.peek_kind() == Some(T::LeftBrace) {
+            let mut kind = input.peek_kind();
+            while matches!(kind, Some(T::Whitespace | T::Newline)) {
+                kind = input.peek_kind();
+            }
+
+            if kind == Some(T::Ident) {
+                let mut kind = input.peek_kind();
+                while kind == Some(T::Whitespace) {
+                    kind = input.peek_kind();
+                }
+                is_struct_lit = kind == Some(T::Colon);
+            }
+        }
+
+        input.reset_peek();
+        is_struct_lit
+    }
+
+    fn parse_struct_lit(input: &mut TokenStream, name: Ident) -> ParseResult<StructLit> {
+        use TokenKind as T;
+
+        log::trace!("Expr::parse_struct_lit(_, {:?})", name.text);
+
+        input.consume(T::LeftBrace)?;
+
+        let mut fields = vec![];
+
+        let right_brace = loop {
+            input.ignore_while(|kind| matches!(kind, T::Whitespace | T::Newline));
+
+            if input.peek_kind() == Some(T::RightBrace) {
+                input.reset_peek();
+                break input.consume(T::RightBrace)?;
+            }
+            input.reset_peek();
+
+            // field
+            let field_name = Ident::parse(input)?;
+            input.ignore_many(T::Whitespace);
+            input.consume(T::Colon)?;
+            let rhs = Expr::parse(input)?;
+            let span = field_name.span().to(rhs.span());
+            fields.push(FieldInit {
+                name: field_name,
+                rhs,
+                span,
+            });
+            input.ignore_many(T::Whitespace);
+
+            // separator
+            match input.peek() {
+                Some(token) => match token.kind {
+                    T::Comma | T::Newline => {
+                        input.next_token();
+                    }
+                    T::RightBrace => { /* end of fields */ }
+                    T::EOF => return Err(syntax_err(token, "unexpected end-of-file")),
+                    _ => return Err(syntax_err(token, "expected ',' or newline after struct field")),
+                },
+                None => return Err(eof_err(input)),
+            }
+            input.reset_peek();
+        };
+
+        let span = name.span().to(right_brace.span());
+
+        Ok(StructLit { name, fields, span })
+    }
+
+    /// Parse an anonymous function, where the `func` keyword has been consumed.
+    fn parse_func_lit(input: &mut TokenStream, keyword: Token) -> ParseResult<FuncLit> {
+        use TokenKind as T;
+
+        log::trace!("Expr::parse_func_lit(_)");
+
+        input.ignore_many(T::Whitespace);
+
+        // arguments
+        input.consume(T::LeftParen)?;
+        let args = Delimited::<FuncArg, Separator>::parse(input)?;
+        input.ignore_many(T::Whitespace);
+        input.consume(T::RightParen)?;
+        input.ignore_many(T::Whitespace);
+
+        // optional return
+        let rtn = if let Ok(arrow) = input.consume(T::ThinArrow) {
+            let ty = Type::parse(input)?;
+            let span = arrow.span().to(ty.span());
+            Some(FuncRtn { ty, span })
+        } else {
+            None
+        };
+
+        // The body is part of the expression, so it
+        // doesn't need to end the statement.
+        let body = Block::parse_braces(input)?;
+
+        let span = keyword.span().to(body.span());
+
+        Ok(FuncLit {
+            keyword,
+            args,
+            rtn,
+            body,
+            span,
+        })
+    }
+
+    // Parse a variable name.
+    //
+    // Depending on what follows the variable's identifier, the bare
+    // name can be part of the following:
+    //
+    // - dot delimited member access
+    // - function call
     // fn parse_bare_name(input: &mut TokenStream, token: Token) -> ParseResult<Expr> {
     //     use TokenKind as T;
 
@@ -655,7 +1154,7 @@ impl Expr {
     fn parse_call_arguments(input: &mut TokenStream) -> ParseResult<Vec<CallArg>> {
         use TokenKind as T;
 
-        println!("Expr::parse_call_arguments(_)");
+        log::trace!("Expr::parse_call_arguments(_)");
 
         let mut args = vec![];
 
@@ -727,6 +1226,38 @@ impl Expr {
         }
     }
 
+    /// Structure literal expression.
+    pub fn expr_struct_lit(&self) -> Option<&StructLit> {
+        match self {
+            Expr::Struct(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Anonymous function expression.
+    pub fn expr_func_lit(&self) -> Option<&FuncLit> {
+        match self {
+            Expr::Func(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Subscript expression.
+    pub fn expr_index(&self) -> Option<&Index> {
+        match self {
+            Expr::Index(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Subscript setter expression.
+    pub fn expr_index_assign(&self) -> Option<&IndexAssign> {
+        match self {
+            Expr::IndexAssign(e) => Some(e),
+            _ => None,
+        }
+    }
+
     /// Number literal expression.
     pub fn expr_num_lit(&self) -> Option<&NumLit> {
         match self {
@@ -735,6 +1266,30 @@ impl Expr {
         }
     }
 
+    /// Boolean literal expression.
+    pub fn expr_bool_lit(&self) -> Option<&BoolLit> {
+        match self {
+            Expr::Bool(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// String literal expression.
+    pub fn expr_str_lit(&self) -> Option<&StrLit> {
+        match self {
+            Expr::Str(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Interpolated string expression.
+    pub fn expr_str_interp(&self) -> Option<&StrInterp> {
+        match self {
+            Expr::Interp(e) => Some(e),
+            _ => None,
+        }
+    }
+
     pub fn expr_group(&self) -> Option<&Group> {
         match self {
             Expr::Group(e) => Some(e),
@@ -753,7 +1308,7 @@ impl MemberPath {
 
     pub fn path(&self) -> Option<&MemberAccess> {
         match self {
-            MemberPath::Path(member_access) => Some(&*member_access),
+            MemberPath::Path(member_access) => Some(member_access),
             MemberPath::Name(_) => None,
         }
     }
@@ -767,3 +1322,152 @@ impl CallArg {
         }
     }
 }
+
+impl Spanned for Expr {
+    fn span(&self) -> Span {
+        match self {
+            Expr::Unknown(span) => *span,
+            Expr::Unary(unary) => unary.span,
+            Expr::Binary(binary) => binary.span,
+            Expr::Assign(assign) => assign.span,
+            Expr::Num(num) => num.span(),
+            Expr::Bool(boolean) => boolean.span(),
+            Expr::Str(string) => string.span(),
+            Expr::Interp(interp) => interp.span,
+            Expr::Group(group) => group.span,
+            Expr::NameAccess(access) => access.span(),
+            Expr::MemberAccess(access) => access.span,
+            Expr::MemberAssign(assign) => assign.span,
+            Expr::Index(index) => index.span,
+            Expr::IndexAssign(assign) => assign.span,
+            Expr::Struct(lit) => lit.span,
+            Expr::Func(lit) => lit.span,
+            Expr::Call(call) => call.span,
+            Expr::Bytecode(_) => Span::default(),
+        }
+    }
+}
+
+impl Spanned for NumLit {
+    fn span(&self) -> Span {
+        self.token.span()
+    }
+}
+
+impl Spanned for StructLit {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for FuncLit {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for FieldInit {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for BoolLit {
+    fn span(&self) -> Span {
+        self.token.span()
+    }
+}
+
+impl Spanned for StrLit {
+    fn span(&self) -> Span {
+        self.token.span()
+    }
+}
+
+impl Spanned for StrInterp {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for InterpPart {
+    fn span(&self) -> Span {
+        match self {
+            InterpPart::Str(string) => string.span(),
+            InterpPart::Expr(expr) => expr.span(),
+        }
+    }
+}
+
+impl Spanned for Group {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Operator {
+    fn span(&self) -> Span {
+        self.token.span()
+    }
+}
+
+impl Spanned for UnaryOp {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for BinaryOp {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Assign {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for NameAccess {
+    fn span(&self) -> Span {
+        self.ident.span()
+    }
+}
+
+impl Spanned for MemberPath {
+    fn span(&self) -> Span {
+        match self {
+            MemberPath::Name(ident) => ident.span(),
+            MemberPath::Path(access) => access.span,
+        }
+    }
+}
+
+impl Spanned for MemberAccess {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for MemberAssign {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Call {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for CallArg {
+    fn span(&self) -> Span {
+        match self {
+            CallArg::Simple(expr) => expr.span(),
+            CallArg::Named { name, rhs } => name.span().to(rhs.span()),
+            CallArg::Block(block) => block.span(),
+        }
+    }
+}
@@ -1,38 +1,67 @@
 //! Function declarations.
 use std::cell::Cell;
 
-use vuur_lexer::{Keyword, TokenKind};
+use smol_str::SmolStr;
+use vuur_lexer::span::{Span, Spanned};
+use vuur_lexer::{Keyword, Token, TokenKind};
 
 use crate::block::Block;
 use crate::delim::Delimited;
+use crate::doc::DocComment;
 use crate::ident::Ident;
 use crate::stream::TokenStream;
 use crate::ty::Type;
-use crate::{Parse, ParseResult};
+use crate::{eof_err, Parse, ParseResult};
 
 /// Function definition statement.
 #[derive(Debug)]
 pub struct FuncDef {
+    /// Documentation comment, attached by the statement parser.
+    pub doc: Option<DocComment>,
+    /// Receiver of a method, or `None` for a plain function.
+    pub receiver: Option<Receiver>,
     pub name: Ident,
     pub args: Delimited<FuncArg, Separator>,
     pub rtn: Option<FuncRtn>,
     pub body: Block,
     pub symbol: Cell<u32>,
+    /// From the `func` keyword up to the closing brace of the body.
+    pub span: Span,
+}
+
+/// Method receiver, declared between the `func` keyword and the method name.
+///
+/// Named receivers look like `(vec: *Vec)`, while anonymous
+/// receivers like `(*Vec)` are bound to [`Receiver::SELF`].
+#[derive(Debug)]
+pub struct Receiver {
+    pub name: Option<Ident>,
+    pub ty: Ident,
+    /// Receiver type is a pointer, like `*Vec`.
+    pub is_ptr: bool,
+    /// From the opening to the closing parenthesis.
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub struct FuncArg {
     pub name: Ident,
-    pub ty: Ident,
+    pub ty: Type,
     pub is_ref: bool,
+    pub span: Span,
 }
 
+/// Comma separating function arguments.
 #[derive(Debug)]
-pub struct Separator;
+pub struct Separator {
+    pub token: Token,
+}
 
 #[derive(Debug)]
 pub struct FuncRtn {
     pub ty: Type,
+    /// From the arrow up to the end of the type.
+    pub span: Span,
 }
 
 impl Parse for FuncDef {
@@ -45,11 +74,24 @@ impl Parse for FuncDef {
         input.ignore_many(T::Whitespace);
 
         // keyword
-        input.consume(T::Keyword(K::Func))?;
+        let keyword = input.consume(T::Keyword(K::Func))?;
         input.ignore_many(T::Whitespace);
 
-        // name
-        let name = Ident::parse(input)?;
+        // optional receiver
+        input.reset_peek();
+        let receiver = if input.peek_kind() == Some(T::LeftParen) {
+            Some(Receiver::parse(input)?)
+        } else {
+            None
+        };
+        input.ignore_many(T::Whitespace);
+
+        // name, which for methods can be an operator
+        let name = if receiver.is_some() {
+            Self::parse_method_name(input)?
+        } else {
+            Ident::parse(input)?
+        };
         input.ignore_many(T::Whitespace);
 
         // arguments
@@ -60,9 +102,10 @@ impl Parse for FuncDef {
         input.ignore_many(T::Whitespace);
 
         // optional return
-        let rtn = if input.consume(T::ThinArrow).is_ok() {
+        let rtn = if let Ok(arrow) = input.consume(T::ThinArrow) {
             let ty = Type::parse(input)?;
-            Some(FuncRtn { ty })
+            let span = arrow.span().to(ty.span());
+            Some(FuncRtn { ty, span })
         } else {
             None
         };
@@ -73,16 +116,130 @@ impl Parse for FuncDef {
         // symbol assigned by compiler later
         let symbol = Cell::new(0);
 
+        let span = keyword.span().to(body.span());
+
         Ok(FuncDef {
+            doc: None,
+            receiver,
             name,
             args,
             rtn,
             body,
             symbol,
+            span,
         })
     }
 }
 
+impl FuncDef {
+    /// Parse the name of a method, which can also be the symbol of an
+    /// overloaded operator. These are the arithmetic and comparison
+    /// operators, and `[]` and `[]=` for getting and setting subscripts.
+    fn parse_method_name(input: &mut TokenStream) -> ParseResult<Ident> {
+        use TokenKind as T;
+
+        input.reset_peek();
+        match input.peek_kind() {
+            Some(
+                T::Add
+                | T::Sub
+                | T::Mul
+                | T::Div
+                | T::Mod
+                | T::EqEq
+                | T::NotEq
+                | T::Less
+                | T::LessEq
+                | T::Greater
+                | T::GreaterEq,
+            ) => {
+                let token = input.next_token().ok_or_else(|| eof_err(input))?;
+                let text = SmolStr::from(input.token_fragment(&token));
+                Ok(Ident { text, token })
+            }
+            Some(T::LeftBracket) => {
+                // Brackets are adjacent, so they read as one symbol.
+                let left = input.consume(T::LeftBracket)?;
+                let mut end = input.consume(T::RightBracket)?;
+                if let Ok(eq) = input.consume(T::Eq) {
+                    end = eq;
+                }
+
+                let span = left.span().to(end.span());
+                let token = Token {
+                    offset: span.start,
+                    size: span.end.to_u32() - span.start.to_u32(),
+                    kind: T::LeftBracket,
+                };
+                let text = SmolStr::from(input.token_fragment(&token));
+                Ok(Ident { text, token })
+            }
+            _ => Ident::parse(input),
+        }
+    }
+}
+
+impl Receiver {
+    /// Name that anonymous receivers are bound to.
+    pub const SELF: &'static str = "self";
+
+    /// Look ahead for a receiver name, which is an identifier followed by a colon.
+    fn is_named(input: &mut TokenStream) -> bool {
+        use TokenKind as T;
+
+        input.reset_peek();
+
+        let mut is_named = false;
+        if input.peek_kind() == Some(T::Ident) {
+            let mut kind = input.peek_kind();
+            while kind == Some(T::Whitespace) {
+                kind = input.peek_kind();
+            }
+            is_named = kind == Some(T::Colon);
+        }
+
+        input.reset_peek();
+        is_named
+    }
+
+    /// Name the receiver is bound to in the method body.
+    pub fn name_text(&self) -> &str {
+        self.name.as_ref().map(|name| name.text.as_str()).unwrap_or(Self::SELF)
+    }
+}
+
+impl Parse for Receiver {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use TokenKind as T;
+
+        let left = input.consume(T::LeftParen)?;
+        input.ignore_many(T::Whitespace);
+
+        // A name followed by a colon binds the receiver to that name.
+        let name = if Self::is_named(input) {
+            let name = Ident::parse(input)?;
+            input.ignore_many(T::Whitespace);
+            input.consume(T::Colon)?;
+            input.ignore_many(T::Whitespace);
+            Some(name)
+        } else {
+            None
+        };
+
+        let is_ptr = input.consume(T::Mul).is_ok();
+        input.ignore_many(T::Whitespace);
+        let ty = Ident::parse(input)?;
+        input.ignore_many(T::Whitespace);
+        let right = input.consume(T::RightParen)?;
+
+        let span = left.span().to(right.span());
+
+        Ok(Receiver { name, ty, is_ptr, span })
+    }
+}
+
 impl Parse for FuncArg {
     type Output = Self;
 
@@ -96,9 +253,11 @@ impl Parse for FuncArg {
         input.ignore_many(T::Whitespace);
         let is_ref = input.consume(TokenKind::Ampersand).is_ok();
         input.ignore_many(T::Whitespace);
-        let ty = Ident::parse(input)?;
+        let ty = Type::parse(input)?;
+
+        let span = name.span().to(ty.span());
 
-        Ok(FuncArg { name, ty, is_ref })
+        Ok(FuncArg { name, ty, is_ref, span })
     }
 }
 
@@ -106,7 +265,37 @@ impl Parse for Separator {
     type Output = Self;
 
     fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
-        input.consume(TokenKind::Comma)?;
-        Ok(Separator)
+        let token = input.consume(TokenKind::Comma)?;
+        Ok(Separator { token })
+    }
+}
+
+impl Spanned for FuncDef {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Receiver {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for FuncArg {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Separator {
+    fn span(&self) -> Span {
+        self.token.span()
+    }
+}
+
+impl Spanned for FuncRtn {
+    fn span(&self) -> Span {
+        self.span
     }
 }
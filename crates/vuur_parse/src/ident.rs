@@ -1,6 +1,7 @@
 //! Identifier.
 
 use smol_str::SmolStr;
+use vuur_lexer::span::{Span, Spanned};
 use vuur_lexer::{Token, TokenKind};
 
 use crate::{stream::TokenStream, Parse, ParseResult};
@@ -21,3 +22,9 @@ impl Parse for Ident {
         Ok(Ident { text, token })
     }
 }
+
+impl Spanned for Ident {
+    fn span(&self) -> Span {
+        self.token.span()
+    }
+}
@@ -2,16 +2,18 @@
 use crate::module::VuurModule;
 use crate::stream::TokenError;
 use crate::stream::TokenStream;
-use vuur_lexer::span::BytePos;
-use vuur_lexer::Lexer;
+use vuur_lexer::span::{BytePos, Span, Spanned};
+use vuur_lexer::{LexError, Lexer, Token, TokenKind};
 
 mod block;
 pub mod cond;
 pub mod delim;
+pub mod doc;
 pub mod error;
 pub mod expr;
 pub mod func;
 pub mod ident;
+pub mod loops;
 pub mod module;
 pub mod pprint;
 pub mod stmt;
@@ -19,10 +21,41 @@ pub mod stream;
 pub mod ty;
 pub mod var;
 
+/// Parse source code into a module.
+///
+/// Returns the first syntax error encountered.
 pub fn parse_str(source: &str) -> ParseResult<VuurModule> {
+    let (module, diagnostics) = parse_str_with_diagnostics(source);
+
+    match diagnostics.into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(module),
+    }
+}
+
+/// Parse source code into a module, recovering from syntax errors.
+///
+/// Returns the partial module, where the source code that could not
+/// be parsed is replaced by placeholder nodes, and every syntax error
+/// encountered in source order.
+pub fn parse_str_with_diagnostics(source: &str) -> (VuurModule, Vec<ParseError>) {
     let lexer = Lexer::from_source(source);
     let mut stream = TokenStream::new(lexer);
-    VuurModule::parse(&mut stream)
+    let result = VuurModule::parse(&mut stream);
+    let mut diagnostics = stream.take_diagnostics();
+
+    let module = match result {
+        Ok(module) => module,
+        Err(err) => {
+            diagnostics.push(err);
+            VuurModule {
+                stmts: Vec::new(),
+                span: Span::new(BytePos::from_u32(0), stream.end_pos()),
+            }
+        }
+    };
+
+    (module, diagnostics)
 }
 
 pub trait Parse {
@@ -35,22 +68,64 @@ type ParseResult<T> = Result<T, ParseError>;
 
 #[derive(Debug)]
 pub enum ParseError {
-    Syntax { msg: String },
+    /// Source code that does not fit the grammar.
+    Syntax {
+        msg: String,
+        /// Source code of the offending token.
+        span: Span,
+        found: TokenKind,
+    },
+    /// Specific token was expected, but another was found.
     Token(crate::stream::TokenError),
+    Lex(LexError),
 }
 
 /// Convenience function for creating syntax errors.
 // TODO: Replace with macro that supports formatting.
 #[inline(always)]
-pub(crate) fn syntax_err(msg: impl ToString) -> ParseError {
-    ParseError::Syntax { msg: msg.to_string() }
+pub(crate) fn syntax_err(token: &Token, msg: impl ToString) -> ParseError {
+    ParseError::Syntax {
+        msg: msg.to_string(),
+        span: token.span(),
+        found: token.kind,
+    }
+}
+
+/// Syntax error for when the token stream ran out
+/// while more tokens were expected.
+pub(crate) fn eof_err(input: &TokenStream) -> ParseError {
+    ParseError::Syntax {
+        msg: "unexpected end-of-file".to_string(),
+        span: Span::empty(input.end_pos()),
+        found: TokenKind::EOF,
+    }
 }
 
 impl ParseError {
-    /// Byte position (offset) and size of token in source
-    /// related to this error.
-    pub fn span(&self) -> (BytePos, u32) {
-        todo!("span for syntax error")
+    /// Source code of the offending token.
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::Syntax { span, .. } => *span,
+            ParseError::Token(err) => err.span,
+            ParseError::Lex(err) => Span::new(err.offset, BytePos::from_u32(err.offset.to_u32() + err.size)),
+        }
+    }
+
+    /// Kind of token that was expected, if a specific one was.
+    pub fn expected(&self) -> Option<TokenKind> {
+        match self {
+            ParseError::Token(err) => Some(err.expected),
+            ParseError::Syntax { .. } | ParseError::Lex(_) => None,
+        }
+    }
+
+    /// Kind of the offending token.
+    pub fn found(&self) -> TokenKind {
+        match self {
+            ParseError::Syntax { found, .. } => *found,
+            ParseError::Token(err) => err.encountered,
+            ParseError::Lex(err) => TokenKind::Error(err.kind),
+        }
     }
 }
 
@@ -59,8 +134,9 @@ impl std::fmt::Display for ParseError {
         use ParseError as E;
 
         match self {
-            E::Syntax { msg } => write!(f, "syntax error: {}", msg),
+            E::Syntax { msg, .. } => write!(f, "syntax error: {}", msg),
             E::Token(err) => std::fmt::Display::fmt(err, f),
+            E::Lex(err) => std::fmt::Display::fmt(err, f),
         }
     }
 }
@@ -71,6 +147,12 @@ impl From<TokenError> for ParseError {
     }
 }
 
+impl From<LexError> for ParseError {
+    fn from(lex_error: LexError) -> Self {
+        ParseError::Lex(lex_error)
+    }
+}
+
 /// Convenience macro for declaring type safe identifiers.
 ///
 /// ```
@@ -119,15 +201,15 @@ macro_rules! declare_id {
             }
         }
 
-        impl Into<u32> for $name {
-            fn into(self) -> u32 {
-                self.as_u32()
+        impl From<$name> for u32 {
+            fn from(id: $name) -> u32 {
+                id.as_u32()
             }
         }
 
-        impl Into<usize> for $name {
-            fn into(self) -> usize {
-                self.as_usize()
+        impl From<$name> for usize {
+            fn from(id: $name) -> usize {
+                id.as_usize()
             }
         }
 
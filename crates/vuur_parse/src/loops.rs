@@ -0,0 +1,123 @@
+//! Loops
+
+use vuur_lexer::span::{Span, Spanned};
+use vuur_lexer::{Keyword, TokenKind};
+
+use crate::{block::Block, expr::Expr, ident::Ident, stream::TokenStream, Parse, ParseResult};
+
+/// Loop that runs its body for as long as the condition holds.
+///
+/// ```not-rust
+/// while i < 10 {
+///     i = i + 1
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WhileStmt {
+    pub cond: Expr,
+    pub body: Block,
+    /// From the `while` keyword up to the end of the body.
+    pub span: Span,
+}
+
+impl Parse for WhileStmt {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        log::trace!("WhileStmt::parse");
+
+        use Keyword as K;
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+
+        // keyword
+        let keyword = input.consume(T::Keyword(K::While))?;
+        input.ignore_many(T::Whitespace);
+
+        // conditional expression
+        let cond = Expr::parse(input)?;
+        input.ignore_many(T::Whitespace);
+
+        // body
+        let body = Block::parse(input)?;
+
+        // Leave the lookahead on the statement following the loop.
+        input.reset_peek();
+
+        Ok(WhileStmt {
+            span: keyword.span().to(body.span()),
+            cond,
+            body,
+        })
+    }
+}
+
+impl Spanned for WhileStmt {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Loop that runs its body once for every value produced by an iterable.
+///
+/// ```not-rust
+/// for i in 0...10 {
+///     total = total + i
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ForStmt {
+    /// Loop variable, bound to the current value.
+    pub name: Ident,
+    pub iter: Expr,
+    pub body: Block,
+    /// From the `for` keyword up to the end of the body.
+    pub span: Span,
+}
+
+impl Parse for ForStmt {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        log::trace!("ForStmt::parse");
+
+        use Keyword as K;
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+
+        // keyword
+        let keyword = input.consume(T::Keyword(K::For))?;
+        input.ignore_many(T::Whitespace);
+
+        // loop variable
+        let name = Ident::parse(input)?;
+        input.ignore_many(T::Whitespace);
+        input.consume(T::Keyword(K::In))?;
+        input.ignore_many(T::Whitespace);
+
+        // iterable expression
+        let iter = Expr::parse(input)?;
+        input.ignore_many(T::Whitespace);
+
+        // body
+        let body = Block::parse(input)?;
+
+        // Leave the lookahead on the statement following the loop.
+        input.reset_peek();
+
+        Ok(ForStmt {
+            span: keyword.span().to(body.span()),
+            name,
+            iter,
+            body,
+        })
+    }
+}
+
+impl Spanned for ForStmt {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
@@ -1,5 +1,6 @@
 //! Script module, not Rust module.
 
+use vuur_lexer::span::{BytePos, Span, Spanned};
 use vuur_lexer::TokenKind;
 
 use crate::{stmt::DefStmt, stream::TokenStream, Parse, ParseResult};
@@ -7,6 +8,8 @@ use crate::{stmt::DefStmt, stream::TokenStream, Parse, ParseResult};
 #[derive(Debug)]
 pub struct VuurModule {
     pub stmts: Vec<DefStmt>,
+    /// The whole source text, up to the end-of-file.
+    pub span: Span,
 }
 
 impl Parse for VuurModule {
@@ -26,10 +29,18 @@ impl Parse for VuurModule {
                     continue;
                 }
                 T::EOF => break,
-                _ => stmts.push(DefStmt::parse(input)?),
+                _ => stmts.push(DefStmt::parse_or_recover(input)),
             }
         }
 
-        Ok(VuurModule { stmts })
+        let span = Span::new(BytePos::from_u32(0), input.pos());
+
+        Ok(VuurModule { stmts, span })
+    }
+}
+
+impl Spanned for VuurModule {
+    fn span(&self) -> Span {
+        self.span
     }
 }
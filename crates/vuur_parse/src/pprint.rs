@@ -2,9 +2,10 @@
 use std::cell::RefCell;
 use std::fmt::{Display, Formatter};
 
-use crate::expr::{CallArg, Expr, MemberAccess, MemberPath, Operator, OperatorKind};
+use crate::expr::{CallArg, Expr, InterpPart, MemberAccess, MemberPath, Operator, OperatorKind};
 use crate::ident::Ident;
 
+// Full palette, even though the printer only uses some colors.
 #[allow(dead_code)]
 #[rustfmt::skip]
 mod color {
@@ -57,7 +58,7 @@ impl<'a> PrettyExpr<'a> {
         use color::*;
 
         match expr {
-            Expr::Unknown => writeln!(f, "unknown")?,
+            Expr::Unknown(_) => writeln!(f, "unknown")?,
             Expr::Unary(unary) => {
                 // op
                 self.fmt_operator(f, &unary.operator)?;
@@ -104,6 +105,27 @@ impl<'a> PrettyExpr<'a> {
                 self.pop_prefix(2);
             }
             Expr::Num(num) => writeln!(f, "number {FG_MAGENTA}\"{}\"{FG_RESET}", num.value)?,
+            Expr::Bool(boolean) => writeln!(f, "bool {FG_MAGENTA}{}{FG_RESET}", boolean.value)?,
+            Expr::Str(string) => writeln!(f, "string {FG_MAGENTA}{:?}{FG_RESET}", string.value)?,
+            Expr::Interp(interp) => {
+                writeln!(f, "interpolation")?;
+
+                for (index, part) in interp.parts.iter().enumerate() {
+                    self.fmt_prefix(f)?;
+                    if index == interp.parts.len() - 1 {
+                        self.write_colour(f, "└─", color::FG_GREEN)?;
+                        self.push_prefix("  ");
+                    } else {
+                        self.write_colour(f, "├─", color::FG_GREEN)?;
+                        self.push_prefix("│ ");
+                    }
+                    match part {
+                        InterpPart::Str(string) => writeln!(f, "string {FG_MAGENTA}{:?}{FG_RESET}", string.value)?,
+                        InterpPart::Expr(expr) => self.fmt_expr(f, expr)?,
+                    }
+                    self.pop_prefix(2);
+                }
+            }
             Expr::Group(group) => {
                 writeln!(f, "group")?;
 
@@ -140,6 +162,90 @@ impl<'a> PrettyExpr<'a> {
                 self.fmt_expr(f, &assign.rhs)?;
                 self.pop_prefix(2);
             }
+            Expr::Index(index) => {
+                writeln!(f, "index")?;
+
+                // lhs
+                self.fmt_prefix(f)?;
+                self.write_colour(f, "├─", color::FG_GREEN)?;
+                self.push_prefix("│ ");
+                self.fmt_expr(f, &index.lhs)?;
+                self.pop_prefix(2);
+
+                // index
+                self.fmt_prefix(f)?;
+                self.write_colour(f, "└─", color::FG_GREEN)?;
+                self.push_prefix("  ");
+                self.fmt_expr(f, &index.index)?;
+                self.pop_prefix(2);
+            }
+            Expr::IndexAssign(assign) => {
+                writeln!(f, "index_assign")?;
+
+                // lhs
+                self.fmt_prefix(f)?;
+                self.write_colour(f, "├─", color::FG_GREEN)?;
+                self.push_prefix("│ ");
+                self.fmt_expr(f, &assign.lhs)?;
+                self.pop_prefix(2);
+
+                // index
+                self.fmt_prefix(f)?;
+                self.write_colour(f, "├─", color::FG_GREEN)?;
+                self.push_prefix("│ ");
+                self.fmt_expr(f, &assign.index)?;
+                self.pop_prefix(2);
+
+                // rhs
+                self.fmt_prefix(f)?;
+                self.write_colour(f, "└─", color::FG_GREEN)?;
+                self.push_prefix("  ");
+                self.fmt_expr(f, &assign.rhs)?;
+                self.pop_prefix(2);
+            }
+            Expr::Struct(lit) => {
+                writeln!(f, "struct {FG_MAGENTA}\"{}\"{FG_RESET}", lit.name.text)?;
+
+                for (index, field) in lit.fields.iter().enumerate() {
+                    self.fmt_prefix(f)?;
+                    if index == lit.fields.len() - 1 {
+                        self.write_colour(f, "└─", color::FG_GREEN)?;
+                        self.push_prefix("  ");
+                    } else {
+                        self.write_colour(f, "├─", color::FG_GREEN)?;
+                        self.push_prefix("│ ");
+                    }
+
+                    writeln!(f, "field {FG_MAGENTA}\"{}\"{FG_RESET}", field.name.text)?;
+                    self.fmt_prefix(f)?;
+                    self.write_colour(f, "└─", color::FG_GREEN)?;
+                    self.push_prefix("  ");
+                    self.fmt_expr(f, &field.rhs)?;
+                    self.pop_prefix(2);
+
+                    self.pop_prefix(2);
+                }
+            }
+            Expr::Func(lit) => {
+                writeln!(f, "func")?;
+
+                // The body is made of statements, so only the arguments are printed.
+                self.fmt_prefix(f)?;
+                self.write_colour(f, "└─", color::FG_GREEN)?;
+                writeln!(f, "args {FG_BLUE}{}{FG_RESET}", lit.args.pairs.len())?;
+
+                self.push_prefix("  ");
+                for (index, pair) in lit.args.pairs.iter().enumerate() {
+                    self.fmt_prefix(f)?;
+                    if index == lit.args.pairs.len() - 1 {
+                        self.write_colour(f, "└─", color::FG_GREEN)?;
+                    } else {
+                        self.write_colour(f, "├─", color::FG_GREEN)?;
+                    }
+                    self.fmt_ident(f, &pair.item.name)?;
+                }
+                self.pop_prefix(2);
+            }
             Expr::Call(call) => {
                 writeln!(f, "call")?;
 
@@ -185,8 +291,26 @@ impl<'a> PrettyExpr<'a> {
             OperatorKind::Sub => writeln!(f, "subtract"),
             OperatorKind::Mul => writeln!(f, "multiply"),
             OperatorKind::Div => writeln!(f, "divide"),
+            OperatorKind::Mod => writeln!(f, "modulo"),
+            OperatorKind::Pow => writeln!(f, "power"),
             OperatorKind::Assign => writeln!(f, "assign"),
             OperatorKind::Equals => writeln!(f, "equals"),
+            OperatorKind::NotEquals => writeln!(f, "not_equals"),
+            OperatorKind::Less => writeln!(f, "less"),
+            OperatorKind::LessEq => writeln!(f, "less_equals"),
+            OperatorKind::Greater => writeln!(f, "greater"),
+            OperatorKind::GreaterEq => writeln!(f, "greater_equals"),
+            OperatorKind::Not => writeln!(f, "not"),
+            OperatorKind::And => writeln!(f, "and"),
+            OperatorKind::Or => writeln!(f, "or"),
+            OperatorKind::BitNot => writeln!(f, "bit_not"),
+            OperatorKind::BitAnd => writeln!(f, "bit_and"),
+            OperatorKind::BitOr => writeln!(f, "bit_or"),
+            OperatorKind::BitXor => writeln!(f, "bit_xor"),
+            OperatorKind::ShiftLeft => writeln!(f, "shift_left"),
+            OperatorKind::ShiftRight => writeln!(f, "shift_right"),
+            OperatorKind::Range => writeln!(f, "range"),
+            OperatorKind::RangeExclusive => writeln!(f, "range_exclusive"),
         }
     }
 
@@ -263,6 +387,6 @@ impl<'a> PrettyExpr<'a> {
 
 impl<'a> Display for PrettyExpr<'a> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        self.fmt_expr(f, &self.expr)
+        self.fmt_expr(f, self.expr)
     }
 }
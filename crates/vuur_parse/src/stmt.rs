@@ -1,13 +1,17 @@
 //! Statements (ie. lines starting with keywords like `func`, `var`, `type`)
 
-use vuur_lexer::{Keyword, TokenKind};
+use vuur_lexer::span::{Span, Spanned};
+use vuur_lexer::{Keyword, Token, TokenKind};
 
 use crate::cond::IfStmt;
+use crate::doc::DocComment;
 use crate::expr::Expr;
 use crate::func::FuncDef;
+use crate::loops::{ForStmt, WhileStmt};
 use crate::stream::TokenStream;
+use crate::ty::TypeDef;
 use crate::var::VarDef;
-use crate::{syntax_err, Parse, ParseResult};
+use crate::{eof_err, syntax_err, Parse, ParseResult};
 
 /// Definition statement.
 ///
@@ -17,9 +21,11 @@ use crate::{syntax_err, Parse, ParseResult};
 #[derive(Debug)]
 pub enum DefStmt {
     Func(FuncDef),
-    Return,
-    Return1(Expr),
-    Type(),
+    /// Return statement without a value, with its keyword.
+    Return(Token),
+    /// Return statement with its keyword and value.
+    Return1(Token, Expr),
+    Type(TypeDef),
     Var(VarDef),
     Simple(SimpleStmt),
 }
@@ -27,8 +33,15 @@ pub enum DefStmt {
 /// Simple statement.
 #[derive(Debug)]
 pub enum SimpleStmt {
-    Unknown,
+    /// Statement that could not be parsed.
+    Unknown(Span),
     If(IfStmt),
+    While(WhileStmt),
+    For(ForStmt),
+    /// Loop exit, with its keyword.
+    Break(Token),
+    /// Jump to the next loop iteration, with its keyword.
+    Continue(Token),
     Expr(Expr),
 }
 
@@ -43,20 +56,28 @@ impl Parse for DefStmt {
         input.ignore_many(T::Newline);
         input.ignore_many(T::Whitespace); // indentation
 
+        // Documentation is attached to the declaration that follows it.
+        let doc = DocComment::parse(input)?;
+
         if let Some(token) = input.peek() {
-            println!("DefStmt: {:?}", token);
+            log::trace!("DefStmt: {:?}", token);
+            if doc.is_some() && matches!(token.kind, T::RightBrace | T::EOF) {
+                return Err(syntax_err(token, "expected declaration after doc comment"));
+            }
+
             if let T::Keyword(keyword) = token.kind {
                 match keyword {
-                    K::Func => FuncDef::parse(input).map(DefStmt::Func),
+                    K::Func => FuncDef::parse(input).map(|func| DefStmt::Func(FuncDef { doc, ..func })),
                     K::Return => DefStmt::parse_return_stmt(input),
-                    K::Var => VarDef::parse(input).map(DefStmt::Var),
+                    K::Type => TypeDef::parse(input).map(|ty| DefStmt::Type(TypeDef { doc, ..ty })),
+                    K::Var => VarDef::parse(input).map(|var| DefStmt::Var(VarDef { doc, ..var })),
                     _ => SimpleStmt::parse(input).map(DefStmt::Simple),
                 }
             } else {
                 SimpleStmt::parse(input).map(DefStmt::Simple)
             }
         } else {
-            Err(syntax_err("unexpected end-of-file"))
+            Err(eof_err(input))
         }
     }
 }
@@ -70,32 +91,53 @@ impl Parse for SimpleStmt {
 
         input.reset_peek();
         if let Some(token) = input.peek() {
-            println!("SimpleStmt: {:?}", token);
+            log::trace!("SimpleStmt: {:?}", token);
             if let T::Keyword(keyword) = token.kind {
                 match keyword {
                     K::If => IfStmt::parse(input).map(SimpleStmt::If),
-                    _ => Ok(SimpleStmt::Unknown),
+                    K::While => WhileStmt::parse(input).map(SimpleStmt::While),
+                    K::For => ForStmt::parse(input).map(SimpleStmt::For),
+                    K::Break => SimpleStmt::parse_jump_stmt(input, keyword).map(SimpleStmt::Break),
+                    K::Continue => SimpleStmt::parse_jump_stmt(input, keyword).map(SimpleStmt::Continue),
+                    // Literals are the only keywords that start an expression.
+                    K::True | K::False => SimpleStmt::parse_expr_stmt(input),
+                    _ => Err(syntax_err(token, "expected statement")),
                 }
             } else {
                 SimpleStmt::parse_expr_stmt(input)
             }
         } else {
-            Err(syntax_err("unexpected end-of-file"))
+            Err(eof_err(input))
         }
     }
 }
 
 impl DefStmt {
+    /// Parse a statement, recovering from syntax errors.
+    ///
+    /// On error the parser skips ahead to the next statement. The error
+    /// is recorded in the token stream, and the skipped source code is
+    /// replaced with a [`SimpleStmt::Unknown`] placeholder.
+    pub(crate) fn parse_or_recover(input: &mut TokenStream) -> DefStmt {
+        input.ignore_many(TokenKind::Whitespace);
+        let start = input.pos();
+
+        DefStmt::parse(input).unwrap_or_else(|err| {
+            let span = input.recover(start, err);
+            DefStmt::Simple(SimpleStmt::Unknown(span))
+        })
+    }
+
     fn parse_return_stmt(input: &mut TokenStream) -> ParseResult<DefStmt> {
         use TokenKind as TK;
 
         input.ignore_many(TokenKind::Whitespace);
-        input.consume(TokenKind::Keyword(Keyword::Return))?;
+        let keyword = input.consume(TokenKind::Keyword(Keyword::Return))?;
         input.ignore_many(TokenKind::Whitespace);
 
         let return_stmt = match input.peek_kind() {
-            None | Some(TK::EOF | TK::Newline) => Ok(DefStmt::Return),
-            _ => Expr::parse(input).map(DefStmt::Return1),
+            None | Some(TK::EOF | TK::Newline) => Ok(DefStmt::Return(keyword)),
+            _ => Ok(DefStmt::Return1(keyword, Expr::parse_or_recover(input))),
         };
 
         // end-of-statement
@@ -118,16 +160,30 @@ impl DefStmt {
         }
     }
 
+    pub fn type_def(&self) -> Option<&TypeDef> {
+        match self {
+            DefStmt::Type(stmt) => Some(stmt),
+            _ => None,
+        }
+    }
+
+    pub fn var(&self) -> Option<&VarDef> {
+        match self {
+            DefStmt::Var(stmt) => Some(stmt),
+            _ => None,
+        }
+    }
+
     pub fn return1(&self) -> Option<&Expr> {
         match self {
-            DefStmt::Return1(expr) => Some(expr),
+            DefStmt::Return1(_, expr) => Some(expr),
             _ => None,
         }
     }
 
     pub fn return1_mut(&mut self) -> Option<&mut Expr> {
         match self {
-            DefStmt::Return1(expr) => Some(expr),
+            DefStmt::Return1(_, expr) => Some(expr),
             _ => None,
         }
     }
@@ -150,6 +206,23 @@ impl SimpleStmt {
         Ok(SimpleStmt::Expr(expr))
     }
 
+    /// Parse a loop control statement, which is only its keyword.
+    fn parse_jump_stmt(input: &mut TokenStream, keyword: Keyword) -> ParseResult<Token> {
+        input.ignore_many(TokenKind::Whitespace);
+        let token = input.consume(TokenKind::Keyword(keyword))?;
+        input.ignore_many(TokenKind::Whitespace);
+
+        // end-of-statement
+        if let Some(next) = input.peek() {
+            if !matches!(next.kind, TokenKind::Newline | TokenKind::RightBrace | TokenKind::EOF) {
+                return Err(syntax_err(next, format!("expected end of statement after '{keyword}'")));
+            }
+        }
+        input.ignore_many(TokenKind::Newline);
+
+        Ok(token)
+    }
+
     pub fn expr(&self) -> Option<&Expr> {
         match self {
             SimpleStmt::Expr(expr) => Some(expr),
@@ -157,3 +230,30 @@ impl SimpleStmt {
         }
     }
 }
+
+impl Spanned for DefStmt {
+    /// Span of the statement, excluding any documentation comment.
+    fn span(&self) -> Span {
+        match self {
+            DefStmt::Func(func) => func.span(),
+            DefStmt::Return(keyword) => keyword.span(),
+            DefStmt::Return1(keyword, expr) => keyword.span().to(expr.span()),
+            DefStmt::Type(type_def) => type_def.span(),
+            DefStmt::Var(var_def) => var_def.span(),
+            DefStmt::Simple(stmt) => stmt.span(),
+        }
+    }
+}
+
+impl Spanned for SimpleStmt {
+    fn span(&self) -> Span {
+        match self {
+            SimpleStmt::Unknown(span) => *span,
+            SimpleStmt::If(if_stmt) => if_stmt.span(),
+            SimpleStmt::While(while_stmt) => while_stmt.span(),
+            SimpleStmt::For(for_stmt) => for_stmt.span(),
+            SimpleStmt::Break(keyword) | SimpleStmt::Continue(keyword) => keyword.span(),
+            SimpleStmt::Expr(expr) => expr.span(),
+        }
+    }
+}
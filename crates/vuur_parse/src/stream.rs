@@ -2,10 +2,18 @@
 use std::slice::SliceIndex;
 
 use itertools::{multipeek, MultiPeek};
-use vuur_lexer::{Lexer, LexerIter, Token, TokenKind};
+use vuur_lexer::span::{BytePos, Span, Spanned};
+use vuur_lexer::{Keyword, Lexer, LexerIter, Token, TokenKind};
+
+use crate::ParseError;
+
+/// Lexed tokens with trivia, like comments, removed.
+type TokenIter<'a> = std::iter::Filter<LexerIter<'a>, fn(&Token) -> bool>;
 
 /// Buffered stream of tokens that allows arbitrary look ahead.
 ///
+/// Trivia tokens are skipped, so the parser never encounters them.
+///
 /// Tokens are lazily lexed. Peeking or consuming the next token
 /// triggers the internal lexer.
 ///
@@ -15,17 +23,22 @@ use vuur_lexer::{Lexer, LexerIter, Token, TokenKind};
 /// can be reset explicitly using `TokenStream::reset_peek` or
 /// implicitly by calling one of the consuming methods.
 pub struct TokenStream<'a> {
-    lexer: MultiPeek<LexerIter<'a>>,
+    lexer: MultiPeek<TokenIter<'a>>,
     /// Keep reference to the source so the parser can
     /// slice fragments from it.
     source: &'a str,
+    /// Errors that the parser recovered from.
+    diagnostics: Vec<ParseError>,
 }
 
 impl<'a> TokenStream<'a> {
     pub fn new(lexer: Lexer<'a>) -> Self {
+        let is_not_trivia: fn(&Token) -> bool = |token| !token.kind.is_trivia();
+
         Self {
             source: lexer.source(),
-            lexer: multipeek(lexer),
+            lexer: multipeek(lexer.into_iter().filter(is_not_trivia)),
+            diagnostics: Vec::new(),
         }
     }
 
@@ -86,11 +99,8 @@ impl<'a> TokenStream<'a> {
     ///
     /// # Errors
     ///
-    /// Returns a [`TokenError`] if the token kind doesn't match.
-    ///
-    /// # Panics
-    ///
-    /// Panics when at end-of-file.
+    /// Returns a [`TokenError`] if the token kind doesn't match,
+    /// or the token stream is at the end.
     pub fn consume(&mut self, token_kind: TokenKind) -> Result<Token, TokenError> {
         // Ensure clean peek state.
         self.lexer.reset_peek();
@@ -99,10 +109,10 @@ impl<'a> TokenStream<'a> {
         match self.lexer.peek() {
             Some(token) => {
                 if token.kind != token_kind {
-                    // TODO: Return parsing error.
                     Err(TokenError {
                         expected: token_kind,
                         encountered: token.kind,
+                        span: token.span(),
                     })
                 } else {
                     self.lexer.next().ok_or_else(|| {
@@ -111,23 +121,17 @@ impl<'a> TokenStream<'a> {
                     })
                 }
             }
-            None => {
-                // TODO: Change from panic to error
-                panic!("unexpected end-of-file");
-            }
+            None => Err(TokenError {
+                expected: token_kind,
+                encountered: TokenKind::EOF,
+                span: Span::empty(self.end_pos()),
+            }),
         }
     }
 
     /// Consumes one or more tokens while the token's matches given kind.
     pub fn ignore_many(&mut self, kind: TokenKind) {
-        self.lexer.reset_peek();
-        if let Some(token) = self.lexer.peek() {
-            if token.kind == kind {
-                self.lexer.next();
-            } else {
-                self.lexer.reset_peek();
-            }
-        }
+        self.ignore_while(|token_kind| token_kind == kind)
     }
 
     /// Consumes one or more tokens while the given predicate tests as `true`.
@@ -165,6 +169,78 @@ impl<'a> TokenStream<'a> {
     pub fn reset_peek(&mut self) {
         self.lexer.reset_peek()
     }
+
+    /// Byte position where the next token starts.
+    ///
+    /// Returns the end of the source when lexing is done.
+    pub fn pos(&mut self) -> BytePos {
+        self.lexer.reset_peek();
+        let pos = match self.lexer.peek() {
+            Some(token) => token.offset,
+            None => self.end_pos(),
+        };
+        self.lexer.reset_peek();
+        pos
+    }
+
+    /// Byte position of the end of the source.
+    #[inline]
+    pub fn end_pos(&self) -> BytePos {
+        BytePos::from_u32(self.source.len() as u32)
+    }
+
+    /// Errors that the parser recovered from, in the order they were encountered.
+    #[inline]
+    pub fn diagnostics(&self) -> &[ParseError] {
+        &self.diagnostics
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<ParseError> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Record a syntax error and skip ahead to the start of the
+    /// next statement, so parsing can continue.
+    ///
+    /// Tokens are skipped up to and including the next newline, or up to
+    /// a closing brace, statement keyword or the end-of-file. At least one
+    /// token is skipped when the parser did not advance past `start`,
+    /// so it can't get stuck on the same error.
+    ///
+    /// Returns the span from `start` up to the end of the skipped tokens,
+    /// for use in a placeholder node.
+    pub fn recover(&mut self, start: BytePos, err: ParseError) -> Span {
+        use Keyword as K;
+        use TokenKind as T;
+
+        let mut end = err.span().end.max(start);
+        self.diagnostics.push(err);
+
+        if self.pos() == start && self.peek_kind() != Some(T::EOF) {
+            if let Some(token) = self.lexer.next() {
+                end = end.max(token.span().end);
+            }
+        }
+
+        self.lexer.reset_peek();
+        while let Some(token) = self.lexer.peek() {
+            match token.kind {
+                T::Newline => {
+                    self.lexer.next();
+                    break;
+                }
+                T::RightBrace | T::EOF => break,
+                T::Keyword(K::For | K::Func | K::If | K::Return | K::Type | K::Var | K::While) => break,
+                _ => {
+                    end = end.max(token.span().end);
+                    self.lexer.next();
+                }
+            }
+        }
+
+        self.lexer.reset_peek();
+        Span::new(start, end)
+    }
 }
 
 /// Error returned when an unexpected token type is encountered.
@@ -172,6 +248,8 @@ impl<'a> TokenStream<'a> {
 pub struct TokenError {
     pub expected: TokenKind,
     pub encountered: TokenKind,
+    /// Source code of the encountered token.
+    pub span: Span,
 }
 
 impl std::error::Error for TokenError {}
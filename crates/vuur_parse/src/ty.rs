@@ -1,27 +1,154 @@
+use vuur_lexer::span::{Span, Spanned};
 use vuur_lexer::{Keyword, Token, TokenKind};
 
+use crate::delim::Delimited;
+use crate::doc::DocComment;
+use crate::func::{FuncArg, FuncRtn, Separator};
 use crate::ident::Ident;
 use crate::stream::TokenStream;
-use crate::{declare_id, syntax_err, Parse};
+use crate::{declare_id, eof_err, syntax_err, Parse, ParseResult};
 
 declare_id!(
     /// Type identifier, not to be confused with Rust's [`std::any::TypeId`].
     pub struct TypeId
 );
 
+/// Type declaration statement.
+///
+/// ```not-rust
+/// type Name = Type
+/// ```
+#[derive(Debug)]
+pub struct TypeDef {
+    /// Documentation comment, attached by the statement parser.
+    pub doc: Option<DocComment>,
+    pub name: Ident,
+    pub ty: Type,
+    /// From the `type` keyword up to the end of the type expression.
+    pub span: Span,
+}
+
 #[derive(Debug)]
 pub struct Type {
     pub ref_: Option<Token>,
     pub kind: TypeKind,
+    /// Includes the reference marker, if any.
+    pub span: Span,
 }
 
 #[derive(Debug)]
 pub enum TypeKind {
     /// Type referred to by identifier.
     Ident(Ident),
-    // TODO: interface
-    // TODO: struct
-    // TODO: func
+    Struct(StructDef),
+    Interface(InterfaceDef),
+    /// Boxed, because the return type is stored inline.
+    Func(Box<FuncTypeDef>),
+}
+
+/// Structure type expression.
+///
+/// ```not-rust
+/// struct {
+///     name: Type,
+/// }
+/// ```
+///
+/// Fields are separated by commas, newlines, or both.
+#[derive(Debug)]
+pub struct StructDef {
+    pub keyword: Token,
+    pub fields: Vec<FieldDef>,
+    /// From the `struct` keyword up to the closing brace.
+    pub span: Span,
+}
+
+/// Interface type expression, listing the methods
+/// a type must have to be used as the interface.
+///
+/// ```not-rust
+/// interface {
+///     Area() -> f32
+/// }
+/// ```
+///
+/// Methods are separated by commas, newlines, or both.
+#[derive(Debug)]
+pub struct InterfaceDef {
+    pub keyword: Token,
+    pub methods: Vec<MethodSig>,
+    /// From the `interface` keyword up to the closing brace.
+    pub span: Span,
+}
+
+/// Function type expression, which is the
+/// signature of a function without names.
+///
+/// ```not-rust
+/// func(i32, i32) -> i32
+/// ```
+#[derive(Debug)]
+pub struct FuncTypeDef {
+    pub keyword: Token,
+    pub args: Vec<Type>,
+    pub rtn: Option<FuncRtn>,
+    /// From the `func` keyword up to the end of the return type,
+    /// or the closing parenthesis if there is none.
+    pub span: Span,
+}
+
+/// Method signature in an interface type, which
+/// is a function declaration without a body.
+#[derive(Debug)]
+pub struct MethodSig {
+    pub name: Ident,
+    pub args: Delimited<FuncArg, Separator>,
+    pub rtn: Option<FuncRtn>,
+    /// From the name up to the end of the return type,
+    /// or the closing parenthesis if there is none.
+    pub span: Span,
+}
+
+/// Field declaration in a structure type.
+#[derive(Debug)]
+pub struct FieldDef {
+    pub name: Ident,
+    pub ty: Type,
+    pub span: Span,
+}
+
+impl Parse for TypeDef {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use Keyword as K;
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+
+        // keyword
+        let keyword = input.consume(T::Keyword(K::Type))?;
+        input.ignore_many(T::Whitespace);
+
+        // name
+        let name = Ident::parse(input)?;
+        input.ignore_many(T::Whitespace);
+
+        // operator (eq)
+        input.consume(T::Eq)?;
+
+        // type expression
+        let ty = Type::parse(input)?;
+
+        let span = keyword.span().to(ty.span());
+
+        Ok(TypeDef {
+            doc: None,
+            name,
+            ty,
+            span,
+        })
+    }
 }
 
 impl Parse for Type {
@@ -37,30 +164,428 @@ impl Parse for Type {
 
         input.reset_peek();
         // TODO: Replace syntax error with unexpected token error
-        if let Some(kind) = input.peek().map(|t| t.kind) {
-            println!("Type token after ref: {:?}", kind);
-            match kind {
+        if let Some(token) = input.peek() {
+            log::trace!("Type token after ref: {:?}", token.kind);
+            match token.kind {
                 T::Ident => {
                     let kind = TypeKind::Ident(Ident::parse(input)?);
-                    Ok(Type { ref_, kind })
+                    let span = match &ref_ {
+                        Some(token) => token.span().to(kind.span()),
+                        None => kind.span(),
+                    };
+                    Ok(Type { ref_, kind, span })
                 }
                 T::Keyword(keyword) => match keyword {
                     K::Interface => {
-                        todo!("interface type declaration")
+                        let kind = TypeKind::Interface(InterfaceDef::parse(input)?);
+                        let span = match &ref_ {
+                            Some(token) => token.span().to(kind.span()),
+                            None => kind.span(),
+                        };
+                        Ok(Type { ref_, kind, span })
                     }
                     K::Struct => {
-                        todo!("struct type declaration")
+                        let kind = TypeKind::Struct(StructDef::parse(input)?);
+                        let span = match &ref_ {
+                            Some(token) => token.span().to(kind.span()),
+                            None => kind.span(),
+                        };
+                        Ok(Type { ref_, kind, span })
                     }
                     K::Func => {
-                        todo!("function type declaration")
+                        let kind = TypeKind::Func(Box::new(FuncTypeDef::parse(input)?));
+                        let span = match &ref_ {
+                            Some(token) => token.span().to(kind.span()),
+                            None => kind.span(),
+                        };
+                        Ok(Type { ref_, kind, span })
                     }
-                    _ => Err(syntax_err("expected type declaration")),
+                    _ => Err(syntax_err(token, "expected type declaration")),
                 },
-                T::EOF => Err(syntax_err("unexpected end-of-file")),
-                _ => Err(syntax_err("expected type declaration")),
+                T::EOF => Err(syntax_err(token, "unexpected end-of-file")),
+                _ => Err(syntax_err(token, "expected type declaration")),
             }
         } else {
-            Err(syntax_err("unexpected end-of-file"))
+            Err(eof_err(input))
+        }
+    }
+}
+
+impl Parse for StructDef {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use Keyword as K;
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+        let keyword = input.consume(T::Keyword(K::Struct))?;
+        input.ignore_many(T::Whitespace);
+        input.consume(T::LeftBrace)?;
+
+        let mut fields = vec![];
+
+        let right_brace = loop {
+            input.ignore_while(|kind| matches!(kind, T::Whitespace | T::Newline));
+
+            if input.peek_kind() == Some(T::RightBrace) {
+                input.reset_peek();
+                break input.consume(T::RightBrace)?;
+            }
+            input.reset_peek();
+
+            fields.push(FieldDef::parse(input)?);
+            input.ignore_many(T::Whitespace);
+
+            // separator
+            match input.peek() {
+                Some(token) => match token.kind {
+                    T::Comma | T::Newline => {
+                        input.next_token();
+                    }
+                    T::RightBrace => { /* end of fields */ }
+                    T::EOF => return Err(syntax_err(token, "unexpected end-of-file")),
+                    _ => return Err(syntax_err(token, "expected ',' or newline after struct field")),
+                },
+                None => return Err(eof_err(input)),
+            }
+            input.reset_peek();
+        };
+
+        let span = keyword.span().to(right_brace.span());
+
+        Ok(StructDef { keyword, fields, span })
+    }
+}
+
+impl Parse for InterfaceDef {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use Keyword as K;
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+        let keyword = input.consume(T::Keyword(K::Interface))?;
+        input.ignore_many(T::Whitespace);
+        input.consume(T::LeftBrace)?;
+
+        let mut methods = vec![];
+
+        let right_brace = loop {
+            input.ignore_while(|kind| matches!(kind, T::Whitespace | T::Newline));
+
+            if input.peek_kind() == Some(T::RightBrace) {
+                input.reset_peek();
+                break input.consume(T::RightBrace)?;
+            }
+            input.reset_peek();
+
+            methods.push(MethodSig::parse(input)?);
+            input.ignore_many(T::Whitespace);
+
+            // separator
+            match input.peek() {
+                Some(token) => match token.kind {
+                    T::Comma | T::Newline => {
+                        input.next_token();
+                    }
+                    T::RightBrace => { /* end of methods */ }
+                    T::EOF => return Err(syntax_err(token, "unexpected end-of-file")),
+                    _ => return Err(syntax_err(token, "expected ',' or newline after interface method")),
+                },
+                None => return Err(eof_err(input)),
+            }
+            input.reset_peek();
+        };
+
+        let span = keyword.span().to(right_brace.span());
+
+        Ok(InterfaceDef { keyword, methods, span })
+    }
+}
+
+impl Parse for FuncTypeDef {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use Keyword as K;
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+        let keyword = input.consume(T::Keyword(K::Func))?;
+        input.ignore_many(T::Whitespace);
+
+        // argument types
+        input.consume(T::LeftParen)?;
+
+        let mut args = vec![];
+
+        let right_paren = loop {
+            input.ignore_many(T::Whitespace);
+
+            if input.peek_kind() == Some(T::RightParen) {
+                input.reset_peek();
+                break input.consume(T::RightParen)?;
+            }
+            input.reset_peek();
+
+            args.push(Type::parse(input)?);
+            input.ignore_many(T::Whitespace);
+
+            // separator
+            match input.peek() {
+                Some(token) => match token.kind {
+                    T::Comma => {
+                        input.next_token();
+                    }
+                    T::RightParen => { /* end of arguments */ }
+                    T::EOF => return Err(syntax_err(token, "unexpected end-of-file")),
+                    _ => return Err(syntax_err(token, "expected ',' or ')' after argument type")),
+                },
+                None => return Err(eof_err(input)),
+            }
+            input.reset_peek();
+        };
+        input.ignore_many(T::Whitespace);
+
+        // optional return
+        let rtn = if let Ok(arrow) = input.consume(T::ThinArrow) {
+            let ty = Type::parse(input)?;
+            let span = arrow.span().to(ty.span());
+            Some(FuncRtn { ty, span })
+        } else {
+            None
+        };
+
+        let span = match &rtn {
+            Some(rtn) => keyword.span().to(rtn.span()),
+            None => keyword.span().to(right_paren.span()),
+        };
+
+        Ok(FuncTypeDef {
+            keyword,
+            args,
+            rtn,
+            span,
+        })
+    }
+}
+
+impl Parse for MethodSig {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+        let name = Ident::parse(input)?;
+        input.ignore_many(T::Whitespace);
+
+        // arguments
+        input.consume(T::LeftParen)?;
+        let args = Delimited::<FuncArg, Separator>::parse(input)?;
+        input.ignore_many(T::Whitespace);
+        let right_paren = input.consume(T::RightParen)?;
+        input.ignore_many(T::Whitespace);
+
+        // optional return
+        let rtn = if let Ok(arrow) = input.consume(T::ThinArrow) {
+            let ty = Type::parse(input)?;
+            let span = arrow.span().to(ty.span());
+            Some(FuncRtn { ty, span })
+        } else {
+            None
+        };
+
+        let span = match &rtn {
+            Some(rtn) => name.span().to(rtn.span()),
+            None => name.span().to(right_paren.span()),
+        };
+
+        Ok(MethodSig { name, args, rtn, span })
+    }
+}
+
+impl Parse for FieldDef {
+    type Output = Self;
+
+    fn parse(input: &mut TokenStream) -> ParseResult<Self::Output> {
+        use TokenKind as T;
+
+        input.ignore_many(T::Whitespace);
+        let name = Ident::parse(input)?;
+        input.ignore_many(T::Whitespace);
+        input.consume(T::Colon)?;
+        let ty = Type::parse(input)?;
+
+        let span = name.span().to(ty.span());
+
+        Ok(FieldDef { name, ty, span })
+    }
+}
+
+impl Spanned for TypeDef {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Type {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for TypeKind {
+    fn span(&self) -> Span {
+        match self {
+            TypeKind::Ident(ident) => ident.span(),
+            TypeKind::Struct(struct_def) => struct_def.span(),
+            TypeKind::Interface(interface_def) => interface_def.span(),
+            TypeKind::Func(func_def) => func_def.span(),
+        }
+    }
+}
+
+impl Spanned for StructDef {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for InterfaceDef {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for FuncTypeDef {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for MethodSig {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for FieldDef {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use vuur_lexer::Lexer;
+
+    #[test]
+    fn test_struct_type_def() {
+        let sources = &[
+            "type Vec = struct {\n    x: float,\n    y: float,\n    z: float,\n}",
+            "type Vec = struct { x: float, y: float, z: float }",
+            "type Vec = struct {\n    x: float\n    y: float, z: float\n}",
+        ];
+
+        for source in sources {
+            let mut stream = TokenStream::new(Lexer::from_source(source));
+            let type_def = TypeDef::parse(&mut stream).expect("parsing type declaration");
+
+            assert_eq!(type_def.name.text, "Vec");
+            assert_eq!(type_def.span.end.to_u32() as usize, source.len());
+
+            let TypeKind::Struct(struct_def) = &type_def.ty.kind else {
+                panic!("expected struct type");
+            };
+            let names = struct_def.fields.iter().map(|f| f.name.text.as_str()).collect::<Vec<_>>();
+            assert_eq!(names, &["x", "y", "z"]);
+
+            for field in &struct_def.fields {
+                assert!(matches!(&field.ty.kind, TypeKind::Ident(ident) if ident.text == "float"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_interface_type_def() {
+        let sources = &[
+            "type Shape = interface {\n    Area() -> f32\n    Scale(s: f32, t: f32)\n}",
+            "type Shape = interface { Area() -> f32, Scale(s: f32, t: f32) }",
+        ];
+
+        for source in sources {
+            let mut stream = TokenStream::new(Lexer::from_source(source));
+            let type_def = TypeDef::parse(&mut stream).expect("parsing type declaration");
+
+            assert_eq!(type_def.name.text, "Shape");
+            assert_eq!(type_def.span.end.to_u32() as usize, source.len());
+
+            let TypeKind::Interface(interface_def) = &type_def.ty.kind else {
+                panic!("expected interface type");
+            };
+            let names = interface_def.methods.iter().map(|m| m.name.text.as_str()).collect::<Vec<_>>();
+            assert_eq!(names, &["Area", "Scale"]);
+
+            let area = &interface_def.methods[0];
+            assert!(area.args.pairs.is_empty());
+            assert!(matches!(&area.rtn.as_ref().unwrap().ty.kind, TypeKind::Ident(ident) if ident.text == "f32"));
+
+            let scale = &interface_def.methods[1];
+            assert_eq!(scale.args.pairs.len(), 2);
+            assert!(scale.rtn.is_none());
+        }
+
+        // Interface methods have no body.
+        let mut stream = TokenStream::new(Lexer::from_source("type Shape = interface { Area() -> f32 {} }"));
+        assert!(TypeDef::parse(&mut stream).is_err());
+    }
+
+    #[test]
+    fn test_func_type() {
+        let source = "func(i32, &Vec) -> func() -> bool";
+        let mut stream = TokenStream::new(Lexer::from_source(source));
+        let ty = Type::parse(&mut stream).expect("parsing function type");
+        assert_eq!(ty.span.end.to_u32() as usize, source.len());
+
+        let TypeKind::Func(func_def) = &ty.kind else {
+            panic!("expected function type");
+        };
+        assert_eq!(func_def.args.len(), 2);
+        assert!(matches!(&func_def.args[0].kind, TypeKind::Ident(ident) if ident.text == "i32"));
+        assert!(func_def.args[1].ref_.is_some());
+
+        // Return type is itself a function type, without arguments.
+        let TypeKind::Func(rtn) = &func_def.rtn.as_ref().unwrap().ty.kind else {
+            panic!("expected function return type");
+        };
+        assert!(rtn.args.is_empty());
+        assert!(matches!(&rtn.rtn.as_ref().unwrap().ty.kind, TypeKind::Ident(ident) if ident.text == "bool"));
+
+        let mut stream = TokenStream::new(Lexer::from_source("func()"));
+        let ty = Type::parse(&mut stream).expect("parsing function type");
+        assert!(matches!(&ty.kind, TypeKind::Func(func_def) if func_def.args.is_empty() && func_def.rtn.is_none()));
+
+        let mut stream = TokenStream::new(Lexer::from_source("func(i32 bool)"));
+        assert!(Type::parse(&mut stream).is_err());
+    }
+
+    #[test]
+    fn test_struct_type_def_error() {
+        let sources = &[
+            "type Vec = struct { x: float y: float }",
+            "type Vec = struct { x: float,",
+        ];
+
+        for source in sources {
+            let mut stream = TokenStream::new(Lexer::from_source(source));
+            assert!(
+                TypeDef::parse(&mut stream).is_err(),
+                "expected error parsing: {source:?}"
+            );
         }
     }
 }
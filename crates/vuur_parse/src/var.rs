@@ -1,17 +1,31 @@
 // Variable definition statement.
+use vuur_lexer::span::{Span, Spanned};
 use vuur_lexer::{Keyword, TokenKind};
 
+use crate::doc::DocComment;
 use crate::expr::Expr;
 use crate::ident::Ident;
 use crate::stream::TokenStream;
 use crate::ty::Type;
 use crate::{Parse, ParseResult};
 
+/// Variable definition statement.
+///
+/// ```not-rust
+/// var name: Type = rhs
+/// ```
+///
+/// Either the type annotation or the initializer may be omitted, but not both.
 #[derive(Debug)]
 pub struct VarDef {
+    /// Documentation comment, attached by the statement parser.
+    pub doc: Option<DocComment>,
     pub name: Ident,
     pub ty: Option<Type>,
-    pub rhs: Expr,
+    pub rhs: Option<Expr>,
+    /// From the `var` keyword up to the end of the right-hand-side,
+    /// or the type annotation when there is no right-hand-side.
+    pub span: Span,
 }
 
 impl Parse for VarDef {
@@ -26,29 +40,61 @@ impl Parse for VarDef {
         assert!(matches!(input.peek_kind(), Some(T::Keyword(K::Var))));
 
         // keyword (var)
-        input.consume(T::Keyword(K::Var))?;
+        let keyword = input.consume(T::Keyword(K::Var))?;
         input.ignore_many(T::Whitespace);
 
         // name
         let name = Ident::parse(input)?;
         input.ignore_many(T::Whitespace);
 
-        // operator (eq)
-        input.consume(T::Eq)?;
-        input.ignore_many(T::Whitespace);
-
-        // rhs
-        let rhs = Expr::parse(input)?;
+        // optional type annotation
+        let ty = if input.match_token(T::Colon) {
+            let ty = Type::parse(input)?;
+            input.ignore_many(T::Whitespace);
+            Some(ty)
+        } else {
+            input.reset_peek();
+            None
+        };
+
+        // Without an initializer the variable starts out with
+        // the zero value of its annotated type.
+        let has_rhs = ty.is_none() || input.peek_kind() == Some(T::Eq);
+        input.reset_peek();
+
+        let rhs = if has_rhs {
+            // operator (eq)
+            input.consume(T::Eq)?;
+            input.ignore_many(T::Whitespace);
+
+            // rhs
+            Some(Expr::parse_or_recover(input))
+        } else {
+            None
+        };
+
+        let span = match (&rhs, &ty) {
+            (Some(rhs), _) => keyword.span().to(rhs.span()),
+            (None, Some(ty)) => keyword.span().to(ty.span()),
+            (None, None) => unreachable!("variable without type must have an initializer"),
+        };
 
         Ok(VarDef {
+            doc: None,
             name,
-            // TODO: type expression
-            ty: None,
+            ty,
             rhs,
+            span,
         })
     }
 }
 
+impl Spanned for VarDef {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -63,12 +109,38 @@ mod test {
         let var_def = VarDef::parse(&mut stream).expect("parsing variable definition statement");
 
         assert_eq!(var_def.name.text, "a");
+        assert!(var_def.ty.is_none());
 
-        // TODO: assert type expression
-
-        let add_expr = var_def.rhs.expr_bin_op().unwrap();
+        let add_expr = var_def.rhs.as_ref().and_then(Expr::expr_bin_op).unwrap();
         assert_eq!(add_expr.operator.kind, OperatorKind::Add);
         assert_eq!(add_expr.lhs.expr_name_access().unwrap().ident.text, "b");
         assert_eq!(add_expr.rhs.expr_name_access().unwrap().ident.text, "c");
     }
+
+    #[test]
+    fn test_var_def_type() {
+        let cases: &[(&str, bool)] = &[("var a: i32 = 5", true), ("var a : str", false)];
+
+        for (source, has_rhs) in cases {
+            let lexer = Lexer::from_source(source);
+            let mut stream = TokenStream::new(lexer);
+
+            let var_def = VarDef::parse(&mut stream).expect("parsing variable definition statement");
+            let ty = var_def.ty.as_ref().expect("type annotation");
+            assert_eq!(var_def.name.text, "a");
+            assert_eq!(var_def.rhs.is_some(), *has_rhs);
+
+            // Without an initializer the statement ends at the type.
+            let end = if *has_rhs {
+                source.len()
+            } else {
+                ty.span().end.to_u32() as usize
+            };
+            assert_eq!(var_def.span.end.to_u32() as usize, end);
+        }
+
+        // Either the type or initializer is required.
+        let mut stream = TokenStream::new(Lexer::from_source("var a\n"));
+        assert!(VarDef::parse(&mut stream).is_err());
+    }
 }
@@ -0,0 +1,121 @@
+use vuur_lexer::span::{SourceFile, Spanned};
+use vuur_lexer::{Keyword, TokenKind};
+use vuur_parse::error::{format_error, ColorChoice, Error};
+use vuur_parse::expr::Expr;
+use vuur_parse::stmt::SimpleStmt;
+use vuur_parse::{parse_str, parse_str_with_diagnostics};
+
+#[test]
+fn test_error_span() {
+    let source = "var x 42";
+    let err = parse_str(source).expect_err("expected missing equals sign");
+
+    assert_eq!(err.expected(), Some(TokenKind::Eq));
+    assert_eq!(err.found(), TokenKind::Number);
+    assert_eq!(err.span().start.to_u32(), 6);
+    assert_eq!(err.span().size(), 2);
+
+    let file = SourceFile::new("test.vu", source);
+    let err = Error::new(err, &file);
+    assert_eq!(err.to_string(), "test.vu:1:7: encountered token 'number', expected '='");
+}
+
+#[test]
+fn test_format_error() {
+    let source = "func Main() {\n    var x = (1 + 2\n}";
+    let file = SourceFile::new("test.vu", source);
+    let err = parse_str(source).expect_err("expected unclosed group");
+
+    let expected = "\
+error: encountered token 'newline', expected ')'
+ --> test.vu:2:19
+  |
+2 |     var x = (1 + 2
+  |                   ^
+  |
+  = note: expected ')', found 'newline'
+";
+    assert_eq!(format_error(&file, &err, ColorChoice::Never), expected);
+
+    // The underline covers the whole offending token.
+    let source = "func Main() {\n    var x = 1 + else\n}";
+    let file = SourceFile::new("test.vu", source);
+    let err = parse_str(source).expect_err("expected missing operand");
+
+    let expected = "\
+error: syntax error: expression expected
+ --> test.vu:2:17
+  |
+2 |     var x = 1 + else
+  |                 ^^^^
+  |
+  = note: found 'else'
+";
+    assert_eq!(format_error(&file, &err, ColorChoice::Never), expected);
+
+    let colored = format_error(&file, &err, ColorChoice::Always);
+    assert!(colored.contains("\x1b[1;31merror\x1b[0m"));
+}
+
+#[test]
+fn test_error_recovery() {
+    let source = "\
+func Main() {
+    var x = 1 +
+    var y = (2
+    Foo(x, y)
+    return else
+}
+var z = ;
+func Add(a: int, b: int) -> int {
+    return a + b
+}
+";
+    let file = SourceFile::new("test.vu", source);
+    let (module, diagnostics) = parse_str_with_diagnostics(source);
+
+    let locations: Vec<String> = diagnostics
+        .iter()
+        .map(|err| file.location(err.span().start).to_string())
+        .collect();
+    assert_eq!(
+        locations,
+        ["test.vu:2:16", "test.vu:3:15", "test.vu:5:12", "test.vu:7:9"],
+        "{diagnostics:#?}"
+    );
+
+    // Statements around the errors are still parsed.
+    assert_eq!(module.stmts.len(), 3);
+    let main = module.stmts[0].func().unwrap();
+    assert_eq!(main.body.stmts.len(), 4);
+    assert!(main.body.stmts[2].simple().unwrap().expr().unwrap().expr_call().is_some());
+    assert_eq!(module.stmts[2].func().unwrap().name.text, "Add");
+
+    // Skipped source code is replaced by placeholders.
+    let x = main.body.stmts[0].var().unwrap().rhs.as_ref().unwrap();
+    assert!(matches!(x, Expr::Unknown(_)));
+    assert_eq!(file.location(x.span().start).to_string(), "test.vu:2:13");
+    assert!(matches!(main.body.stmts[3].return1(), Some(Expr::Unknown(_))));
+}
+
+#[test]
+fn test_error_recovery_unknown_stmt() {
+    let source = "func Main() {\n    else 1 + 2\n    Foo()\n}";
+    let (module, diagnostics) = parse_str_with_diagnostics(source);
+    assert_eq!(diagnostics.len(), 1);
+
+    let main = module.stmts[0].func().unwrap();
+    assert_eq!(main.body.stmts.len(), 2);
+
+    match main.body.stmts[0].simple() {
+        Some(SimpleStmt::Unknown(span)) => {
+            let text = &source[span.start.to_u32() as usize..span.end.to_u32() as usize];
+            assert_eq!(text, "else 1 + 2");
+        }
+        stmt => panic!("expected unknown statement: {stmt:?}"),
+    }
+
+    // The first error is returned when parsing without diagnostics.
+    let err = parse_str(source).expect_err("expected syntax error");
+    assert_eq!(err.found(), TokenKind::Keyword(Keyword::Else));
+}
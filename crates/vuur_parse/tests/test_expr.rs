@@ -1,14 +1,30 @@
-use vuur_lexer::{Lexer, TokenKind};
+use vuur_lexer::number::NumValue;
+use vuur_lexer::span::{Span, Spanned};
+use vuur_lexer::{LexErrorKind, Lexer, TokenKind};
 use vuur_parse::{
-    expr::{BinaryOp, Expr, OperatorKind},
+    expr::{BinaryOp, Expr, InterpPart, OperatorKind},
     stream::TokenStream,
-    Parse,
+    Parse, ParseError,
 };
 
 /// Test number literal parsing.
 #[test]
 fn test_number_literal() {
-    let cases: &[(&str, i32)] = &[("1", 1), ("123456", 123456), ("000", 0)];
+    let cases: &[(&str, NumValue)] = &[
+        ("1", NumValue::I32(1)),
+        ("123456", NumValue::I32(123456)),
+        ("000", NumValue::I32(0)),
+        ("1_000_000", NumValue::I32(1_000_000)),
+        ("0xFF", NumValue::I32(255)),
+        ("0b1010", NumValue::I32(10)),
+        ("0o17", NumValue::I32(15)),
+        ("10i64", NumValue::I64(10)),
+        ("1.5e-3", NumValue::F32(1.5e-3)),
+        ("2.0f32", NumValue::F32(2.0)),
+        ("0.25f64", NumValue::F64(0.25)),
+        ("-2147483648", NumValue::I32(i32::MIN)),
+        ("-9223372036854775808i64", NumValue::I64(i64::MIN)),
+    ];
 
     for (case_no, (source, expected)) in cases.iter().enumerate() {
         let lexer = Lexer::from_source(source);
@@ -20,6 +36,115 @@ fn test_number_literal() {
     }
 }
 
+/// Number literals that don't fit their type are lexical errors.
+#[test]
+fn test_number_literal_overflow() {
+    let cases: &[(&str, LexErrorKind)] = &[
+        ("2147483648", LexErrorKind::NumberOverflow),
+        ("-2147483649", LexErrorKind::NumberOverflow),
+        ("0x1_0000_0000_0000_0000i64", LexErrorKind::NumberOverflow),
+        ("1e39", LexErrorKind::NumberOverflow),
+        ("1e309f64", LexErrorKind::NumberOverflow),
+        ("12u8", LexErrorKind::InvalidNumberSuffix),
+    ];
+
+    for (source, expected) in cases {
+        let lexer = Lexer::from_source(source);
+        let mut stream = TokenStream::new(lexer);
+        match Expr::parse(&mut stream) {
+            Err(ParseError::Lex(err)) => {
+                assert_eq!(err.kind, *expected);
+                assert_eq!(err.offset.to_u32(), 0);
+                assert_eq!(err.size as usize, source.len());
+            }
+            result => panic!("expected lexical error for '{source}', got {result:?}"),
+        }
+    }
+}
+
+/// Test string literal parsing.
+#[test]
+fn test_string_literal() {
+    let cases: &[(&str, &str)] = &[
+        (r#""""#, ""),
+        (r#""hello, world""#, "hello, world"),
+        (r#""tab\tnewline\n""#, "tab\tnewline\n"),
+        (r#""\\ \"quoted\"""#, "\\ \"quoted\""),
+        (r#""\u{48}\u{1F525}""#, "H🔥"),
+    ];
+
+    for (case_no, (source, expected)) in cases.iter().enumerate() {
+        let lexer = Lexer::from_source(source);
+        let mut stream = TokenStream::new(lexer);
+        let expr = Expr::parse(&mut stream)
+            .unwrap_or_else(|err| panic!("case {}: failed to parse string literal '{}': {}", case_no, source, err));
+        let str_lit = expr.expr_str_lit().expect("expression is not a string literal");
+        assert_eq!(str_lit.value, *expected);
+        assert_eq!(str_lit.token.fragment(source), *source);
+    }
+}
+
+/// Malformed string literals are reported as lexical errors.
+#[test]
+fn test_string_literal_error() {
+    let cases: &[(&str, LexErrorKind)] = &[
+        (r#""unterminated"#, LexErrorKind::UnterminatedString),
+        (r#""bad \escape""#, LexErrorKind::InvalidEscape),
+        (r#""\u{XYZ}""#, LexErrorKind::InvalidUnicodeEscape),
+    ];
+
+    for (source, expected) in cases {
+        let lexer = Lexer::from_source(source);
+        let mut stream = TokenStream::new(lexer);
+        match Expr::parse(&mut stream) {
+            Err(ParseError::Lex(err)) => {
+                assert_eq!(err.kind, *expected);
+                assert_eq!(err.offset.to_u32(), 0);
+                assert_eq!(err.size as usize, source.len());
+            }
+            result => panic!("expected lexical error for '{source}', got {result:?}"),
+        }
+    }
+}
+
+/// Test string interpolation parsing.
+#[test]
+fn test_string_interpolation() {
+    let source = r#""hello %(name), you have %(count + 1) items\%(not) %("nested %(x)")""#;
+    let lexer = Lexer::from_source(source);
+    let mut stream = TokenStream::new(lexer);
+    let expr = Expr::parse(&mut stream).expect("parse interpolated string");
+    let interp = expr.expr_str_interp().expect("expression is not an interpolated string");
+    assert_eq!(interp.parts.len(), 7);
+
+    let strings = interp
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            InterpPart::Str(string) => Some(string.value.as_str()),
+            InterpPart::Expr(_) => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(strings, ["hello ", ", you have ", " items%(not) ", ""]);
+
+    let exprs = interp
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            InterpPart::Expr(expr) => Some(expr),
+            InterpPart::Str(_) => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(exprs[0].expr_name_access().expect("name access").ident.text, "name");
+    assert_eq!(
+        exprs[1].expr_bin_op().expect("binary op").operator.kind,
+        OperatorKind::Add
+    );
+
+    let nested = exprs[2].expr_str_interp().expect("nested interpolation");
+    assert_eq!(nested.parts.len(), 3);
+}
+
 /// Test parentheses groups.
 #[test]
 fn test_parentheses_group() {
@@ -148,7 +273,7 @@ fn test_simple_call_arithm_arg() {
 
     // arg 1: "1 + 2"
     {
-        let BinaryOp { operator, lhs, rhs } = &expr.expr_call().unwrap().args[0]
+        let BinaryOp { operator, lhs, rhs, .. } = &expr.expr_call().unwrap().args[0]
             .simple()
             .expect("simple call arg")
             .expr_bin_op()
@@ -176,7 +301,7 @@ fn test_simple_call_arithm_arg() {
     // arg 3: "2 - 4 * 5"
     {
         // "2 - ..."
-        let BinaryOp { operator, lhs, rhs } = &expr.expr_call().unwrap().args[2]
+        let BinaryOp { operator, lhs, rhs, .. } = &expr.expr_call().unwrap().args[2]
             .simple()
             .expect("simple call arg")
             .expr_bin_op()
@@ -188,7 +313,7 @@ fn test_simple_call_arithm_arg() {
         assert_eq!(lhs.expr_num_lit().unwrap().token.fragment(source), "2");
 
         // "... 4 * 5"
-        let BinaryOp { operator, lhs, rhs } = rhs.expr_bin_op().unwrap();
+        let BinaryOp { operator, lhs, rhs, .. } = rhs.expr_bin_op().unwrap();
 
         assert_eq!(operator.token.fragment(source), "*");
         assert_eq!(lhs.expr_num_lit().unwrap().token.fragment(source), "4");
@@ -284,3 +409,181 @@ fn test_field_setter_path() {
         "128"
     );
 }
+
+/// Test subscript, which can be chained and used as a setter.
+#[test]
+fn test_subscript() {
+    let source = "foo.bar[i + 1][0]";
+    let lexer = Lexer::from_source(source);
+    let mut stream = TokenStream::new(lexer);
+    let expr = Expr::parse(&mut stream).expect("expr parse");
+    println!("{:#?}", expr);
+
+    let outer = expr.expr_index().expect("outer subscript");
+    assert_eq!(
+        outer.index.expr_num_lit().expect("number literal").token.fragment(source),
+        "0"
+    );
+    assert_eq!(span_text(source, outer.span), "foo.bar[i + 1][0]");
+
+    let inner = outer.lhs.expr_index().expect("inner subscript");
+    assert!(inner.lhs.expr_member_access().is_some());
+    assert!(inner.index.expr_bin_op().is_some());
+    assert_eq!(span_text(source, inner.span), "foo.bar[i + 1]");
+
+    let source = "foo[1] = 42";
+    let lexer = Lexer::from_source(source);
+    let mut stream = TokenStream::new(lexer);
+    let expr = Expr::parse(&mut stream).expect("expr parse");
+
+    let assign = expr.expr_index_assign().expect("subscript assign");
+    assert_eq!(assign.lhs.expr_name_access().expect("name").ident.text, "foo");
+    assert_eq!(
+        assign.index.expr_num_lit().expect("number literal").token.fragment(source),
+        "1"
+    );
+    assert_eq!(assign.operator.kind, TokenKind::Eq);
+    assert_eq!(span_text(source, assign.span), "foo[1] = 42");
+}
+
+/// Test structure literal with fields separated by commas and newlines.
+#[test]
+fn test_struct_literal() {
+    let sources = &[
+        "Vec { x: 1.0, y: a + b }",
+        "Vec {\n    x: 1.0,\n    y: a + b,\n}",
+        "Vec {\n    x: 1.0\n    y: a + b\n}",
+    ];
+
+    for source in sources {
+        let lexer = Lexer::from_source(source);
+        let mut stream = TokenStream::new(lexer);
+        let expr = Expr::parse(&mut stream).expect("expr parse");
+
+        let lit = expr.expr_struct_lit().expect("struct literal");
+        assert_eq!(lit.name.text, "Vec");
+        assert_eq!(span_text(source, lit.span), *source);
+
+        assert_eq!(lit.fields.len(), 2);
+        assert_eq!(lit.fields[0].name.text, "x");
+        assert!(lit.fields[0].rhs.expr_num_lit().is_some());
+        assert_eq!(lit.fields[1].name.text, "y");
+        assert!(lit.fields[1].rhs.expr_bin_op().is_some());
+    }
+}
+
+/// A name followed by a block is not a structure literal.
+#[test]
+fn test_struct_literal_block() {
+    let source = "func Main() {\n    if a {\n        b\n    }\n    while c {}\n}";
+    let module = vuur_parse::parse_str(source).expect("parse");
+    let func = module.stmts[0].func().unwrap();
+
+    let if_stmt = match func.body.stmts[0].simple() {
+        Some(vuur_parse::stmt::SimpleStmt::If(if_stmt)) => if_stmt,
+        stmt => panic!("expected if statement: {stmt:?}"),
+    };
+    assert!(if_stmt.cond.expr_name_access().is_some());
+}
+
+/// Render an expression as an s-expression, to make operator grouping visible.
+/// Slice the source code covered by a span.
+fn span_text(source: &str, span: Span) -> &str {
+    &source[span.start.to_u32() as usize..span.end.to_u32() as usize]
+}
+
+#[test]
+fn test_expr_spans() {
+    let cases: &[(&str, &str)] = &[
+        ("  1 + 2 * 3  ", "1 + 2 * 3"),
+        ("-(a + b)", "-(a + b)"),
+        ("(a)", "(a)"),
+        ("foo.bar.baz", "foo.bar.baz"),
+        ("foo.bar(1, x + 2)  ", "foo.bar(1, x + 2)"),
+        ("a = b + c", "a = b + c"),
+        ("foo.bar = 42", "foo.bar = 42"),
+        ("\"a %(b) c\"", "\"a %(b) c\""),
+    ];
+
+    for (source, expected) in cases.iter().copied() {
+        let mut stream = TokenStream::new(Lexer::from_source(source));
+        let expr = Expr::parse(&mut stream).expect("parsing expression");
+        assert_eq!(
+            span_text(source, expr.span()),
+            expected,
+            "unexpected span for '{source}'"
+        );
+    }
+}
+
+#[test]
+fn test_member_path_spans() {
+    let source = "foo.bar.baz(a, b)";
+    let mut stream = TokenStream::new(Lexer::from_source(source));
+    let expr = Expr::parse(&mut stream).expect("parsing expression");
+
+    let call = expr.expr_call().unwrap();
+    assert_eq!(span_text(source, call.span), "foo.bar.baz(a, b)");
+    assert_eq!(span_text(source, call.args[1].span()), "b");
+
+    let access = call.callee.expr_member_access().unwrap();
+    assert_eq!(span_text(source, access.span), "foo.bar.baz");
+    assert_eq!(span_text(source, access.name.span()), "baz");
+
+    let path = access.path.path().unwrap();
+    assert_eq!(span_text(source, path.span), "foo.bar");
+    assert_eq!(span_text(source, path.path.span()), "foo");
+}
+
+fn to_sexpr(expr: &Expr, source: &str) -> String {
+    match expr {
+        Expr::Num(num) => num.token.fragment(source).to_string(),
+        Expr::Bool(boolean) => boolean.value.to_string(),
+        Expr::NameAccess(access) => access.ident.text.to_string(),
+        Expr::Group(group) => to_sexpr(&group.expr, source),
+        Expr::Unary(unary) => format!("({:?} {})", unary.operator.kind, to_sexpr(&unary.rhs, source)),
+        Expr::Binary(binary) => format!(
+            "({:?} {} {})",
+            binary.operator.kind,
+            to_sexpr(&binary.lhs, source),
+            to_sexpr(&binary.rhs, source)
+        ),
+        _ => panic!("unexpected expression: {expr:?}"),
+    }
+}
+
+/// Test operator precedence and associativity.
+#[test]
+fn test_operator_precedence() {
+    let cases: &[(&str, &str)] = &[
+        ("1 + 2 * 3", "(Add 1 (Mul 2 3))"),
+        ("a - b - c", "(Sub (Sub a b) c)"),
+        ("10 % 3 * 2", "(Mul (Mod 10 3) 2)"),
+        ("2 ** 3 ** 2", "(Pow 2 (Pow 3 2))"),
+        ("-2 ** 2", "(Neg (Pow 2 2))"),
+        ("-2 * 3", "(Mul -2 3)"),
+        ("- 2 * 3", "(Mul (Neg 2) 3)"),
+        ("1 -2", "(Sub 1 2)"),
+        ("2 * 3 ** 2", "(Mul 2 (Pow 3 2))"),
+        ("a || b && c", "(Or a (And b c))"),
+        ("a && b || c", "(Or (And a b) c)"),
+        ("a == b < c", "(Equals a (Less b c))"),
+        ("a <= b != c >= d", "(NotEquals (LessEq a b) (GreaterEq c d))"),
+        ("a > b", "(Greater a b)"),
+        ("a | b ^ c & d", "(BitOr a (BitXor b (BitAnd c d)))"),
+        ("1 << 2 + 3", "(ShiftLeft 1 (Add 2 3))"),
+        ("a >> b & c", "(BitAnd (ShiftRight a b) c)"),
+        ("a .. b + 1", "(Range a (Add b 1))"),
+        ("0 ... n", "(RangeExclusive 0 n)"),
+        ("!a != ~b", "(NotEquals (Not a) (BitNot b))"),
+        ("!(a || b)", "(Not (Or a b))"),
+        ("!true == false", "(Equals (Not true) false)"),
+    ];
+
+    for (source, expected) in cases {
+        let lexer = Lexer::from_source(source);
+        let mut stream = TokenStream::new(lexer);
+        let expr = Expr::parse(&mut stream).unwrap_or_else(|err| panic!("failed to parse '{source}': {err}"));
+        assert_eq!(to_sexpr(&expr, source), *expected, "unexpected grouping of '{source}'");
+    }
+}
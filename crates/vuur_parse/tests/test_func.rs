@@ -1,9 +1,9 @@
-use vuur_lexer::span::BytePos;
-use vuur_lexer::Lexer;
+use vuur_lexer::span::{BytePos, Span, Spanned};
+use vuur_lexer::{LexErrorKind, Lexer};
 use vuur_parse::delim::Delimited;
-use vuur_parse::func::{FuncArg, Separator};
+use vuur_parse::func::{FuncArg, Receiver, Separator};
 use vuur_parse::stream::TokenStream;
-use vuur_parse::{parse_str, Parse};
+use vuur_parse::{parse_str, Parse, ParseError};
 
 #[test]
 fn test_func_parse() {
@@ -21,13 +21,219 @@ fn test_delimited() {
 
     let pair1 = &delimited.pairs[0];
     assert_eq!(pair1.item.name.token.offset, BytePos::from_u32(0));
-    assert_eq!(pair1.item.ty.token.offset, BytePos::from_u32(3));
+    assert_eq!(pair1.item.ty.span().start, BytePos::from_u32(3));
 
     let pair2 = &delimited.pairs[1];
     assert_eq!(pair2.item.name.token.offset, BytePos::from_u32(8));
-    assert_eq!(pair2.item.ty.token.offset, BytePos::from_u32(11));
+    assert_eq!(pair2.item.ty.span().start, BytePos::from_u32(11));
 
     let pair3 = &delimited.pairs[2];
     assert_eq!(pair3.item.name.token.offset, BytePos::from_u32(16));
-    assert_eq!(pair3.item.ty.token.offset, BytePos::from_u32(20));
+    assert_eq!(pair3.item.ty.span().start, BytePos::from_u32(20));
+
+    // Pairs include their trailing delimiter.
+    assert_eq!(pair1.span(), Span::new(BytePos::from_u32(0), BytePos::from_u32(7)));
+    assert_eq!(pair3.span(), Span::new(BytePos::from_u32(16), BytePos::from_u32(23)));
+    assert_eq!(delimited.span, Span::new(BytePos::from_u32(0), BytePos::from_u32(23)));
+}
+
+#[test]
+fn test_func_spans() {
+    let source = "func Add(a: int, b: int) -> int {\n    if a {\n        return a\n    }\n    return a + b\n}\n";
+    let module = parse_str(source).unwrap();
+    let text = |span: Span| &source[span.start.to_u32() as usize..span.end.to_u32() as usize];
+
+    let func = module.stmts[0].func().unwrap();
+    assert_eq!(text(func.span), &source[..source.len() - 1]);
+    assert_eq!(text(func.args.span), "a: int, b: int");
+    assert_eq!(text(func.rtn.as_ref().unwrap().span), "-> int");
+    assert_eq!(text(func.body.stmts[0].span()), "if a {\n        return a\n    }");
+    assert_eq!(text(func.body.stmts[1].span()), "return a + b");
+    assert_eq!(module.span.end, BytePos::from_u32(source.len() as u32));
+
+    // An empty argument list points to where the arguments would be.
+    let module = parse_str("func Main() {\n}").unwrap();
+    let args = &module.stmts[0].func().unwrap().args;
+    assert!(args.pairs.is_empty());
+    assert_eq!(args.span, Span::empty(BytePos::from_u32(10)));
+}
+
+#[test]
+fn test_comments_skipped() {
+    let source = r#"
+// Line comment before a function.
+func Add(a: int, /* inline */ b: int) -> int { // trailing comment
+    /* block comment
+       /* nested */
+    */
+    return a + b // trailing comment
+}
+"#;
+    let module = parse_str(source).expect("parse module with comments");
+    assert_eq!(module.stmts.len(), 1);
+
+    let func = module.stmts[0].func().expect("function definition");
+    assert!(func.doc.is_none());
+    assert_eq!(func.args.pairs.len(), 2);
+    assert_eq!(func.body.stmts.len(), 1);
+}
+
+#[test]
+fn test_doc_comments() {
+    let source = r#"
+/// Vector length.
+type Length = float
+
+/// Adds two numbers
+///   together.
+///
+func Add(a: int, b: int) -> int {
+    /// Sum of the arguments.
+    var c = a + b
+    return c
+}
+
+//// Regular comment.
+func Main() {}
+"#;
+    let module = parse_str(source).expect("parse module with doc comments");
+    assert_eq!(module.stmts.len(), 3);
+
+    let type_def = module.stmts[0].type_def().expect("type definition");
+    assert_eq!(type_def.name.text, "Length");
+    assert_eq!(type_def.doc.as_ref().expect("type doc").text, "Vector length.");
+
+    let func = module.stmts[1].func().expect("function definition");
+    let doc = func.doc.as_ref().expect("function doc");
+    assert_eq!(doc.text, "Adds two numbers\n  together.\n");
+    assert_eq!(doc.lines.len(), 3);
+    assert_eq!(doc.lines[0].fragment(source), "/// Adds two numbers");
+
+    let var = func.body.stmts[0].var().expect("variable definition");
+    assert_eq!(var.doc.as_ref().expect("variable doc").text, "Sum of the arguments.");
+
+    let main = module.stmts[2].func().expect("function definition");
+    assert!(main.doc.is_none());
+}
+
+#[test]
+fn test_doc_comment_errors() {
+    // Doc comment must be followed by something to document.
+    assert!(parse_str("func Main() {\n    /// dangling\n}\n").is_err());
+    assert!(parse_str("/// dangling").is_err());
+
+    // Unterminated block comment is reported where it opens.
+    match parse_str("func Main() {}\n/* /* */\n") {
+        Err(ParseError::Lex(err)) => {
+            assert_eq!(err.kind, LexErrorKind::UnterminatedComment);
+            assert_eq!(err.offset, BytePos::from_u32(15));
+        }
+        result => panic!("expected lexical error, got {result:?}"),
+    }
+}
+
+#[test]
+fn test_method_receiver() {
+    let source = r#"
+func (vec: *Vec) Magnitude() -> float {
+    return vec.x
+}
+
+func ( *Vec ) MagnitudeSqr() -> float {
+    return self.x
+}
+
+func (Vec) Zero() {}
+
+func Plain() {}
+"#;
+    let module = parse_str(source).expect("parse methods");
+    let text = |span: Span| &source[span.start.to_u32() as usize..span.end.to_u32() as usize];
+
+    let func = module.stmts[0].func().unwrap();
+    let receiver = func.receiver.as_ref().expect("named receiver");
+    assert_eq!(receiver.name.as_ref().unwrap().text, "vec");
+    assert_eq!(receiver.name_text(), "vec");
+    assert_eq!(receiver.ty.text, "Vec");
+    assert!(receiver.is_ptr);
+    assert_eq!(text(receiver.span), "(vec: *Vec)");
+    assert_eq!(func.name.text, "Magnitude");
+
+    let func = module.stmts[1].func().unwrap();
+    let receiver = func.receiver.as_ref().expect("anonymous receiver");
+    assert!(receiver.name.is_none());
+    assert_eq!(receiver.name_text(), Receiver::SELF);
+    assert!(receiver.is_ptr);
+    assert_eq!(text(receiver.span), "( *Vec )");
+    assert_eq!(func.name.text, "MagnitudeSqr");
+
+    let receiver = module.stmts[2].func().unwrap().receiver.as_ref().expect("value receiver");
+    assert!(receiver.name.is_none());
+    assert!(!receiver.is_ptr);
+
+    assert!(module.stmts[3].func().unwrap().receiver.is_none());
+
+    // Receiver must name a type.
+    assert!(parse_str("func (vec: *) Magnitude() {}").is_err());
+}
+
+#[test]
+fn test_operator_method_names() {
+    let source = r#"
+func (a: *Vec) +(b: Vec) -> Vec {}
+func (a: *Vec) ==(b: Vec) -> bool {}
+func (a: *Vec) <=(b: Vec) -> bool {}
+func (a: *Vec) [](index: i32) -> f32 {}
+func (a: *Vec) []=(index: i32, value: f32) {}
+"#;
+    let module = parse_str(source).expect("parse operator methods");
+    let text = |span: Span| &source[span.start.to_u32() as usize..span.end.to_u32() as usize];
+
+    let names = module
+        .stmts
+        .iter()
+        .map(|stmt| stmt.func().unwrap().name.text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["+", "==", "<=", "[]", "[]="]);
+
+    let func = module.stmts[4].func().unwrap();
+    assert_eq!(text(func.name.span()), "[]=");
+    assert_eq!(func.args.pairs.len(), 2);
+
+    // Operators are only names of methods.
+    assert!(parse_str("func +(a: i32, b: i32) -> i32 {}").is_err());
+    assert!(parse_str("func (a: *Vec) [ ](index: i32) -> f32 {}").is_err());
+}
+
+#[test]
+fn test_anonymous_func() {
+    let source = r#"
+func Main() {
+    var double = func(a: i32) -> i32 { return a * 2 }
+    var noop = func() {
+    }
+    Apply(func(a: i32) -> i32 { return a }, 1)
+}
+"#;
+    let module = parse_str(source).expect("parse anonymous functions");
+    let text = |span: Span| &source[span.start.to_u32() as usize..span.end.to_u32() as usize];
+    let body = &module.stmts[0].func().unwrap().body;
+
+    let lit = body.stmts[0].var().unwrap().rhs.as_ref().unwrap().expr_func_lit().unwrap();
+    assert_eq!(text(lit.span), "func(a: i32) -> i32 { return a * 2 }");
+    assert_eq!(lit.args.pairs[0].item.name.text, "a");
+    assert_eq!(text(lit.rtn.as_ref().unwrap().span), "-> i32");
+    assert_eq!(lit.body.stmts.len(), 1);
+
+    let lit = body.stmts[1].var().unwrap().rhs.as_ref().unwrap().expr_func_lit().unwrap();
+    assert!(lit.args.pairs.is_empty());
+    assert!(lit.rtn.is_none());
+
+    // The body doesn't end the statement, so more arguments can follow.
+    let call = body.stmts[2].simple().unwrap().expr().unwrap().expr_call().unwrap();
+    assert_eq!(call.args.len(), 2);
+    assert!(call.args[0].simple().unwrap().expr_func_lit().is_some());
+
+    // Anonymous functions have no name.
+    assert!(parse_str("func Main() {\n    var f = func F() {}\n}").is_err());
 }
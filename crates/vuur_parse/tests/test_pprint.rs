@@ -7,22 +7,25 @@ const FG_RESET: &str = "\x1b[0m";
 
 fn parse_expr(source_code: &str) -> Option<Expr> {
     let mut module = vuur_parse::parse_str(source_code).expect("parsing test module");
-    if let Some(DefStmt::Simple(stmt)) = module.stmts.pop() {
-        if let SimpleStmt::Expr(expr) = stmt {
-            return Some(expr);
-        }
+    if let Some(DefStmt::Simple(SimpleStmt::Expr(expr))) = module.stmts.pop() {
+        return Some(expr);
     }
     None
 }
 
 const CASES: &[&str] = &[
     "1 + 2 * 3",
+    "0xFF + 1.5e-3f64 * 10i64",
     "(1 + 2) * 3",
     "1 * 2 * 3",
     "1 + 2 - 3 * 4",
     "1 + (2 - 3) * 4 / 5",
     "-1",
     "1 + (-2)",
+    "!a || b && c != d",
+    "a | b ^ c & d << 1 >= 2 % 3",
+    "-2 ** 3 ** 2",
+    "~x .. y ... z",
     "x = 1 + 2",
     "z = x + y * 32",
     "one.two.three",
@@ -30,6 +33,8 @@ const CASES: &[&str] = &[
     "one(2 + 3, 4 * 5, 6, seven)",
     "position = Vector(3, 4)",
     "x = sqrt(((1 + 2) * 3) - ((4 + 5) * 6))",
+    r#"greet("hello\tworld")"#,
+    r#"greet("hello %(name), you have %(count + 1) items")"#,
 ];
 
 #[test]
@@ -0,0 +1,57 @@
+//! Functions created at runtime, with the variables they captured.
+//!
+//! A captured variable stays on the operand stack while the function that
+//! declared it is executing, and the upvalue points at its stack slot. When
+//! the local goes out of scope, the upvalue is closed by moving the value
+//! into it, so closures that outlive the call frame keep the variable.
+//!
+//! Closures that capture the same local share the upvalue, so they
+//! see each other's assignments.
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::value::Value;
+
+/// Function with the upvalues it captured from its enclosing functions.
+pub struct Closure {
+    pub func_id: u32,
+    /// Upvalues indexed in the order the compiler captured them.
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+/// Variable captured by a closure.
+#[derive(Debug)]
+pub enum Upvalue {
+    /// Variable is a local still on the stack, at the absolute slot index.
+    Open(usize),
+    /// Variable outlived its scope, and its value moved into the upvalue.
+    Closed(Value),
+}
+
+impl Closure {
+    pub fn new(func_id: u32) -> Self {
+        Self {
+            func_id,
+            upvalues: Vec::new(),
+        }
+    }
+}
+
+/// Closures are only equal to themselves, since
+/// the variables they captured can change.
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Upvalues can refer back to the closure, so they're not formatted.
+        f.debug_struct("Closure")
+            .field("func_id", &self.func_id)
+            .field("upvalues", &self.upvalues.len())
+            .finish()
+    }
+}
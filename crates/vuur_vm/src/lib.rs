@@ -2,21 +2,41 @@ pub use std::cell::Ref;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use vuur_compile::bytecode::{decode_arg_a, decode_arg_k, decode_opcode, opcodes as ops};
-use vuur_compile::Chunk;
+use vuur_compile::bytecode::{
+    decode_arg_a, decode_arg_k, decode_dyn_call, decode_field, decode_opcode, decode_u64, opcodes as ops,
+};
+use vuur_compile::{Chunk, FieldTy, TypeDef, TypeId};
 
+pub mod closure;
 pub mod error;
 pub mod obj;
+pub mod value;
 
+use self::closure::{Closure, Upvalue};
 use self::error::{ErrorKind, Result, RuntimeError};
+use self::obj::{FieldKind, Obj, ObjBuilder, ObjError, ObjInfo};
+use self::value::{FromValue, Value};
+
+/// Execution trace of the interpreter loop, logged per instruction.
+///
+/// Compiled out unless the `trace` feature is enabled, so the
+/// dispatch loop doesn't pay for formatting or the log level check.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        log::trace!($($arg)*);
+    };
+}
 
 pub const STRIDE: usize = 4;
-pub const END_OF_CHUNK: usize = std::usize::MAX;
+pub const END_OF_CHUNK: usize = usize::MAX;
 
 #[derive(Debug)]
 pub struct VM {
     /// Current running fiber
     pub(crate) fiber: Rc<RefCell<Fiber>>,
+    /// Object layouts of the loaded chunk's types, indexed by type ID.
+    pub(crate) types: Vec<Rc<ObjInfo>>,
 }
 
 #[derive(Debug)]
@@ -24,42 +44,72 @@ pub struct Fiber {
     /// Instruction pointer
     pub(crate) ip: usize,
     /// Operand stack
-    pub(crate) stack: Vec<u32>,
+    pub(crate) stack: Vec<Value>,
     /// Call stack of function return information.
     pub(crate) calls: Vec<FrameInfo>,
+    /// Upvalues of captured locals that are still on the
+    /// operand stack, sorted by their stack slot.
+    pub(crate) open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
     /// Indicates if the fiber intends to resume execution in the future
     pub(crate) done: bool,
     /// ---------------------------
     pub(crate) error: Option<String>,
+    /// Address of the instruction that caused the error.
+    pub(crate) error_addr: usize,
 }
 
 #[derive(Debug)]
 struct FrameInfo {
+    /// Function being executed in this call frame.
+    func_id: u32,
     /// Offset in the stack where this call frame's
     /// local stack starts.
     base: usize,
     /// Byte offset in chunk to return to when
     /// this stack frame is popped.
     return_addr: usize,
+    /// Closure being executed, which holds the upvalues
+    /// of the variables captured by the function.
+    closure: Option<Rc<Closure>>,
 }
 
 impl Default for FrameInfo {
     #[inline]
     fn default() -> Self {
         Self {
+            func_id: 0,
             base: 0,
             return_addr: 0,
+            closure: None,
         }
     }
 }
 
+impl Default for VM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl VM {
     pub fn new() -> Self {
         Self {
             fiber: Rc::new(RefCell::new(Fiber::new())),
+            types: Vec::new(),
         }
     }
 
+    /// Object layout of a type declared in the loaded chunk.
+    #[inline]
+    pub fn obj_info(&self, type_id: TypeId) -> Option<&Rc<ObjInfo>> {
+        self.types.get(type_id.to_usize())
+    }
+
+    /// Build the object layouts for the types declared in the chunk.
+    fn load_types(&mut self, chunk: &Chunk) {
+        self.types = chunk.types().iter().map(|type_def| Rc::new(build_obj_info(type_def))).collect();
+    }
+
     /// The current fiber that the VM will execute when resumed.
     #[inline]
     pub fn fiber(&self) -> Ref<'_, Fiber> {
@@ -67,17 +117,21 @@ impl VM {
     }
 
     // TODO: Return value from finished fiber
-    pub fn run(&mut self, chunk: &Chunk) -> Option<u32> {
+    pub fn run(&mut self, chunk: &Chunk) -> Option<Value> {
+        self.load_types(chunk);
+
         let entrypoint_id = chunk.entrypoint().unwrap();
         let entrypoint = chunk.func_by_id(entrypoint_id.to_u32());
         let entrypoint_addr = entrypoint.map(|f| f.bytecode_span.0).unwrap_or(0) as usize;
+        let local_count = entrypoint.map(|f| f.local_count).unwrap_or(0);
 
         match (*self.fiber).try_borrow_mut() {
             Ok(mut fiber) => {
                 fiber.ip = entrypoint_addr;
-                fiber.run(chunk);
+                fiber.enter_root(entrypoint_id.to_u32(), local_count);
+                fiber.run(chunk, &self.types);
                 if let Some(error) = &fiber.error {
-                    println!("runtime error: {}", error);
+                    log::debug!("runtime error: {}", error);
                     None
                 } else if fiber.done {
                     // Fiber is done executing, and cannot be resumed.
@@ -87,7 +141,7 @@ impl VM {
                             kind: ErrorKind::Nil, ..
                         }) => None,
                         Err(err) => {
-                            eprintln!("failed getting return value from fiber: {}", err);
+                            log::error!("failed getting return value from fiber: {}", err);
                             None
                         }
                     }
@@ -97,7 +151,7 @@ impl VM {
                 }
             }
             Err(err) => {
-                eprintln!("fiber already borrowed: {}", err);
+                log::error!("fiber already borrowed: {}", err);
                 None
             }
         }
@@ -108,6 +162,60 @@ impl VM {
     }
 }
 
+/// Lay out the fields of a declared type, in declaration order.
+fn build_obj_info(type_def: &TypeDef) -> ObjInfo {
+    type_def
+        .fields
+        .iter()
+        .fold(ObjBuilder::new().with_name(&type_def.name), |builder, field| {
+            let kind = match field.ty {
+                FieldTy::Bool => FieldKind::Bool,
+                FieldTy::I32 => FieldKind::I32,
+                FieldTy::I64 => FieldKind::I64,
+                FieldTy::F32 => FieldKind::F32,
+                FieldTy::F64 => FieldKind::F64,
+            };
+            builder.with_field(&field.name, kind)
+        })
+        .build()
+}
+
+/// Read an object field as a runtime value of the field's declared
+/// type, which fails when the object's layout doesn't agree.
+fn field_value(obj: &Obj, index: usize, ty: FieldTy) -> std::result::Result<Value, ObjError> {
+    match ty {
+        FieldTy::Bool => obj.field::<bool>(index).copied().map(Value::Bool),
+        FieldTy::I32 => obj.field::<i32>(index).copied().map(Value::I32),
+        FieldTy::I64 => obj.field::<i64>(index).copied().map(Value::I64),
+        FieldTy::F32 => obj.field::<f32>(index).copied().map(Value::F32),
+        FieldTy::F64 => obj.field::<f64>(index).copied().map(Value::F64),
+    }
+}
+
+/// Write a runtime value into an object field, which
+/// fails when the value doesn't match the field's kind.
+fn set_field_value(obj: &mut Obj, index: usize, value: Value) -> std::result::Result<(), ObjError> {
+    match value {
+        Value::Bool(value) => obj.set_field(index, value),
+        Value::I32(value) => obj.set_field(index, value),
+        Value::I64(value) => obj.set_field(index, value),
+        Value::F32(value) => obj.set_field(index, value),
+        Value::F64(value) => obj.set_field(index, value),
+        // Remaining values have no field kind, so struct fields can't hold them yet.
+        Value::Nil | Value::Str(_) | Value::Obj(_) | Value::Iface { .. } | Value::Func(_) | Value::Closure(_) => {
+            Err(ObjError::UnsupportedValue {
+                expected: obj.info().field_kind(index)?,
+            })
+        }
+    }
+}
+
+impl Default for Fiber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Fiber {
     pub fn new() -> Self {
         Self {
@@ -115,18 +223,35 @@ impl Fiber {
             stack: Vec::with_capacity(1024),
             // FIXME: For now the top level module function directs the interpreter to the starting byte to abort.
             calls: vec![FrameInfo {
+                func_id: 0,
                 base: 0,
                 return_addr: END_OF_CHUNK,
+                closure: None,
             }],
+            open_upvalues: Vec::new(),
             done: false,
             error: None,
+            error_addr: 0,
+        }
+    }
+
+    /// Prepare the root call frame to execute the given function.
+    fn enter_root(&mut self, func_id: u32, local_count: usize) {
+        if let Some(frame) = self.calls.first_mut() {
+            frame.func_id = func_id;
         }
+
+        // Reserve stack slots for the function's local variables.
+        self.stack.resize(self.stack.len() + local_count, Value::Nil);
     }
 
-    // TODO: Support other value types for Fiber return
-    pub fn take_return(&mut self) -> Result<u32> {
+    pub fn take_return(&mut self) -> Result<Value> {
         if self.done {
-            self.stack.last().cloned().ok_or_else(|| RuntimeError::new(ErrorKind::Nil, ""))
+            self.stack
+                .last()
+                .filter(|value| !value.is_nil())
+                .cloned()
+                .ok_or_else(|| RuntimeError::new(ErrorKind::Nil, ""))
         } else {
             Err(RuntimeError::new(
                 ErrorKind::FiberState,
@@ -135,11 +260,13 @@ impl Fiber {
         }
     }
 
-    pub fn run(&mut self, chunk: &Chunk) {
-        println!("running...");
+    /// Execute the chunk's bytecode, with the object
+    /// layouts of its types indexed by type ID.
+    pub fn run(&mut self, chunk: &Chunk, types: &[Rc<ObjInfo>]) {
+        trace!("running...");
         'eval: loop {
             if self.ip >= chunk.code().len() {
-                println!("end-of-chunk");
+                trace!("end-of-chunk");
                 self.complete();
                 break 'eval;
             }
@@ -151,96 +278,391 @@ impl Fiber {
 
             let instruction = chunk.code()[self.ip];
 
+            #[cfg(feature = "trace")]
             {
                 let [o, a, b, c] = instruction.to_le_bytes();
-                self.print_ip();
-                print!("{:02X} {:02X} {:02X} {:02X}  ", o, a, b, c);
+                log::trace!("0x{:08X}  {:02X} {:02X} {:02X} {:02X}", self.ip * 4, o, a, b, c);
             }
 
             let op = decode_opcode(instruction);
 
             match op {
                 ops::NOOP => {
-                    println!("");
+                    trace!("noop");
                     self.ip += 1
                 }
                 ops::POP => {
-                    println!("pop");
+                    trace!("pop");
                     self.stack.pop();
                     self.ip += 1;
                 }
                 ops::ADD_I32 => {
-                    println!("add.i32");
-                    let b = self.stack.pop().unwrap_or_default() as i32;
-                    let a = self.stack.pop().unwrap_or_default() as i32;
+                    trace!("add.i32");
+                    let b = self.pop_i32();
+                    let a = self.pop_i32();
                     let c = a.wrapping_add(b);
-                    self.stack.push(c as u32);
+                    self.stack.push(Value::I32(c));
                     self.ip += 1;
-                    println!("  stack: {:?}", self.stack);
+                    trace!("  stack: {:?}", self.stack);
                 }
                 ops::SUB_I32 => {
-                    println!("sub.i32");
-                    let b = self.stack.pop().unwrap_or_default() as i32;
-                    let a = self.stack.pop().unwrap_or_default() as i32;
+                    trace!("sub.i32");
+                    let b = self.pop_i32();
+                    let a = self.pop_i32();
                     let c = a.wrapping_sub(b);
-                    self.stack.push(c as u32);
+                    self.stack.push(Value::I32(c));
                     self.ip += 1
                 }
                 ops::MUL_I32 => {
-                    println!("mul.i32");
-                    let b = self.stack.pop().unwrap_or_default() as i32;
-                    let a = self.stack.pop().unwrap_or_default() as i32;
+                    trace!("mul.i32");
+                    let b = self.pop_i32();
+                    let a = self.pop_i32();
                     let c = a.wrapping_mul(b);
-                    self.stack.push(c as u32);
+                    self.stack.push(Value::I32(c));
                     self.ip += 1
                 }
                 ops::DIV_I32 => {
-                    println!("div.i32");
-                    let b = self.stack.pop().unwrap_or_default() as i32;
-                    let a = self.stack.pop().unwrap_or_default() as i32;
+                    trace!("div.i32");
+                    let b = self.pop_i32();
+                    let a = self.pop_i32();
                     match a.checked_div(b) {
                         Some(c) => {
-                            self.stack.push(c as u32);
+                            self.stack.push(Value::I32(c));
                             self.ip += 1;
                         }
                         None => self.set_error("divide by zero"),
                     }
                 }
                 ops::NEG_I32 => {
-                    println!("neg.i32");
-                    let b = self.stack.pop().unwrap_or_default() as i32;
-                    self.stack.push(-b as u32);
+                    trace!("neg.i32");
+                    let b = self.pop_i32();
+                    self.stack.push(Value::I32(b.wrapping_neg()));
                     self.ip += 1;
                 }
                 ops::EQ_I32 => {
-                    println!("eq.i32");
-                    let b = self.stack.pop().unwrap_or_default() as i32;
-                    let a = self.stack.pop().unwrap_or_default() as i32;
-                    self.stack.push(if a == b { 1 } else { 0 });
-                    self.ip += 1;
+                    trace!("eq.i32");
+                    self.binary_op(|a: i32, b| a == b);
+                }
+                ops::ADD_I64 => {
+                    trace!("add.i64");
+                    self.binary_op(|a: i64, b| a.wrapping_add(b));
+                }
+                ops::SUB_I64 => {
+                    trace!("sub.i64");
+                    self.binary_op(|a: i64, b| a.wrapping_sub(b));
+                }
+                ops::MUL_I64 => {
+                    trace!("mul.i64");
+                    self.binary_op(|a: i64, b| a.wrapping_mul(b));
+                }
+                ops::DIV_I64 => {
+                    trace!("div.i64");
+                    let b = self.pop::<i64>();
+                    let a = self.pop::<i64>();
+                    match a.checked_div(b) {
+                        Some(c) => {
+                            self.stack.push(Value::I64(c));
+                            self.ip += 1;
+                        }
+                        None => self.set_error("divide by zero"),
+                    }
+                }
+                ops::NEG_I64 => {
+                    trace!("neg.i64");
+                    self.unary_op(|a: i64| a.wrapping_neg());
+                }
+                ops::EQ_I64 => {
+                    trace!("eq.i64");
+                    self.binary_op(|a: i64, b| a == b);
+                }
+                ops::ADD_F32 => {
+                    trace!("add.f32");
+                    self.binary_op(|a: f32, b| a + b);
+                }
+                ops::SUB_F32 => {
+                    trace!("sub.f32");
+                    self.binary_op(|a: f32, b| a - b);
+                }
+                ops::MUL_F32 => {
+                    trace!("mul.f32");
+                    self.binary_op(|a: f32, b| a * b);
+                }
+                ops::DIV_F32 => {
+                    trace!("div.f32");
+                    self.binary_op(|a: f32, b| a / b);
+                }
+                ops::NEG_F32 => {
+                    trace!("neg.f32");
+                    self.unary_op(|a: f32| -a);
+                }
+                ops::EQ_F32 => {
+                    trace!("eq.f32");
+                    self.binary_op(|a: f32, b| a == b);
+                }
+                ops::ADD_F64 => {
+                    trace!("add.f64");
+                    self.binary_op(|a: f64, b| a + b);
+                }
+                ops::SUB_F64 => {
+                    trace!("sub.f64");
+                    self.binary_op(|a: f64, b| a - b);
+                }
+                ops::MUL_F64 => {
+                    trace!("mul.f64");
+                    self.binary_op(|a: f64, b| a * b);
+                }
+                ops::DIV_F64 => {
+                    trace!("div.f64");
+                    self.binary_op(|a: f64, b| a / b);
+                }
+                ops::NEG_F64 => {
+                    trace!("neg.f64");
+                    self.unary_op(|a: f64| -a);
+                }
+                ops::EQ_F64 => {
+                    trace!("eq.f64");
+                    self.binary_op(|a: f64, b| a == b);
+                }
+                ops::NE_I32 => {
+                    trace!("ne.i32");
+                    self.binary_op(|a: i32, b| a != b);
+                }
+                ops::LT_I32 => {
+                    trace!("lt.i32");
+                    self.binary_op(|a: i32, b| a < b);
+                }
+                ops::LE_I32 => {
+                    trace!("le.i32");
+                    self.binary_op(|a: i32, b| a <= b);
+                }
+                ops::GT_I32 => {
+                    trace!("gt.i32");
+                    self.binary_op(|a: i32, b| a > b);
+                }
+                ops::GE_I32 => {
+                    trace!("ge.i32");
+                    self.binary_op(|a: i32, b| a >= b);
+                }
+                ops::NE_I64 => {
+                    trace!("ne.i64");
+                    self.binary_op(|a: i64, b| a != b);
+                }
+                ops::LT_I64 => {
+                    trace!("lt.i64");
+                    self.binary_op(|a: i64, b| a < b);
+                }
+                ops::LE_I64 => {
+                    trace!("le.i64");
+                    self.binary_op(|a: i64, b| a <= b);
+                }
+                ops::GT_I64 => {
+                    trace!("gt.i64");
+                    self.binary_op(|a: i64, b| a > b);
+                }
+                ops::GE_I64 => {
+                    trace!("ge.i64");
+                    self.binary_op(|a: i64, b| a >= b);
+                }
+                ops::NE_F32 => {
+                    trace!("ne.f32");
+                    self.binary_op(|a: f32, b| a != b);
+                }
+                ops::LT_F32 => {
+                    trace!("lt.f32");
+                    self.binary_op(|a: f32, b| a < b);
+                }
+                ops::LE_F32 => {
+                    trace!("le.f32");
+                    self.binary_op(|a: f32, b| a <= b);
+                }
+                ops::GT_F32 => {
+                    trace!("gt.f32");
+                    self.binary_op(|a: f32, b| a > b);
+                }
+                ops::GE_F32 => {
+                    trace!("ge.f32");
+                    self.binary_op(|a: f32, b| a >= b);
+                }
+                ops::NE_F64 => {
+                    trace!("ne.f64");
+                    self.binary_op(|a: f64, b| a != b);
+                }
+                ops::LT_F64 => {
+                    trace!("lt.f64");
+                    self.binary_op(|a: f64, b| a < b);
+                }
+                ops::LE_F64 => {
+                    trace!("le.f64");
+                    self.binary_op(|a: f64, b| a <= b);
+                }
+                ops::GT_F64 => {
+                    trace!("gt.f64");
+                    self.binary_op(|a: f64, b| a > b);
+                }
+                ops::GE_F64 => {
+                    trace!("ge.f64");
+                    self.binary_op(|a: f64, b| a >= b);
+                }
+                ops::EQ_BOOL => {
+                    trace!("eq.bool");
+                    self.binary_op(|a: bool, b| a == b);
+                }
+                ops::NE_BOOL => {
+                    trace!("ne.bool");
+                    self.binary_op(|a: bool, b| a != b);
+                }
+                ops::NOT_BOOL => {
+                    trace!("not.bool");
+                    self.unary_op(|a: bool| !a);
                 }
                 ops::PUSH_CONST => {
-                    // TODO: constant table
-                    let konst_idx = decode_arg_k(instruction);
-                    println!(".pushk {}", konst_idx);
-                    self.ip += 1
+                    let konst_idx = decode_arg_k(instruction) as usize;
+                    trace!("pushk.i32 {konst_idx}");
+                    match self.load_constant(chunk, konst_idx) {
+                        Some(bits) => {
+                            self.stack.push(Value::I32(bits as i32));
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("constant {konst_idx} not found")),
+                    }
+                }
+                ops::PUSH_CONST_I64 => {
+                    let konst_idx = decode_arg_k(instruction) as usize;
+                    trace!("pushk.i64 {konst_idx}");
+                    match self.load_constant2(chunk, konst_idx) {
+                        Some(bits) => {
+                            self.stack.push(Value::I64(bits as i64));
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("constant {konst_idx} not found")),
+                    }
+                }
+                ops::PUSH_CONST_F32 => {
+                    let konst_idx = decode_arg_k(instruction) as usize;
+                    trace!("pushk.f32 {konst_idx}");
+                    match self.load_constant(chunk, konst_idx) {
+                        Some(bits) => {
+                            self.stack.push(Value::F32(f32::from_bits(bits)));
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("constant {konst_idx} not found")),
+                    }
+                }
+                ops::PUSH_CONST_F64 => {
+                    let konst_idx = decode_arg_k(instruction) as usize;
+                    trace!("pushk.f64 {konst_idx}");
+                    match self.load_constant2(chunk, konst_idx) {
+                        Some(bits) => {
+                            self.stack.push(Value::F64(f64::from_bits(bits)));
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("constant {konst_idx} not found")),
+                    }
+                }
+                ops::PUSH_CONST_BOOL => {
+                    let konst_idx = decode_arg_k(instruction) as usize;
+                    trace!("pushk.bool {konst_idx}");
+                    match self.load_constant(chunk, konst_idx) {
+                        Some(bits) => {
+                            self.stack.push(Value::Bool(bits != 0));
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("constant {konst_idx} not found")),
+                    }
                 }
                 ops::PUSH_CONST_IMM => {
                     let konst = decode_arg_a(instruction);
-                    println!("push.i32.im {}", konst);
-                    self.stack.push(konst as u32);
+                    trace!("push.i32.im {}", konst);
+                    self.stack.push(Value::I32(konst));
+                    self.ip += 1;
+                }
+                ops::PUSH_FUNC => {
+                    let func_id = decode_arg_k(instruction);
+                    trace!("push.func {func_id}");
+                    self.stack.push(Value::Func(func_id));
+                    self.ip += 1;
+                }
+                ops::CLOSURE => {
+                    let func_id = decode_arg_k(instruction);
+                    trace!("closure {func_id}");
+                    self.stack.push(Value::Closure(Rc::new(Closure::new(func_id))));
+                    self.ip += 1;
+                }
+                ops::CAPTURE_LOCAL | ops::CAPTURE_UPVALUE => {
+                    let index = decode_arg_k(instruction) as usize;
+                    let upvalue = if op == ops::CAPTURE_LOCAL {
+                        trace!("capture.local {index}");
+                        let base = self.calls.last().map(|frame| frame.base).unwrap_or_default();
+                        Some(self.capture_upvalue(base + index))
+                    } else {
+                        trace!("capture.upvalue {index}");
+                        self.upvalue(index)
+                    };
+
+                    // The closure was just created, so nothing else refers to it yet.
+                    match (self.stack.last_mut(), upvalue) {
+                        (Some(Value::Closure(closure)), Some(upvalue)) => match Rc::get_mut(closure) {
+                            Some(closure) => {
+                                closure.upvalues.push(upvalue);
+                                self.ip += 1;
+                            }
+                            None => self.set_error("cannot capture variable into a shared closure"),
+                        },
+                        (Some(Value::Closure(_)), None) => {
+                            self.set_error(format!("failed to find upvalue {index} of current closure"))
+                        }
+                        (Some(value), _) => {
+                            let message = format!("expected value of type closure, found {}", value.type_name());
+                            self.set_error(message)
+                        }
+                        (None, _) => self.set_error("stack underflow when capturing variable"),
+                    }
+                }
+                ops::PUSH_STR => {
+                    let string_idx = decode_arg_k(instruction);
+                    trace!("push.str {string_idx}");
+                    let string = self
+                        .calls
+                        .last()
+                        .and_then(|frame| chunk.func_by_id(frame.func_id))
+                        .and_then(|func| func.strings.get(string_idx as usize));
+                    match string {
+                        Some(string) => {
+                            self.stack.push(Value::Str(string.clone()));
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("string constant {string_idx} not found")),
+                    }
+                }
+                ops::TO_STR => {
+                    trace!("to.str");
+                    match self.stack.pop() {
+                        Some(Value::Str(string)) => self.stack.push(Value::Str(string)),
+                        Some(value) => self.stack.push(Value::Str(value.to_string().into())),
+                        None => self.set_error("stack underflow"),
+                    }
+                    self.ip += 1;
+                }
+                ops::CONCAT_STR => {
+                    trace!("concat.str");
+                    let b = self.pop_str();
+                    let a = self.pop_str();
+                    let mut string = String::with_capacity(a.len() + b.len());
+                    string.push_str(&a);
+                    string.push_str(&b);
+                    self.stack.push(Value::Str(string.into()));
                     self.ip += 1;
                 }
                 ops::LOAD_LOCAL => {
                     let local_id = decode_arg_k(instruction);
-                    println!("load.local {}", local_id);
+                    trace!("load.local {}", local_id);
                     // FIXME: Top call frame should be infallible to avoid this failure case
                     match self.calls.last() {
                         // Because the VM is stack based, the function's local variables
                         // are already on the operand stack.
                         Some(frame) => {
                             let stack_offset = frame.base + local_id as usize;
-                            self.stack.push(self.stack[stack_offset]);
+                            self.stack.push(self.stack[stack_offset].clone());
                             self.ip += 1;
                         }
                         None => {
@@ -250,12 +672,12 @@ impl Fiber {
                 }
                 ops::STORE_LOCAL => {
                     let local_id = decode_arg_k(instruction);
-                    println!("store.local {local_id}");
+                    trace!("store.local {local_id}");
                     // FIXME: Top call frame should be infallible to avoid this failure case
                     match self.calls.last() {
                         Some(frame) => {
                             let stack_offset = frame.base + local_id as usize;
-                            self.stack[stack_offset] = self.stack.pop().unwrap_or(0);
+                            self.stack[stack_offset] = self.stack.pop().unwrap_or_default();
                             self.ip += 1;
                         }
                         None => {
@@ -263,57 +685,219 @@ impl Fiber {
                         }
                     }
                 }
+                ops::LOAD_UPVALUE => {
+                    let index = decode_arg_k(instruction);
+                    trace!("load.upvalue {index}");
+                    match self.upvalue(index as usize) {
+                        Some(upvalue) => {
+                            let value = match &*upvalue.borrow() {
+                                Upvalue::Open(slot) => self.stack[*slot].clone(),
+                                Upvalue::Closed(value) => value.clone(),
+                            };
+                            self.stack.push(value);
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("failed to find upvalue {index} of current closure")),
+                    }
+                }
+                ops::STORE_UPVALUE => {
+                    let index = decode_arg_k(instruction);
+                    trace!("store.upvalue {index}");
+                    match self.upvalue(index as usize) {
+                        Some(upvalue) => {
+                            let value = self.stack.pop().unwrap_or_default();
+                            match &mut *upvalue.borrow_mut() {
+                                Upvalue::Open(slot) => self.stack[*slot] = value,
+                                Upvalue::Closed(closed) => *closed = value,
+                            }
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("failed to find upvalue {index} of current closure")),
+                    }
+                }
+                ops::CLOSE_UPVALUE => {
+                    let local_id = decode_arg_k(instruction);
+                    trace!("close.upvalue {local_id}");
+                    let base = self.calls.last().map(|frame| frame.base).unwrap_or_default();
+                    self.close_upvalues(base + local_id as usize);
+                    self.ip += 1;
+                }
                 ops::FUNC => {
-                    println!(".function");
+                    trace!(".function");
                     self.ip += 2; // skip constant table
                 }
                 ops::SKIP_1 => {
-                    println!("skip.i32.1");
-                    let a = self.stack.pop().unwrap_or_default() as i32;
-                    if a == 1 {
+                    trace!("skip.true");
+                    let a = self.pop::<bool>();
+                    if a {
                         self.ip += 2
                     } else {
                         self.ip += 1
                     }
                 }
                 ops::SKIP_EQ_I32 => {
-                    println!("skip.eq.i32");
-                    let b = self.stack.pop().unwrap_or_default() as i32;
-                    let a = self.stack.pop().unwrap_or_default() as i32;
+                    trace!("skip.eq.i32");
+                    let b = self.pop_i32();
+                    let a = self.pop_i32();
                     if a == b {
                         self.ip += 2
                     } else {
                         self.ip += 1
                     }
                 }
+                ops::RANGE_NEXT_I32 | ops::RANGE_NEXT_INCL_I32 => {
+                    let local_id = decode_arg_k(instruction);
+                    trace!("range.next {local_id}");
+                    // Range state is kept in two consecutive local slots,
+                    // the counter followed by the end bound.
+                    match self.calls.last() {
+                        Some(frame) => {
+                            let stack_offset = frame.base + local_id as usize;
+                            match (&self.stack[stack_offset], &self.stack[stack_offset + 1]) {
+                                (Value::I32(counter), Value::I32(end)) => {
+                                    let (counter, end) = (*counter, *end);
+                                    let has_next = if op == ops::RANGE_NEXT_I32 {
+                                        (counter as i64) + 1 < end as i64
+                                    } else {
+                                        counter < end
+                                    };
+
+                                    // Fall through to the jump back to the loop body,
+                                    // or skip it when the range is exhausted.
+                                    if has_next {
+                                        self.stack[stack_offset] = Value::I32(counter + 1);
+                                        self.ip += 1
+                                    } else {
+                                        self.ip += 2
+                                    }
+                                }
+                                _ => self.set_error("expected range of i32"),
+                            }
+                        }
+                        None => {
+                            self.set_error("range iteration but no frame on call stack");
+                        }
+                    }
+                }
+                ops::NEW_OBJ => {
+                    let type_id = decode_arg_k(instruction);
+                    trace!("new.obj {type_id}");
+                    match types.get(type_id as usize) {
+                        Some(obj_info) => {
+                            let obj = Obj::new(obj_info.clone());
+                            self.stack.push(Value::Obj(Rc::new(RefCell::new(obj))));
+                            self.ip += 1;
+                        }
+                        None => self.set_error(format!("failed to find type for id {type_id}")),
+                    }
+                }
+                ops::GET_FIELD => {
+                    let (type_id, field_index) = decode_field(decode_arg_k(instruction));
+                    trace!("get.field {field_index}");
+                    let field = chunk
+                        .types()
+                        .get(type_id as usize)
+                        .and_then(|type_def| type_def.fields.get(field_index as usize));
+                    match (self.stack.pop(), field) {
+                        (Some(Value::Obj(obj)), Some(field)) => {
+                            match field_value(&obj.borrow(), field_index as usize, field.ty) {
+                                Ok(value) => {
+                                    self.stack.push(value);
+                                    self.ip += 1;
+                                }
+                                Err(err) => {
+                                    self.set_error(format!("cannot get object field at index {field_index}: {err}"))
+                                }
+                            }
+                        }
+                        (Some(Value::Obj(_)), None) => {
+                            self.set_error(format!("failed to find field {field_index} of type for id {type_id}"))
+                        }
+                        (Some(value), _) => {
+                            self.set_error(format!("expected value of type object, found {}", value.type_name()))
+                        }
+                        (None, _) => self.set_error("stack underflow when getting field"),
+                    }
+                }
+                ops::SET_FIELD => {
+                    let (_, field_index) = decode_field(decode_arg_k(instruction));
+                    trace!("set.field {field_index}");
+                    let value = self.stack.pop().unwrap_or_default();
+                    // The object is left on the stack, so more fields can be set.
+                    match self.stack.last() {
+                        Some(Value::Obj(obj)) => {
+                            let type_name = value.type_name();
+                            let result = set_field_value(&mut obj.borrow_mut(), field_index as usize, value);
+                            match result {
+                                Ok(()) => self.ip += 1,
+                                Err(err) => self.set_error(format!(
+                                    "cannot store value of type {type_name} in object field at index {field_index}: {err}"
+                                )),
+                            }
+                        }
+                        Some(value) => {
+                            let message = format!("expected value of type object, found {}", value.type_name());
+                            self.set_error(message)
+                        }
+                        None => self.set_error("stack underflow when setting field"),
+                    }
+                }
+                ops::TO_IFACE => {
+                    let vtable = decode_arg_k(instruction);
+                    trace!("to.iface {vtable}");
+                    match self.stack.pop() {
+                        Some(Value::Obj(obj)) => {
+                            self.stack.push(Value::Iface { obj, vtable });
+                            self.ip += 1;
+                        }
+                        Some(value) => {
+                            self.set_error(format!("expected value of type object, found {}", value.type_name()))
+                        }
+                        None => self.set_error("stack underflow when converting to interface"),
+                    }
+                }
                 ops::CALL => {
                     let func_id = decode_arg_k(instruction);
-                    println!("call {func_id}");
-                    self.call_func(chunk, func_id);
+                    trace!("call {func_id}");
+                    self.call_func(chunk, func_id, None);
+                }
+                ops::DYN_CALL => {
+                    let (arity, slot) = decode_dyn_call(decode_arg_k(instruction));
+                    trace!("dyn.call {slot} {arity}");
+                    self.call_dyn(chunk, arity as usize, slot as usize);
                 }
                 ops::RETURN => {
                     let n = decode_arg_k(instruction);
-                    println!("return {n}");
+                    trace!("return {n}");
                     match self.calls.pop() {
                         Some(frame) => {
                             // TODO: Multiple return values
-                            let result = self.stack.pop().unwrap_or_default();
+                            let result = if n > 0 {
+                                self.stack.pop().unwrap_or_default()
+                            } else {
+                                Value::Nil
+                            };
+
+                            // Locals captured by closures outlive the stack that belongs to the
+                            // current function, so they're moved into their upvalues first.
+                            self.close_upvalues(frame.base);
 
                             // Truncate the stack that belongs to the current function.
                             self.stack.truncate(frame.base);
 
+                            trace!("return to 0x{:06X}", frame.return_addr);
+                            trace!("  base: {}", frame.base);
+                            trace!("  result: {result}");
+
                             // Put the result back onto the stack for the caller function.
                             self.stack.push(result);
 
-                            println!("return to 0x{:06X}", frame.return_addr);
-                            println!("  base: {}", frame.base);
-                            println!("  result: {result}");
-                            println!("  stack: {:?}", self.stack);
+                            trace!("  stack: {:?}", self.stack);
                             self.ip = frame.return_addr;
                         }
                         None => {
                             // abort
-                            println!(".abort");
+                            trace!(".abort");
                             self.done = true;
                             break 'eval;
                         }
@@ -321,16 +905,16 @@ impl Fiber {
                 }
                 ops::JUMP => {
                     let addr = decode_arg_k(instruction);
-                    println!("jump 0x{:X}", addr * 4);
+                    trace!("jump 0x{:X}", addr * 4);
                     self.ip = addr as usize;
                 }
                 ops::ABORT => {
-                    println!("abort");
+                    trace!("abort");
                     self.done = true;
                     break 'eval;
                 }
                 _ => {
-                    println!("abort");
+                    trace!("abort");
                     self.done = true;
                     break 'eval;
                 }
@@ -339,22 +923,25 @@ impl Fiber {
     }
 
     #[inline(always)]
-    fn call_func(&mut self, chunk: &Chunk, func_id: u32) {
+    fn call_func(&mut self, chunk: &Chunk, func_id: u32, closure: Option<Rc<Closure>>) {
         match chunk.func_by_id(func_id) {
             Some(func) => {
-                let mut arg_start = 0;
-                let mut local_start = 0;
                 match self.stack.len().checked_sub(func.arity as usize) {
                     Some(stack_base) => {
-                        arg_start = stack_base;
+                        trace!("call {} 0x{:X}", func_id, func.bytecode_span.0);
+                        trace!("  base:  {stack_base}");
+                        trace!("  args:  {:?}", &self.stack[stack_base..]);
 
                         // Extend stack for the function's local variable slots.
-                        self.stack.resize(self.stack.len() + func.local_count, 0);
+                        self.stack.resize(self.stack.len() + func.local_count, Value::Nil);
+                        trace!("  stack: {:?}", self.stack);
 
                         self.calls.push(FrameInfo {
+                            func_id,
                             base: stack_base,
                             // after this insrtuction
                             return_addr: self.ip + 1,
+                            closure,
                         });
                     }
                     None => self.set_error("stack underflow when attempting to set function call base"),
@@ -362,12 +949,187 @@ impl Fiber {
 
                 // jump to function bytecode
                 self.ip = func.bytecode_span.0 as usize;
-                println!("call {} 0x{:X}", func_id, func.bytecode_span.0);
-                println!("  base:  {arg_start}");
-                println!("  args:  {:?}", &self.stack[arg_start..arg_start + func.arity as usize]);
-                println!("  stack: {:?}", self.stack);
             }
-            None => self.set_error("failed to find function for id {func_id}"),
+            None => self.set_error(format!("failed to find function for id {func_id}")),
+        }
+    }
+
+    /// Call the method in a vtable slot of the receiver, which is
+    /// the interface value below the other arguments on the stack.
+    ///
+    /// The method is passed the object itself as its receiver.
+    ///
+    /// When the receiver is a function value, the function is
+    /// called instead, and removed from below its arguments.
+    fn call_dyn(&mut self, chunk: &Chunk, arity: usize, slot: usize) {
+        let index = match self.stack.len().checked_sub(arity) {
+            Some(index) if arity > 0 => index,
+            _ => {
+                self.set_error("stack underflow when attempting to find call receiver");
+                return;
+            }
+        };
+
+        let receiver = &mut self.stack[index];
+        let (func_id, closure) = match receiver {
+            Value::Func(func_id) => {
+                let func_id = *func_id;
+                self.stack.remove(index);
+                (func_id, None)
+            }
+            Value::Closure(closure) => {
+                let closure = closure.clone();
+                self.stack.remove(index);
+                (closure.func_id, Some(closure))
+            }
+            Value::Iface { obj, vtable } => {
+                let vtable = *vtable;
+                let func_id = chunk.vtable_by_id(vtable).and_then(|vtable| vtable.funcs.get(slot));
+                match func_id {
+                    Some(func_id) => {
+                        *receiver = Value::Obj(obj.clone());
+                        (func_id.to_u32(), None)
+                    }
+                    None => {
+                        self.set_error(format!("failed to find method in slot {slot} of vtable {vtable}"));
+                        return;
+                    }
+                }
+            }
+            value => {
+                let message = format!("expected value of type interface or func, found {}", value.type_name());
+                self.set_error(message);
+                return;
+            }
+        };
+
+        self.call_func(chunk, func_id, closure);
+    }
+
+    /// Find the upvalue of the closure executing in the current
+    /// call frame, which captured a variable of an enclosing function.
+    fn upvalue(&self, index: usize) -> Option<Rc<RefCell<Upvalue>>> {
+        self.calls
+            .last()
+            .and_then(|frame| frame.closure.as_ref())
+            .and_then(|closure| closure.upvalues.get(index))
+            .cloned()
+    }
+
+    /// Find the open upvalue of the local in the given stack slot,
+    /// creating it when the local wasn't captured before.
+    ///
+    /// Closures capturing the same local share its upvalue.
+    fn capture_upvalue(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let position = self
+            .open_upvalues
+            .binary_search_by_key(&slot, |upvalue| match &*upvalue.borrow() {
+                Upvalue::Open(slot) => *slot,
+                Upvalue::Closed(_) => unreachable!("closed upvalues are removed from the open list"),
+            });
+
+        match position {
+            Ok(position) => self.open_upvalues[position].clone(),
+            Err(position) => {
+                let upvalue = Rc::new(RefCell::new(Upvalue::Open(slot)));
+                self.open_upvalues.insert(position, upvalue.clone());
+                upvalue
+            }
+        }
+    }
+
+    /// Close the open upvalues of the locals in the given stack slot
+    /// and above, by moving the values of the locals into them.
+    fn close_upvalues(&mut self, first: usize) {
+        let position = self.open_upvalues.partition_point(|upvalue| match &*upvalue.borrow() {
+            Upvalue::Open(slot) => *slot < first,
+            Upvalue::Closed(_) => unreachable!("closed upvalues are removed from the open list"),
+        });
+
+        for upvalue in self.open_upvalues.drain(position..) {
+            let mut upvalue = upvalue.borrow_mut();
+            if let Upvalue::Open(slot) = *upvalue {
+                *upvalue = Upvalue::Closed(self.stack[slot].clone());
+            }
+        }
+    }
+
+    /// Look up a slot in the constant table of the function
+    /// executing in the current call frame.
+    fn load_constant(&self, chunk: &Chunk, index: usize) -> Option<u32> {
+        self.calls
+            .last()
+            .and_then(|frame| chunk.func_by_id(frame.func_id))
+            .and_then(|func| func.constants.get(index).copied())
+    }
+
+    /// Look up a 64-bit constant, which takes up two consecutive slots.
+    fn load_constant2(&self, chunk: &Chunk, index: usize) -> Option<u64> {
+        let low = self.load_constant(chunk, index)?;
+        let high = self.load_constant(chunk, index + 1)?;
+        Some(decode_u64([low, high]))
+    }
+
+    /// Pop the top of the operand stack as a primitive value.
+    ///
+    /// A value of the wrong type puts the fiber into an error state,
+    /// which stops evaluation before the next instruction.
+    #[inline(always)]
+    fn pop<T: FromValue + Default>(&mut self) -> T {
+        match self.stack.pop() {
+            Some(value) => match T::from_value(&value) {
+                Some(value) => value,
+                None => {
+                    self.set_error(format!(
+                        "expected value of type {}, found {}",
+                        T::TYPE_NAME,
+                        value.type_name()
+                    ));
+                    T::default()
+                }
+            },
+            None => T::default(),
+        }
+    }
+
+    #[inline(always)]
+    fn pop_i32(&mut self) -> i32 {
+        self.pop::<i32>()
+    }
+
+    /// Replace the top operand with the result of the given operation.
+    #[inline(always)]
+    fn unary_op<T, R>(&mut self, op: impl FnOnce(T) -> R)
+    where
+        T: FromValue + Default,
+        R: Into<Value>,
+    {
+        let a = self.pop::<T>();
+        self.stack.push(op(a).into());
+        self.ip += 1;
+    }
+
+    /// Replace the top two operands with the result of the given operation.
+    #[inline(always)]
+    fn binary_op<T, R>(&mut self, op: impl FnOnce(T, T) -> R)
+    where
+        T: FromValue + Default,
+        R: Into<Value>,
+    {
+        let b = self.pop::<T>();
+        let a = self.pop::<T>();
+        self.stack.push(op(a, b).into());
+        self.ip += 1;
+    }
+
+    fn pop_str(&mut self) -> Rc<str> {
+        match self.stack.pop() {
+            Some(Value::Str(value)) => value,
+            Some(value) => {
+                self.set_error(format!("expected value of type str, found {}", value.type_name()));
+                "".into()
+            }
+            None => "".into(),
         }
     }
 
@@ -375,12 +1137,21 @@ impl Fiber {
     /// for later retrieval. See [`Self::error()`]
     #[cold]
     fn set_error<S: ToString>(&mut self, message: S) {
-        self.error = Some(message.to_string())
+        self.error = Some(message.to_string());
+        self.error_addr = self.ip;
     }
 
     /// Retrieve the fiber's current error, if any.
     pub fn error(&self) -> Option<&str> {
-        self.error.as_ref().map(|s| s.as_str())
+        self.error.as_deref()
+    }
+
+    /// Bytecode address of the instruction that caused the fiber's
+    /// current error, if any.
+    ///
+    /// See [`Chunk::source_span`] to find the position in the source code.
+    pub fn error_addr(&self) -> Option<usize> {
+        self.error.as_ref().map(|_| self.error_addr)
     }
 
     /// Checks whether the fiber is in an error state.
@@ -395,8 +1166,4 @@ impl Fiber {
     fn complete(&mut self) {
         self.done = true
     }
-
-    fn print_ip(&self) {
-        print!("0x{:08X}  ", self.ip * 4);
-    }
 }
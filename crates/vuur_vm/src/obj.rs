@@ -2,16 +2,25 @@
 //!
 //! References:
 //! - https://en.wikipedia.org/wiki/Data_structure_alignment
-use std::{alloc::Layout, cmp::Reverse, fmt, rc::Rc};
+use std::{alloc::Layout, cell::RefCell, cmp::Reverse, fmt, rc::Rc};
 
 // TODO: ObjInfo identity to detect cycling types
 
 // ----------------------------------------------------------------------------
 // Object Value
 
+/// Unit of an object's data storage. Its alignment is the largest of any field kind,
+/// so fields laid out at aligned offsets are aligned in memory too.
+type Word = u64;
+
 // TODO: Reference counting is temporary solution for prototype. Replace with less overhead.
 pub struct Obj {
-    data: Box<[u8]>,
+    data: Box<[Word]>,
+    /// Slots for reference fields, which point to objects stored out-of-line.
+    ///
+    /// Includes the reference fields of inline nested objects.
+    // TODO: Cycles between references leak.
+    refs: Box<[Option<Rc<RefCell<Obj>>>]>,
     ty: Rc<ObjInfo>,
 }
 
@@ -21,62 +30,330 @@ impl Obj {
 
         Self {
             data: Self::make_storage(ty.as_ref()),
+            refs: (0..ty.ref_count).map(|_| None).collect(),
             ty,
         }
     }
 
-    fn make_storage(ty: &ObjInfo) -> Box<[u8]> {
-        (0..ty.size).map(|_| 0).collect::<Vec<u8>>().into_boxed_slice()
+    /// Type information of the object.
+    #[inline]
+    pub fn info(&self) -> &ObjInfo {
+        &self.ty
     }
 
-    pub fn field<T: AsField>(&self, index: usize) -> Option<&T> {
-        let field_info = self.ty.layout.get(index)?;
-        let kind = T::kind();
-        if field_info.kind == kind {
-            let [start, end] = field_info.range();
-            let data = &self.data[start..end];
-            debug_assert_eq!(
-                data.len(),
-                field_info.kind.size() as usize,
-                "data slice must be the same size as the underlying field type"
-            );
+    fn make_storage(ty: &ObjInfo) -> Box<[Word]> {
+        debug_assert!(
+            ty.align <= std::mem::align_of::<Word>(),
+            "object alignment exceeds the alignment of its storage"
+        );
+        vec![0; ty.size.div_ceil(std::mem::size_of::<Word>())].into_boxed_slice()
+    }
 
-            Some(T::from_slice(data))
-        } else {
-            None
+    /// Object data as bytes, which includes any padding after the last field.
+    #[inline]
+    fn bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.data)
+    }
+
+    pub fn field<T: AsField>(&self, index: usize) -> Result<&T, ObjError> {
+        self.field_at(&[index])
+    }
+
+    pub fn field_mut<T: AsField>(&mut self, index: usize) -> Result<&mut T, ObjError> {
+        self.field_at_mut(&[index])
+    }
+
+    /// Field reached by following a path of field indices through inline nested objects.
+    ///
+    /// For example, `[1, 0]` is `transform.position.x` when `position` is the second
+    /// field of `transform`. The value is borrowed directly from the object's data,
+    /// so reading through nested objects doesn't copy them.
+    pub fn field_at<T: AsField>(&self, path: &[usize]) -> Result<&T, ObjError> {
+        let pos = self.ty.resolve_path(path)?;
+        pos.expect_kind(T::kind())?;
+
+        let [start, end] = pos.range();
+        let data = &self.bytes()[start..end];
+        debug_assert_eq!(
+            data.len(),
+            pos.field.kind.size(),
+            "data slice must be the same size as the underlying field type"
+        );
+
+        T::from_slice(data).ok_or_else(|| pos.misaligned())
+    }
+
+    pub fn field_at_mut<T: AsField>(&mut self, path: &[usize]) -> Result<&mut T, ObjError> {
+        let pos = self.ty.resolve_path(path)?;
+        pos.expect_kind(T::kind())?;
+
+        let [start, end] = pos.range();
+        let data = &mut bytemuck::cast_slice_mut::<Word, u8>(&mut self.data)[start..end];
+        debug_assert_eq!(
+            data.len(),
+            pos.field.kind.size(),
+            "data slice must be the same size as the underlying field type"
+        );
+
+        T::from_slice_mut(data).ok_or_else(|| pos.misaligned())
+    }
+
+    pub fn field_by_name<T: AsField>(&self, name: &str) -> Result<&T, ObjError> {
+        self.field(self.ty.expect_field_index(name)?)
+    }
+
+    pub fn field_by_name_mut<T: AsField>(&mut self, name: &str) -> Result<&mut T, ObjError> {
+        self.field_mut(self.ty.expect_field_index(name)?)
+    }
+
+    pub fn set_field<T: AsField>(&mut self, field_index: usize, value: T) -> Result<(), ObjError> {
+        self.set_field_at(&[field_index], value)
+    }
+
+    pub fn set_field_at<T: AsField>(&mut self, path: &[usize], value: T) -> Result<(), ObjError> {
+        *self.field_at_mut::<T>(path)? = value;
+        Ok(())
+    }
+
+    /// Object pointed to by the reference field at the given index.
+    ///
+    /// Returns `None` when the reference is not set.
+    pub fn get_ref(&self, index: usize) -> Result<Option<&Rc<RefCell<Obj>>>, ObjError> {
+        self.get_ref_at(&[index])
+    }
+
+    pub fn get_ref_at(&self, path: &[usize]) -> Result<Option<&Rc<RefCell<Obj>>>, ObjError> {
+        let pos = self.ty.resolve_path(path)?;
+        pos.expect_kind(FieldKind::Ref)?;
+        Ok(self.refs[pos.ref_slot].as_ref())
+    }
+
+    pub fn set_ref(&mut self, index: usize, obj: Option<Rc<RefCell<Obj>>>) -> Result<(), ObjError> {
+        self.set_ref_at(&[index], obj)
+    }
+
+    pub fn set_ref_at(&mut self, path: &[usize], obj: Option<Rc<RefCell<Obj>>>) -> Result<(), ObjError> {
+        let pos = self.ty.resolve_path(path)?;
+        pos.expect_kind(FieldKind::Ref)?;
+        self.refs[pos.ref_slot] = obj;
+        Ok(())
+    }
+}
+
+/// Objects are equal when they're of the same type,
+/// their fields hold the same bytes, and their
+/// references point to the same objects.
+impl PartialEq for Obj {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.ty, &other.ty)
+            && self.data == other.data
+            && self.refs.iter().zip(other.refs.iter()).all(|pair| match pair {
+                (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            })
+    }
+}
+
+/// Dumps every field value, for example `Transform { position: Vec3 { x: 1, y: 2, z: 3 } }`.
+///
+/// References are printed as addresses, because
+/// following them could recurse through a cycle.
+impl fmt::Debug for Obj {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&ObjView::new(self), f)
+    }
+}
+
+impl fmt::Display for Obj {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&ObjView::new(self), f)
+    }
+}
+
+/// Formatting view of an object, or an object nested inside it.
+struct ObjView<'a> {
+    obj: &'a Obj,
+    ty: &'a ObjInfo,
+    offset: usize,
+    ref_slot: usize,
+}
+
+/// Formatting view of a single field's value.
+struct FieldView<'a> {
+    obj: &'a Obj,
+    field: &'a FieldInfo,
+    offset: usize,
+    ref_slot: usize,
+}
+
+impl<'a> ObjView<'a> {
+    fn new(obj: &'a Obj) -> Self {
+        Self {
+            obj,
+            ty: &obj.ty,
+            offset: 0,
+            ref_slot: 0,
         }
     }
 
-    pub fn field_mut<T: AsField>(&mut self, index: usize) -> Option<&mut T> {
-        let field_info = self.ty.layout.get(index)?;
-        let kind = T::kind();
-        if field_info.kind == kind {
-            let [start, end] = field_info.range();
-            let data = &mut self.data[start..end];
-            debug_assert_eq!(
-                data.len(),
-                field_info.kind.size() as usize,
-                "data slice must be the same size as the underlying field type"
-            );
+    fn fields(&self) -> impl Iterator<Item = FieldView<'a>> + '_ {
+        self.ty.layout.iter().map(|field| FieldView {
+            obj: self.obj,
+            field,
+            offset: self.offset + field.offset as usize,
+            ref_slot: self.ref_slot + field.ref_slot as usize,
+        })
+    }
+}
 
-            Some(T::from_slice_mut(data))
-        } else {
-            None
+impl FieldView<'_> {
+    fn read<T: AsField + Copy>(&self) -> Result<T, ObjError> {
+        let data = &self.obj.bytes()[self.offset..self.offset + self.field.size()];
+        T::from_slice(data).copied().ok_or(ObjError::Misaligned {
+            offset: self.offset,
+            align: self.field.kind.align(),
+        })
+    }
+
+    fn fmt_value(&self, f: &mut fmt::Formatter<'_>, debug: bool) -> fmt::Result {
+        fn write<T: fmt::Debug + fmt::Display>(
+            value: Result<T, ObjError>,
+            f: &mut fmt::Formatter<'_>,
+            debug: bool,
+        ) -> fmt::Result {
+            match value {
+                Ok(value) if debug => fmt::Debug::fmt(&value, f),
+                Ok(value) => fmt::Display::fmt(&value, f),
+                Err(err) => write!(f, "<{err}>"),
+            }
+        }
+
+        match self.field.kind {
+            FieldKind::Bool => write(self.read::<bool>(), f, debug),
+            FieldKind::U8 => write(self.read::<u8>(), f, debug),
+            FieldKind::U16 => write(self.read::<u16>(), f, debug),
+            FieldKind::U32 => write(self.read::<u32>(), f, debug),
+            FieldKind::U64 => write(self.read::<u64>(), f, debug),
+            FieldKind::USize => write(self.read::<usize>(), f, debug),
+            FieldKind::I8 => write(self.read::<i8>(), f, debug),
+            FieldKind::I16 => write(self.read::<i16>(), f, debug),
+            FieldKind::I32 => write(self.read::<i32>(), f, debug),
+            FieldKind::I64 => write(self.read::<i64>(), f, debug),
+            FieldKind::ISize => write(self.read::<isize>(), f, debug),
+            FieldKind::F32 => write(self.read::<f32>(), f, debug),
+            FieldKind::F64 => write(self.read::<f64>(), f, debug),
+            FieldKind::Obj => {
+                let view = ObjView {
+                    obj: self.obj,
+                    ty: self.field.nested.as_deref().expect("nested object field without type"),
+                    offset: self.offset,
+                    ref_slot: self.ref_slot,
+                };
+                if debug {
+                    fmt::Debug::fmt(&view, f)
+                } else {
+                    fmt::Display::fmt(&view, f)
+                }
+            }
+            FieldKind::Ref => match &self.obj.refs[self.ref_slot] {
+                Some(obj) => {
+                    let ptr = Rc::as_ptr(obj);
+                    match obj.try_borrow() {
+                        Ok(obj) => write!(f, "{}<{ptr:?}>", obj.ty.name),
+                        Err(_) => write!(f, "<{ptr:?}>"),
+                    }
+                }
+                None => write!(f, "null"),
+            },
         }
     }
+}
 
-    /// TODO: Errors
-    pub fn set_field<T: AsField>(&mut self, field_index: usize, value: T) {
-        let field: &mut T = self.field_mut(field_index).expect("invalid field");
-        *field = value;
+impl fmt::Debug for ObjView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct(&self.ty.name);
+        for field in self.fields() {
+            debug.field(&field.field.name, &field);
+        }
+        debug.finish()
     }
 }
 
-impl fmt::Display for Obj {
+impl fmt::Display for ObjView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self.ty.name)?;
+        for (index, field) in self.fields().enumerate() {
+            let sep = if index == 0 { " " } else { ", " };
+            write!(f, "{sep}{}: {field}", field.field.name)?;
+        }
+        write!(f, " }}")
+    }
+}
+
+impl fmt::Debug for FieldView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_value(f, true)
+    }
+}
+
+impl fmt::Display for FieldView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_value(f, false)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Errors
+
+/// Error accessing the fields of an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjError {
+    /// Field index is past the last field of the type.
+    OutOfRange { index: usize, len: usize },
+    /// Type has no field with the given name.
+    UnknownField(String),
+    /// Field was accessed as a different kind than it was declared.
+    TypeMismatch {
+        /// Kind of the field in the type's layout.
+        expected: FieldKind,
+        /// Kind the field was accessed as.
+        actual: FieldKind,
+    },
+    /// Value has no field kind, so it can't be stored in the field.
+    UnsupportedValue {
+        /// Kind of the field in the type's layout.
+        expected: FieldKind,
+    },
+    /// Field data is not aligned for its kind.
+    Misaligned { offset: usize, align: usize },
+    /// Field path does not contain any field indices.
+    EmptyPath,
+}
+
+impl std::error::Error for ObjError {}
+
+impl fmt::Display for ObjError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let name = self.ty.name.as_str();
-        let ptr = self.data.as_ptr();
-        write!(f, "{name}<{ptr:?}>")
+        match self {
+            Self::OutOfRange { index, len } => {
+                write!(f, "field index {index} out of range for object with {len} fields")
+            }
+            Self::UnknownField(name) => write!(f, "object has no field named '{name}'"),
+            Self::TypeMismatch { expected, actual } => {
+                write!(f, "mismatched field kind: expected {expected:?}, found {actual:?}")
+            }
+            Self::UnsupportedValue { expected } => {
+                write!(
+                    f,
+                    "mismatched field kind: expected {expected:?}, found a value without a field kind"
+                )
+            }
+            Self::Misaligned { offset, align } => {
+                write!(f, "field at offset {offset} is not aligned to {align} bytes")
+            }
+            Self::EmptyPath => write!(f, "empty field path"),
+        }
     }
 }
 
@@ -104,18 +381,31 @@ impl ObjBuilder {
         self
     }
 
+    pub fn with_scheme(mut self, scheme: LayoutScheme) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
+
     pub fn with_field(mut self, name: &str, kind: FieldKind) -> Self {
         let name = name.to_string();
         self.fields.push(FieldDesc::Value { name, kind });
         self
     }
 
+    /// Object stored inline, as part of this object's data.
     pub fn with_nested(mut self, name: &str, obj: Rc<ObjInfo>) -> Self {
         let name = name.to_string();
         self.fields.push(FieldDesc::Nested { name, obj });
         self
     }
 
+    /// Reference to an object stored out-of-line.
+    pub fn with_ref(mut self, name: &str) -> Self {
+        let name = name.to_string();
+        self.fields.push(FieldDesc::Ref { name });
+        self
+    }
+
     pub fn build(self) -> ObjInfo {
         let Self {
             name, scheme, fields, ..
@@ -124,23 +414,22 @@ impl ObjBuilder {
         let name = name.unwrap_or_else(|| ObjDesc::DEFAULT_NAME.to_string());
         let scheme = scheme.unwrap_or(ObjDesc::DEFAULT_SCHEME);
 
-        let kinds = fields
-            .iter()
-            .map(|f| {
-                if let FieldDesc::Value { kind, .. } = f {
-                    *kind
-                } else {
-                    todo!("nested objects")
-                }
+        let specs = fields
+            .into_iter()
+            .map(|f| match f {
+                FieldDesc::Value { name, kind } => FieldSpec::value(kind).named(name),
+                FieldDesc::Nested { name, obj } => FieldSpec::nested(obj).named(name),
+                FieldDesc::Ref { name } => FieldSpec::value(FieldKind::Ref).named(name),
             })
             .collect::<Vec<_>>();
-        let (layout, size) = ObjInfo::build_layout(scheme, &kinds);
-        ObjInfo {
-            layout,
-            name,
-            size,
-            scheme,
-        }
+
+        ObjInfo::from_specs(name, scheme, &specs)
+    }
+}
+
+impl Default for ObjBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -161,6 +450,7 @@ pub struct ObjDesc {
 pub enum FieldDesc {
     Value { name: String, kind: FieldKind },
     Nested { name: String, obj: Rc<ObjInfo> },
+    Ref { name: String },
 }
 
 impl ObjDesc {
@@ -168,15 +458,74 @@ impl ObjDesc {
     pub const DEFAULT_SCHEME: LayoutScheme = LayoutScheme::C99;
 }
 
+/// Field that still has to be placed in a layout.
+#[derive(Clone)]
+struct FieldSpec {
+    name: String,
+    kind: FieldKind,
+    nested: Option<Rc<ObjInfo>>,
+}
+
+impl FieldSpec {
+    fn value(kind: FieldKind) -> Self {
+        debug_assert_ne!(
+            kind,
+            FieldKind::Obj,
+            "nested object fields must be built with their type"
+        );
+        Self {
+            name: String::new(),
+            kind,
+            nested: None,
+        }
+    }
+
+    fn nested(obj: Rc<ObjInfo>) -> Self {
+        Self {
+            name: String::new(),
+            kind: FieldKind::Obj,
+            nested: Some(obj),
+        }
+    }
+
+    fn named(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    fn size(&self) -> usize {
+        self.nested.as_ref().map(|obj| obj.size).unwrap_or_else(|| self.kind.size())
+    }
+
+    fn align(&self) -> usize {
+        self.nested.as_ref().map(|obj| obj.align).unwrap_or_else(|| self.kind.align())
+    }
+
+    /// Number of reference slots the field takes up.
+    fn ref_count(&self) -> usize {
+        match &self.nested {
+            Some(obj) => obj.ref_count,
+            None if self.kind == FieldKind::Ref => 1,
+            None => 0,
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Type Info
 
 /// Type information for object.
+#[derive(Debug)]
 pub struct ObjInfo {
+    /// Fields in declaration order.
     layout: Vec<FieldInfo>,
     name: String,
     /// Size of a value of this type in number of bytes.
     size: usize,
+    /// Alignment of a value of this type, when nested inside another object.
+    align: usize,
+    /// Number of reference slots, including those of nested objects.
+    ref_count: usize,
     scheme: LayoutScheme,
 }
 
@@ -190,13 +539,21 @@ pub enum LayoutScheme {
 
 #[derive(Debug)]
 pub struct FieldInfo {
+    name: String,
+    /// Byte offset of the field in the object's data.
     offset: u16,
+    /// Index of the field's first slot in the object's references.
+    ref_slot: u16,
     kind: FieldKind,
+    /// Type of the object stored inline, when the field is [`FieldKind::Obj`].
+    nested: Option<Rc<ObjInfo>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum FieldKind {
+    /// Boolean stored in a byte, which is either 0 or 1.
+    Bool,
     U8,
     U16,
     U32,
@@ -209,49 +566,96 @@ pub enum FieldKind {
     ISize,
     F32,
     F64,
-    // TODO: ObjInfo (Ptr?) field
+    /// Object stored inline. Its size and alignment are those of its type.
+    Obj,
+    /// Reference to an object stored out-of-line.
+    ///
+    /// References are kept beside the object's data, so they don't take up any bytes.
+    Ref,
+}
+
+/// Field location resolved through a path of nested objects.
+struct FieldPos<'a> {
+    offset: usize,
+    ref_slot: usize,
+    field: &'a FieldInfo,
+}
+
+impl FieldPos<'_> {
+    fn range(&self) -> [usize; 2] {
+        [self.offset, self.offset + self.field.size()]
+    }
+
+    fn expect_kind(&self, kind: FieldKind) -> Result<(), ObjError> {
+        if self.field.kind == kind {
+            Ok(())
+        } else {
+            Err(ObjError::TypeMismatch {
+                expected: self.field.kind,
+                actual: kind,
+            })
+        }
+    }
+
+    fn misaligned(&self) -> ObjError {
+        ObjError::Misaligned {
+            offset: self.offset,
+            align: self.field.kind.align(),
+        }
+    }
 }
 
 impl ObjInfo {
     pub fn new(scheme: LayoutScheme, fields: &[FieldKind]) -> Self {
         let name = ObjDesc::DEFAULT_NAME.to_string();
+        // Without names, fields are named by their index like tuple fields.
+        let specs = fields
+            .iter()
+            .enumerate()
+            .map(|(index, kind)| FieldSpec::value(*kind).named(index.to_string()))
+            .collect::<Vec<_>>();
+        Self::from_specs(name, scheme, &specs)
+    }
+
+    fn from_specs(name: String, scheme: LayoutScheme, fields: &[FieldSpec]) -> Self {
         let (layout, size) = Self::build_layout(scheme, fields);
+        let align = fields.iter().map(FieldSpec::align).max().unwrap_or(1);
+        let ref_count = fields.iter().map(FieldSpec::ref_count).sum();
         Self {
             layout,
             name,
             size,
+            align,
+            ref_count,
             scheme,
         }
     }
 
-    fn build_layout(scheme: LayoutScheme, fields: &[FieldKind]) -> (Vec<FieldInfo>, usize) {
-        // Descriptors need to be copied, because the layout can
-        // optionally be reordered.
-        let mut fields = fields.iter().copied().collect::<Vec<_>>();
+    fn build_layout(scheme: LayoutScheme, fields: &[FieldSpec]) -> (Vec<FieldInfo>, usize) {
+        // Fields are placed in this order, but the layout keeps
+        // declaration order so field indices are stable.
+        let mut order = (0..fields.len()).collect::<Vec<_>>();
         if scheme == LayoutScheme::Reorder {
-            Self::reorder_layout(&mut fields);
+            Self::reorder_layout(fields, &mut order);
         }
 
-        let mut layout = vec![];
+        let mut offsets = vec![0; fields.len()];
         let mut offset: usize = 0;
-        let mut max_align: usize = 0;
+        let mut max_align: usize = 1;
 
-        for kind in fields.iter().copied() {
+        for index in order {
             // Align the offset of this field.
-            let align = kind.align();
+            let align = fields[index].align();
             let padding = (align - (offset % align)) % align;
             let aligned_offset = offset + padding;
 
             // TODO: Graceful error when fields are < u16
             debug_assert!((aligned_offset as u16) < u16::MAX, "layout must fit in u16");
 
-            layout.push(FieldInfo {
-                offset: aligned_offset as u16,
-                kind,
-            });
+            offsets[index] = aligned_offset;
 
             // Possibly unaligned offset where next field starts.
-            offset = aligned_offset + kind.size();
+            offset = aligned_offset + fields[index].size();
 
             // Track the largest alignment for determing padding trail later.
             max_align = max_align.max(align);
@@ -261,13 +665,94 @@ impl ObjInfo {
         // alignment of any structure member.
         let size = offset + ((max_align - (offset % max_align)) % max_align);
 
+        let mut ref_slot = 0;
+        let layout = fields
+            .iter()
+            .zip(offsets)
+            .map(|(spec, offset)| {
+                let field_info = FieldInfo {
+                    name: spec.name.clone(),
+                    offset: offset as u16,
+                    ref_slot: ref_slot as u16,
+                    kind: spec.kind,
+                    nested: spec.nested.clone(),
+                };
+                ref_slot += spec.ref_count();
+                field_info
+            })
+            .collect();
+
         (layout, size)
     }
 
     /// Reorder the fields to optimize for space.
-    fn reorder_layout(layout: &mut [FieldKind]) {
-        // Order largest to smallest.
-        layout.sort_by_key(|kind| Reverse(kind.size()));
+    fn reorder_layout(fields: &[FieldSpec], order: &mut [usize]) {
+        // Order largest to smallest alignment. For plain values this is the
+        // same as their size, and nested objects are placed by their strictest field.
+        order.sort_by_key(|index| Reverse(fields[*index].align()));
+    }
+
+    /// Find a field by following field indices through nested objects.
+    fn resolve_path(&self, path: &[usize]) -> Result<FieldPos<'_>, ObjError> {
+        let (last, parents) = path.split_last().ok_or(ObjError::EmptyPath)?;
+        let mut ty = self;
+        let mut offset = 0;
+        let mut ref_slot = 0;
+
+        for index in parents {
+            let field = ty.expect_field(*index)?;
+            offset += field.offset as usize;
+            ref_slot += field.ref_slot as usize;
+            ty = field.nested.as_deref().ok_or(ObjError::TypeMismatch {
+                expected: field.kind,
+                actual: FieldKind::Obj,
+            })?;
+        }
+
+        let field = ty.expect_field(*last)?;
+        Ok(FieldPos {
+            offset: offset + field.offset as usize,
+            ref_slot: ref_slot + field.ref_slot as usize,
+            field,
+        })
+    }
+
+    fn expect_field(&self, index: usize) -> Result<&FieldInfo, ObjError> {
+        self.layout.get(index).ok_or(ObjError::OutOfRange {
+            index,
+            len: self.layout.len(),
+        })
+    }
+
+    fn expect_field_index(&self, name: &str) -> Result<usize, ObjError> {
+        self.field_index(name).ok_or_else(|| ObjError::UnknownField(name.to_string()))
+    }
+
+    /// Kind of value stored in the field at the given index.
+    pub fn field_kind(&self, index: usize) -> Result<FieldKind, ObjError> {
+        self.expect_field(index).map(|field_info| field_info.kind)
+    }
+
+    /// Index of the field with the given name.
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.layout.iter().position(|field_info| field_info.name == name)
+    }
+
+    /// Iterate the fields in declaration order, as name, kind and byte offset.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, FieldKind, usize)> + '_ {
+        self.layout
+            .iter()
+            .map(|field_info| (field_info.name.as_str(), field_info.kind, field_info.offset as usize))
+    }
+
+    /// Type of the object stored inline in the field at the given index.
+    pub fn nested_info(&self, index: usize) -> Option<&Rc<ObjInfo>> {
+        self.layout.get(index)?.nested.as_ref()
+    }
+
+    /// Name of the type.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
     }
 
     /// Size of a value of this type in number of bytes.
@@ -275,6 +760,11 @@ impl ObjInfo {
         self.size
     }
 
+    /// Alignment of a value of this type in number of bytes.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
     pub fn layout_scheme(&self) -> LayoutScheme {
         self.scheme
     }
@@ -283,14 +773,23 @@ impl ObjInfo {
 impl FieldInfo {
     /// Start and end indices of the field in the object's data.
     pub fn range(&self) -> [usize; 2] {
-        [self.offset as usize, self.offset as usize + self.kind.size() as usize]
+        [self.offset as usize, self.offset as usize + self.size()]
+    }
+
+    fn size(&self) -> usize {
+        self.nested.as_ref().map(|obj| obj.size).unwrap_or_else(|| self.kind.size())
     }
 }
 
 impl FieldKind {
+    /// Size of the value in the object's data.
+    ///
+    /// Nested objects and references are zero, because
+    /// their storage is determined by their type.
     #[rustfmt::skip]
     pub fn size(&self) -> usize {
         match self {
+            Self::Bool  => std::mem::size_of::<bool>(),
             Self::U8    => std::mem::size_of::<u8>(),
             Self::U16   => std::mem::size_of::<u16>(),
             Self::U32   => std::mem::size_of::<u32>(),
@@ -303,12 +802,15 @@ impl FieldKind {
             Self::ISize => std::mem::size_of::<isize>(),
             Self::F32   => std::mem::size_of::<f32>(),
             Self::F64   => std::mem::size_of::<f64>(),
+            Self::Obj   => 0,
+            Self::Ref   => 0,
         }
     }
 
     #[rustfmt::skip]
     pub fn align(&self) -> usize {
         match self {
+            Self::Bool  => std::mem::align_of::<bool>(),
             Self::U8    => std::mem::align_of::<u8>(),
             Self::U16   => std::mem::align_of::<u16>(),
             Self::U32   => std::mem::align_of::<u32>(),
@@ -321,6 +823,8 @@ impl FieldKind {
             Self::ISize => std::mem::align_of::<isize>(),
             Self::F32   => std::mem::align_of::<f32>(),
             Self::F64   => std::mem::align_of::<f64>(),
+            Self::Obj   => 1,
+            Self::Ref   => 1,
         }
     }
 
@@ -335,8 +839,9 @@ impl FieldKind {
 // TODO: macro that implements AsField for all supported field types.
 pub trait AsField {
     fn kind() -> FieldKind;
-    fn from_slice(data: &[u8]) -> &Self;
-    fn from_slice_mut(data: &mut [u8]) -> &mut Self;
+    /// Cast field data to the value, which fails when the data is misaligned.
+    fn from_slice(data: &[u8]) -> Option<&Self>;
+    fn from_slice_mut(data: &mut [u8]) -> Option<&mut Self>;
 }
 
 macro_rules! impl_as_field {
@@ -348,18 +853,36 @@ macro_rules! impl_as_field {
             }
 
             #[inline]
-            fn from_slice(data: &[u8]) -> &Self {
-                &bytemuck::cast_slice::<u8, Self>(data)[0]
+            fn from_slice(data: &[u8]) -> Option<&Self> {
+                bytemuck::try_cast_slice::<u8, Self>(data).ok()?.first()
             }
 
             #[inline]
-            fn from_slice_mut(data: &mut [u8]) -> &mut Self {
-                &mut bytemuck::cast_slice_mut::<u8, Self>(data)[0]
+            fn from_slice_mut(data: &mut [u8]) -> Option<&mut Self> {
+                bytemuck::try_cast_slice_mut::<u8, Self>(data).ok()?.first_mut()
             }
         }
     };
 }
 
+/// Booleans are checked when cast, because bytes other than 0 and 1 aren't valid.
+impl AsField for bool {
+    #[inline]
+    fn kind() -> FieldKind {
+        FieldKind::Bool
+    }
+
+    #[inline]
+    fn from_slice(data: &[u8]) -> Option<&Self> {
+        bytemuck::checked::try_cast_slice::<u8, Self>(data).ok()?.first()
+    }
+
+    #[inline]
+    fn from_slice_mut(data: &mut [u8]) -> Option<&mut Self> {
+        bytemuck::checked::try_cast_slice_mut::<u8, Self>(data).ok()?.first_mut()
+    }
+}
+
 impl_as_field!(u8, U8);
 impl_as_field!(u16, U16);
 impl_as_field!(u32, U32);
@@ -387,7 +910,7 @@ mod test {
     fn test_build_layout() {
         let (layout, size) = ObjInfo::build_layout(
             LayoutScheme::C99,
-            &[FieldKind::U8, FieldKind::U16, FieldKind::U8, FieldKind::U32],
+            &[FieldKind::U8, FieldKind::U16, FieldKind::U8, FieldKind::U32].map(FieldSpec::value),
         );
 
         assert_eq!(layout[0].offset, EXPECTED[0], "first field must be at start of object");
@@ -406,10 +929,19 @@ mod test {
 
         assert_eq!(obj_info.size, 8);
 
+        assert_eq!(obj_info.layout[3].offset, 0, "largest field must be placed first");
+
         let mut obj = Obj::new(obj_info);
-        // FIXME: Field indices must not change when reordered
-        obj.set_field::<u32>(0, 1234);
-        assert_eq!(obj.field::<u32>(0), Some(&1234));
+        obj.set_field::<u32>(3, 1234).unwrap();
+        assert_eq!(obj.field::<u32>(3), Ok(&1234));
+        assert_eq!(
+            obj.field::<u32>(0),
+            Err(ObjError::TypeMismatch {
+                expected: FieldKind::U8,
+                actual: FieldKind::U32
+            }),
+            "field indices must not change when reordered"
+        );
     }
 
     #[test]
@@ -424,23 +956,47 @@ mod test {
         let field1: &u16 = obj.field(1).unwrap();
         assert_eq!(*field1, 0);
 
-        obj.set_field(0, 7_u8);
-        obj.set_field(1, 42_u16);
-        obj.set_field(2, 11_u8);
-        obj.set_field(3, 1234_u32);
+        obj.set_field(0, 7_u8).unwrap();
+        obj.set_field(1, 42_u16).unwrap();
+        obj.set_field(2, 11_u8).unwrap();
+        obj.set_field(3, 1234_u32).unwrap();
 
-        assert_eq!(obj.field::<u8>(0), Some(&7));
-        assert_eq!(obj.field::<u16>(1), Some(&42));
-        assert_eq!(obj.field::<u8>(2), Some(&11));
-        assert_eq!(obj.field::<u32>(3), Some(&1234));
+        assert_eq!(obj.field::<u8>(0), Ok(&7));
+        assert_eq!(obj.field::<u16>(1), Ok(&42));
+        assert_eq!(obj.field::<u8>(2), Ok(&11));
+        assert_eq!(obj.field::<u32>(3), Ok(&1234));
 
         assert_eq!(
             obj.field::<u16>(5),
-            None,
-            "out-of-bounds access must gracefully return `None`"
+            Err(ObjError::OutOfRange { index: 5, len: 4 }),
+            "out-of-bounds access must gracefully return an error"
         );
     }
 
+    #[test]
+    fn test_aligned_fields() {
+        let ty = Rc::new(ObjInfo::new(
+            LayoutScheme::C99,
+            &[FieldKind::U8, FieldKind::F64, FieldKind::Bool, FieldKind::U64],
+        ));
+
+        // Many objects, so the test doesn't depend on
+        // one allocation happening to be aligned.
+        let mut objs: Vec<Obj> = (0..16).map(|_| Obj::new(ty.clone())).collect();
+
+        for (index, obj) in objs.iter_mut().enumerate() {
+            obj.set_field(1, index as f64).unwrap();
+            obj.set_field(2, true).unwrap();
+            obj.set_field(3, index as u64).unwrap();
+        }
+
+        for (index, obj) in objs.iter().enumerate() {
+            assert_eq!(obj.field::<f64>(1), Ok(&(index as f64)));
+            assert_eq!(obj.field::<bool>(2), Ok(&true));
+            assert_eq!(obj.field::<u64>(3), Ok(&(index as u64)));
+        }
+    }
+
     #[test]
     fn test_builder() {
         let obj_info = ObjBuilder::new()
@@ -456,4 +1012,231 @@ mod test {
         let obj = Obj::new(obj_info);
         println!("{obj}");
     }
+
+    fn vec3_info(scheme: LayoutScheme) -> Rc<ObjInfo> {
+        let obj_info = ObjBuilder::new()
+            .with_name("Vec3")
+            .with_scheme(scheme)
+            .with_field("x", FieldKind::F32)
+            .with_field("y", FieldKind::F32)
+            .with_field("z", FieldKind::F32)
+            .build();
+
+        Rc::new(obj_info)
+    }
+
+    #[test]
+    fn test_nested_layout() {
+        let vec3 = vec3_info(LayoutScheme::C99);
+        assert_eq!(vec3.size(), 12);
+        assert_eq!(vec3.align(), 4);
+
+        let transform = ObjBuilder::new()
+            .with_name("Transform")
+            .with_field("flag", FieldKind::U8)
+            .with_nested("position", vec3.clone())
+            .with_field("id", FieldKind::U8)
+            .with_field("scale", FieldKind::F64)
+            .build();
+
+        assert_eq!(transform.layout[0].offset, 0);
+        assert_eq!(
+            transform.layout[1].offset, 4,
+            "nested object must be aligned to its strictest field"
+        );
+        assert_eq!(transform.layout[2].offset, 16);
+        assert_eq!(transform.layout[3].offset, 24);
+        assert_eq!(transform.size(), 32);
+        assert_eq!(transform.align(), 8);
+    }
+
+    #[test]
+    fn test_nested_reorder_layout() {
+        let vec3 = vec3_info(LayoutScheme::Reorder);
+
+        let transform = ObjBuilder::new()
+            .with_name("Transform")
+            .with_scheme(LayoutScheme::Reorder)
+            .with_field("flag", FieldKind::U8)
+            .with_nested("position", vec3)
+            .with_field("id", FieldKind::U8)
+            .with_field("scale", FieldKind::F64)
+            .build();
+
+        assert_eq!(transform.layout[3].offset, 0);
+        assert_eq!(transform.layout[1].offset, 8);
+        assert_eq!(transform.layout[0].offset, 20);
+        assert_eq!(transform.layout[2].offset, 21);
+        assert_eq!(transform.size(), 24);
+    }
+
+    #[test]
+    fn test_nested_field_path() {
+        let vec3 = vec3_info(LayoutScheme::C99);
+        let transform = ObjBuilder::new()
+            .with_name("Transform")
+            .with_nested("position", vec3.clone())
+            .with_nested("rotation", vec3)
+            .build();
+
+        let mut obj = Obj::new(Rc::new(transform));
+        obj.set_field_at(&[0, 0], 1.0_f32).unwrap();
+        obj.set_field_at(&[1, 2], 3.0_f32).unwrap();
+
+        assert_eq!(obj.field_at::<f32>(&[0, 0]), Ok(&1.0));
+        assert_eq!(obj.field_at::<f32>(&[0, 2]), Ok(&0.0));
+        assert_eq!(obj.field_at::<f32>(&[1, 2]), Ok(&3.0));
+        assert_eq!(
+            obj.field::<f32>(0),
+            Err(ObjError::TypeMismatch {
+                expected: FieldKind::Obj,
+                actual: FieldKind::F32
+            }),
+            "nested object is not a value"
+        );
+        assert_eq!(
+            obj.field_at::<f32>(&[0, 3]),
+            Err(ObjError::OutOfRange { index: 3, len: 3 })
+        );
+        assert_eq!(
+            obj.field_at::<f32>(&[0, 0, 0]),
+            Err(ObjError::TypeMismatch {
+                expected: FieldKind::F32,
+                actual: FieldKind::Obj
+            }),
+            "value has no fields"
+        );
+        assert_eq!(obj.field_at::<f32>(&[]), Err(ObjError::EmptyPath));
+    }
+
+    #[test]
+    fn test_ref_field() {
+        let vec3 = vec3_info(LayoutScheme::C99);
+        let node = ObjBuilder::new()
+            .with_name("Node")
+            .with_field("id", FieldKind::U32)
+            .with_ref("target")
+            .build();
+        assert_eq!(node.size(), 4, "references are not stored in the object's data");
+
+        let holder = Rc::new(
+            ObjBuilder::new()
+                .with_name("Holder")
+                .with_ref("first")
+                .with_nested("node", Rc::new(node))
+                .build(),
+        );
+
+        let target = Rc::new(RefCell::new(Obj::new(vec3)));
+        target.borrow_mut().set_field(1, 5.0_f32).unwrap();
+
+        let mut obj = Obj::new(holder);
+        assert!(obj.get_ref(0).unwrap().is_none());
+        assert!(obj.get_ref_at(&[1, 1]).unwrap().is_none());
+        assert_eq!(
+            obj.set_ref(1, None),
+            Err(ObjError::TypeMismatch {
+                expected: FieldKind::Obj,
+                actual: FieldKind::Ref
+            })
+        );
+
+        obj.set_ref_at(&[1, 1], Some(target.clone())).unwrap();
+        assert!(
+            obj.get_ref(0).unwrap().is_none(),
+            "nested references must have their own slot"
+        );
+
+        let found = obj.get_ref_at(&[1, 1]).unwrap().expect("reference must be set");
+        assert!(Rc::ptr_eq(found, &target));
+        assert_eq!(found.borrow().field::<f32>(1), Ok(&5.0));
+    }
+
+    #[test]
+    fn test_field_by_name() {
+        let ty = Rc::new(
+            ObjBuilder::new()
+                .with_name("Message")
+                .with_field("one", FieldKind::U8)
+                .with_field("two", FieldKind::U16)
+                .with_ref("three")
+                .build(),
+        );
+
+        assert_eq!(ty.field_index("two"), Some(1));
+        assert_eq!(ty.field_index("four"), None);
+        assert_eq!(
+            ty.fields().collect::<Vec<_>>(),
+            vec![
+                ("one", FieldKind::U8, 0),
+                ("two", FieldKind::U16, 2),
+                ("three", FieldKind::Ref, 4)
+            ]
+        );
+
+        let mut obj = Obj::new(ty);
+        *obj.field_by_name_mut::<u16>("two").unwrap() = 42;
+        assert_eq!(obj.field_by_name::<u16>("two"), Ok(&42));
+        assert_eq!(
+            obj.field_by_name::<u8>("two"),
+            Err(ObjError::TypeMismatch {
+                expected: FieldKind::U16,
+                actual: FieldKind::U8
+            })
+        );
+        assert_eq!(
+            obj.field_by_name::<u16>("four"),
+            Err(ObjError::UnknownField("four".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unnamed_fields() {
+        let ty = ObjInfo::new(LayoutScheme::C99, &[FieldKind::U8, FieldKind::U32]);
+        assert_eq!(ty.field_index("1"), Some(1));
+    }
+
+    #[test]
+    fn test_obj_format() {
+        let vec3 = vec3_info(LayoutScheme::C99);
+        let transform = ObjBuilder::new()
+            .with_name("Transform")
+            .with_nested("position", vec3)
+            .with_field("id", FieldKind::I32)
+            .with_ref("parent")
+            .build();
+
+        let mut obj = Obj::new(Rc::new(transform));
+        obj.set_field_at(&[0, 0], 1.5_f32).unwrap();
+        obj.set_field(1, -7_i32).unwrap();
+
+        assert_eq!(
+            obj.to_string(),
+            "Transform { position: Vec3 { x: 1.5, y: 0, z: 0 }, id: -7, parent: null }"
+        );
+        assert_eq!(
+            format!("{obj:?}"),
+            "Transform { position: Vec3 { x: 1.5, y: 0.0, z: 0.0 }, id: -7, parent: null }"
+        );
+    }
+
+    #[test]
+    fn test_misaligned_field() {
+        // Object storage is only guaranteed to be byte aligned,
+        // so casting field data must check the alignment.
+        let data = [0_u8; 8];
+        let start = data.as_ptr().align_offset(4) + 1;
+        assert_eq!(u32::from_slice(&data[start..start + 4]), None);
+    }
+
+    #[test]
+    fn test_error_message() {
+        let ty = Rc::new(ObjBuilder::new().with_field("id", FieldKind::I32).build());
+        let mut obj = Obj::new(ty);
+
+        let err = obj.set_field(0, 1.0_f64).unwrap_err();
+        assert_eq!(err.to_string(), "mismatched field kind: expected I32, found F64");
+        let err = obj.field::<i32>(2).unwrap_err();
+        assert_eq!(err.to_string(), "field index 2 out of range for object with 1 fields");
+    }
 }
@@ -0,0 +1,169 @@
+//! Runtime values.
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::closure::Closure;
+use crate::obj::Obj;
+
+/// Value stored in a fiber's operand stack.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Value {
+    /// Absence of a value, for example the result
+    /// of calling a function that returns nothing.
+    #[default]
+    Nil,
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Str(Rc<str>),
+    /// Object laid out by a struct type.
+    ///
+    /// Objects are shared, so copies of the value refer to the same object.
+    Obj(Rc<RefCell<Obj>>),
+    /// Object converted to an interface type.
+    ///
+    /// Method calls are dispatched through the vtable, which lists
+    /// the methods the object's type uses to satisfy the interface.
+    Iface {
+        obj: Rc<RefCell<Obj>>,
+        vtable: u32,
+    },
+    /// Function as a value, referring to it by ID.
+    Func(u32),
+    /// Function that captured variables of its enclosing functions.
+    Closure(Rc<Closure>),
+}
+
+impl Value {
+    #[inline]
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Value::Nil)
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Value::I32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Name of the value's type, for use in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "bool",
+            Value::I32(_) => "i32",
+            Value::I64(_) => "i64",
+            Value::F32(_) => "f32",
+            Value::F64(_) => "f64",
+            Value::Str(_) => "str",
+            Value::Obj(_) => "object",
+            Value::Iface { .. } => "interface",
+            Value::Func(_) | Value::Closure(_) => "func",
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::I32(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::I64(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::F32(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::F64(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Str(value.into())
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(value) => fmt::Display::fmt(value, f),
+            Value::I32(value) => fmt::Display::fmt(value, f),
+            Value::I64(value) => fmt::Display::fmt(value, f),
+            // Debug formatting keeps the fraction of whole numbers, like `1.0`.
+            Value::F32(value) => fmt::Debug::fmt(value, f),
+            Value::F64(value) => fmt::Debug::fmt(value, f),
+            Value::Str(value) => fmt::Display::fmt(value, f),
+            Value::Obj(obj) | Value::Iface { obj, .. } => fmt::Display::fmt(&*obj.borrow(), f),
+            Value::Func(func_id) => write!(f, "func#{func_id}"),
+            Value::Closure(closure) => write!(f, "func#{}", closure.func_id),
+        }
+    }
+}
+
+/// Conversion from a runtime value to a primitive Rust type.
+pub trait FromValue: Sized {
+    /// Name of the value's type, for use in error messages.
+    const TYPE_NAME: &'static str;
+
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+macro_rules! impl_from_value {
+    ($ty:ty, $variant:ident, $name:literal) => {
+        impl FromValue for $ty {
+            const TYPE_NAME: &'static str = $name;
+
+            #[inline]
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::$variant(value) => Some(*value),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_from_value!(bool, Bool, "bool");
+impl_from_value!(i32, I32, "i32");
+impl_from_value!(i64, I64, "i64");
+impl_from_value!(f32, F32, "f32");
+impl_from_value!(f64, F64, "f64");
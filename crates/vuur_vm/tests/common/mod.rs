@@ -0,0 +1,10 @@
+//! Support shared by the VM integration tests.
+use vuur_vm::value::Value;
+
+/// Parse, compile and run the script, returning the result of its main function.
+pub fn run_script(source: &str) -> Option<Value> {
+    let module = vuur_parse::parse_str(source).expect("parsing test script");
+    let chunk = vuur_compile::compile(&module).expect("compiling test script");
+    let mut vm = vuur_vm::VM::new();
+    vm.run(&chunk)
+}
@@ -2,9 +2,10 @@
 use vuur_compile::bytecode::{encode_a, encode_simple, opcodes::*};
 use vuur_compile::Chunk;
 use vuur_parse::expr::Expr;
+use vuur_vm::value::Value;
 
-type PROGRAM<'a> = &'a [u32];
-type RESULT = Option<u32>;
+type Program<'a> = &'a [u32];
+type Expected = Option<Value>;
 
 const TEST_PROGRAM: &str = r#"
 func Main() -> int {
@@ -13,7 +14,7 @@ func Main() -> int {
 "#;
 
 fn create_program(bytecode: &[u32]) -> Chunk {
-    let bytecode_expr = Expr::Bytecode(bytecode.iter().copied().collect());
+    let bytecode_expr = Expr::Bytecode(bytecode.to_vec());
 
     let mut module = vuur_parse::parse_str(TEST_PROGRAM).expect("parsing test program");
 
@@ -26,7 +27,7 @@ fn create_program(bytecode: &[u32]) -> Chunk {
 
 #[test]
 fn test_arithmetic() {
-    let cases: &[(PROGRAM, RESULT)] = &[
+    let cases: &[(Program, Expected)] = &[
         (
             &[
                 // 1 + 2 * 3
@@ -36,7 +37,7 @@ fn test_arithmetic() {
                 encode_simple(MUL_I32),
                 encode_simple(ADD_I32),
             ],
-            Some(7),
+            Some(Value::I32(7)),
         ),
         (
             &[
@@ -47,7 +48,7 @@ fn test_arithmetic() {
                 encode_a(PUSH_CONST_IMM, 3),
                 encode_simple(MUL_I32),
             ],
-            Some(9),
+            Some(Value::I32(9)),
         ),
         (
             &[
@@ -57,7 +58,7 @@ fn test_arithmetic() {
                 encode_a(PUSH_CONST_IMM, 6),
                 encode_simple(ADD_I32),
             ],
-            Some(2),
+            Some(Value::I32(2)),
         ),
         (
             &[
@@ -67,7 +68,7 @@ fn test_arithmetic() {
                 encode_simple(NEG_I32),
                 encode_simple(ADD_I32),
             ],
-            Some(2),
+            Some(Value::I32(2)),
         ),
         (
             &[
@@ -78,7 +79,7 @@ fn test_arithmetic() {
                 encode_a(PUSH_CONST_IMM, 4),
                 encode_simple(DIV_I32),
             ],
-            Some(6),
+            Some(Value::I32(6)),
         ),
     ];
 
@@ -98,7 +99,7 @@ fn test_arithmetic() {
 // TODO: Retrieve error from VM
 #[test]
 fn test_arithmetic_error() {
-    let cases: &[(PROGRAM, RESULT)] = &[(
+    let cases: &[(Program, Expected)] = &[(
         // divide by zero
         &[
             encode_a(PUSH_CONST_IMM, 42),
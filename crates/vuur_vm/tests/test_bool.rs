@@ -0,0 +1,107 @@
+//! Tests for boolean values.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_bool_comparisons() {
+    let cases: &[(&str, bool)] = &[
+        ("func Main() -> bool { return true }", true),
+        ("func Main() -> bool { return !true }", false),
+        ("func Main() -> bool { return 1 < 2 }", true),
+        ("func Main() -> bool { return 2 <= 1 }", false),
+        ("func Main() -> bool { return 3i64 > 2i64 }", true),
+        ("func Main() -> bool { return 1.5 >= 1.5 }", true),
+        ("func Main() -> bool { return 0.5f64 != 0.5f64 }", false),
+        ("func Main() -> bool { return 1 == 2 == false }", true),
+    ];
+
+    for (index, (source, expected)) in cases.iter().enumerate() {
+        assert_eq!(
+            run_script(source),
+            Some(Value::Bool(*expected)),
+            "unexpected result from bool case-{index}"
+        );
+    }
+}
+
+#[test]
+fn test_bool_condition() {
+    let source = r#"
+func Max(a: i32, b: i32) -> i32 {
+    var greater = a > b
+    if greater {
+        return a
+    }
+    return b
+}
+
+func Main() -> str {
+    var done = false
+    if !done {
+        return "max %(Max(3, 7))"
+    }
+    return "done"
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::from("max 7")));
+}
+
+#[test]
+fn test_if_else() {
+    let source = r#"
+func Sign(n: i32) -> i32 {
+    if n < 0 {
+        var negative = true
+    } else {
+        return 1
+    }
+    return -1
+}
+
+func Main() -> i32 {
+    return Sign(-5) * 10 + Sign(5)
+}
+"#;
+
+    // Sign(-5) takes the true block, which must not fall through into the else block.
+    assert_eq!(run_script(source), Some(Value::I32(-9)));
+}
+
+#[test]
+fn test_if_else_if() {
+    let source = r#"
+func Grade(n: i32) -> i32 {
+    if n >= 90 {
+        return 1
+    } else if n >= 50 {
+        var passed = true
+    } else {
+        return 3
+    }
+    return 2
+}
+
+func Main() -> i32 {
+    return Grade(95) * 100 + Grade(70) * 10 + Grade(10)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(123)));
+}
+
+#[test]
+fn test_bool_disassemble() {
+    let module = vuur_parse::parse_str("func Main() -> bool { return false }").expect("parsing test script");
+    let chunk = vuur_compile::compile(&module).expect("compiling test script");
+
+    let mut buf = String::new();
+    vuur_compile::disassemble(&mut buf, &chunk).expect("disassembling test script");
+    assert!(
+        buf.contains("pushk.bool\t0\t; false"),
+        "bool constant not found in:\n{buf}"
+    );
+}
@@ -0,0 +1,188 @@
+//! Tests for closures, which capture variables of their enclosing functions.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_capture_local() {
+    let source = r#"
+func Main() -> i32 {
+    var x = 40
+    var add = func(n: i32) -> i32 {
+        return x + n
+    }
+    return add(2)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(42)));
+}
+
+#[test]
+fn test_counter() {
+    let source = r#"
+func Counter() -> func() -> i32 {
+    var count = 0
+    return func() -> i32 {
+        count = count + 1
+        return count
+    }
+}
+
+func Main() -> i32 {
+    var a = Counter()
+    var b = Counter()
+    a()
+    a()
+    b()
+    return a() * 10 + b()
+}
+"#;
+
+    // Each call to the outer function captures a fresh variable.
+    assert_eq!(run_script(source), Some(Value::I32(32)));
+}
+
+#[test]
+fn test_outlives_frame() {
+    let source = r#"
+func Adder(n: i32) -> func(i32) -> i32 {
+    return func(x: i32) -> i32 {
+        return x + n
+    }
+}
+
+func Main() -> i32 {
+    var add2 = Adder(2)
+    var add5 = Adder(5)
+
+    // Reuse the stack slots of the returned frames.
+    var a = 100
+    var b = 200
+    return add2(10) + add5(20)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(37)));
+}
+
+#[test]
+fn test_shared_upvalue() {
+    let source = r#"
+func Main() -> i32 {
+    var total = 1
+    var add = func(n: i32) {
+        total = total + n
+    }
+    var get = func() -> i32 {
+        return total
+    }
+    add(2)
+    total = total * 10
+    add(4)
+    return get()
+}
+"#;
+
+    // Closures and the enclosing function see each other's assignments.
+    assert_eq!(run_script(source), Some(Value::I32(34)));
+}
+
+#[test]
+fn test_nested_capture() {
+    let source = r#"
+func Outer() -> func() -> func() -> i32 {
+    var x = 7
+    return func() -> func() -> i32 {
+        return func() -> i32 {
+            x = x * 2
+            return x
+        }
+    }
+}
+
+func Main() -> i32 {
+    var middle = Outer()
+    var inner = middle()
+    inner()
+    return inner()
+}
+"#;
+
+    // The middle function captures the variable for the inner one.
+    assert_eq!(run_script(source), Some(Value::I32(28)));
+}
+
+#[test]
+fn test_nested_func() {
+    let source = r#"
+func Fib(n: i32) -> i32 {
+    var memo = 0
+    func inner(k: i32) -> i32 {
+        memo = memo + 1
+        if k < 2 {
+            return k
+        }
+        return inner(k - 1) + inner(k - 2)
+    }
+    return inner(n) * 1000 + memo
+}
+
+func Main() -> i32 {
+    return Fib(6)
+}
+"#;
+
+    // Nested functions can call themselves, and read their parent's locals.
+    assert_eq!(run_script(source), Some(Value::I32(8025)));
+}
+
+#[test]
+fn test_loop_capture() {
+    let source = r#"
+func Apply(f: func() -> i32) -> i32 {
+    return f()
+}
+
+func Main() -> i32 {
+    var first = func() -> i32 { return 0 }
+    var last = func() -> i32 { return 0 }
+    for i in 1..3 {
+        var sq = i * i
+        if i == 1 {
+            first = func() -> i32 { return sq + i }
+        }
+        last = func() -> i32 { return sq + i }
+    }
+    return Apply(first) * 100 + Apply(last)
+}
+"#;
+
+    // Every iteration has its own variables to capture.
+    assert_eq!(run_script(source), Some(Value::I32(212)));
+}
+
+#[test]
+fn test_break_capture() {
+    let source = r#"
+func Main() -> i32 {
+    var f = func() -> i32 { return 0 }
+    var i = 0
+    while true {
+        var n = i
+        f = func() -> i32 { return n }
+        if i == 3 {
+            break
+        }
+        i = i + 1
+    }
+    i = 10
+    var m = 99
+    return f()
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(3)));
+}
@@ -0,0 +1,102 @@
+//! Tests for functions used as values.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_anonymous_func() {
+    let source = r#"
+func Main() -> i32 {
+    var double = func(x: i32) -> i32 {
+        return x * 2
+    }
+    return double(21)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(42)));
+}
+
+#[test]
+fn test_named_func_value() {
+    let source = r#"
+func Add(a: i32, b: i32) -> i32 {
+    return a + b
+}
+
+func Sub(a: i32, b: i32) -> i32 {
+    return a - b
+}
+
+func Main() -> i32 {
+    var op: func(i32, i32) -> i32 = Add
+    var sum = op(7, 3)
+    op = Sub
+    return sum * op(7, 3)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(40)));
+}
+
+#[test]
+fn test_func_arg() {
+    let source = r#"
+func Apply(f: func(i32) -> i32, x: i32) -> i32 {
+    return f(f(x))
+}
+
+func Main() -> i32 {
+    return Apply(func(x: i32) -> i32 { return x + 3 }, 1)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(7)));
+}
+
+#[test]
+fn test_func_return() {
+    let source = r#"
+func Square(x: i32) -> i32 {
+    return x * x
+}
+
+func Pick() -> func(i32) -> i32 {
+    return Square
+}
+
+func Main() -> i32 {
+    return Pick()(5)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(25)));
+}
+
+#[test]
+fn test_func_after_nested() {
+    let source = r#"
+func Main() -> i32 {
+    var abs = func(x: i32) -> i32 {
+        if x < 0 {
+            return -x
+        }
+        return x
+    }
+
+    // Jumps after the nested function must
+    // account for it being emitted first.
+    var total = 0
+    var i = -3
+    while i < 3 {
+        total = total + abs(i)
+        i = i + 1
+    }
+    return total
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(9)));
+}
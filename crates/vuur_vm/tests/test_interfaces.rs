@@ -0,0 +1,113 @@
+//! Tests for interface types, which dispatch method calls at runtime.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+const SHAPES: &str = r#"
+type Shape = interface {
+    Area() -> f32
+    Scale(s: f32)
+}
+
+type Rect = struct {
+    w: f32,
+    h: f32,
+}
+
+type Circle = struct { r: f32 }
+
+func (rect: *Rect) Area() -> f32 {
+    return rect.w * rect.h
+}
+
+func (rect: *Rect) Scale(s: f32) {
+    rect.w = rect.w * s
+    rect.h = rect.h * s
+}
+
+func (c: *Circle) Area() -> f32 {
+    return 3.0 * c.r * c.r
+}
+
+func (c: *Circle) Scale(s: f32) {
+    c.r = c.r * s
+}
+
+func AreaOf(shape: Shape) -> f32 {
+    return shape.Area()
+}
+"#;
+
+#[test]
+fn test_interface_call() {
+    let source = format!(
+        "{SHAPES}{}",
+        r#"
+func Main() -> f32 {
+    var a: Shape = Rect { w: 2.0, h: 3.0 }
+    var b: Shape = Circle { r: 1.0 }
+    return a.Area() + b.Area()
+}
+"#
+    );
+
+    assert_eq!(run_script(&source), Some(Value::F32(9.0)));
+}
+
+#[test]
+fn test_interface_arg() {
+    let source = format!(
+        "{SHAPES}{}",
+        r#"
+func Main() -> f32 {
+    var c = Circle { r: 2.0 }
+    return AreaOf(Rect { w: 4.0, h: 0.5 }) + AreaOf(c)
+}
+"#
+    );
+
+    assert_eq!(run_script(&source), Some(Value::F32(14.0)));
+}
+
+#[test]
+fn test_interface_shares_object() {
+    let source = format!(
+        "{SHAPES}{}",
+        r#"
+func Main() -> f32 {
+    var rect = Rect { w: 1.0, h: 2.0 }
+    var shape: Shape = rect
+    shape.Scale(3.0)
+    return rect.w + rect.h
+}
+"#
+    );
+
+    // Converting to an interface doesn't copy the object.
+    assert_eq!(run_script(&source), Some(Value::F32(9.0)));
+}
+
+#[test]
+fn test_interface_reassign() {
+    let source = format!(
+        "{SHAPES}{}",
+        r#"
+func Largest(a: Shape, b: Shape) -> Shape {
+    if a.Area() < b.Area() {
+        return b
+    }
+    return a
+}
+
+func Main() -> f32 {
+    var shape: Shape = Circle { r: 1.0 }
+    shape = Largest(shape, Rect { w: 2.0, h: 2.0 })
+    return shape.Area()
+}
+"#
+    );
+
+    assert_eq!(run_script(&source), Some(Value::F32(4.0)));
+}
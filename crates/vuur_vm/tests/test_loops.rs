@@ -0,0 +1,169 @@
+//! Tests for loops.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_while_loop() {
+    let source = r#"
+func Main() -> i32 {
+    var total = 0
+    var i = 0
+    while i < 10 {
+        i = i + 1
+        total = total + i
+    }
+    return total
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(55)));
+}
+
+#[test]
+fn test_nested_loops() {
+    let source = r#"
+func Main() -> i32 {
+    var total = 0
+    var i = 0
+    while i < 5 {
+        i = i + 1
+        if i == 2 {
+            continue
+        }
+
+        var j = 0
+        while true {
+            j = j + 1
+            if j > i {
+                break
+            }
+            total = total + j
+        }
+    }
+    return total
+}
+"#;
+
+    // 1 + (1 + 2 + 3) + (1 + 2 + 3 + 4) + (1 + 2 + 3 + 4 + 5)
+    assert_eq!(run_script(source), Some(Value::I32(32)));
+}
+
+#[test]
+fn test_loop_recursive() {
+    let source = r#"
+func SumTo(n: i32) -> i32 {
+    if n == 0 {
+        return 0
+    }
+
+    var total = 0
+    var i = 0
+    while i < n {
+        i = i + 1
+        total = total + i
+        if i == n {
+            total = total + SumTo(n - 1)
+            break
+        }
+    }
+    return total
+}
+
+func Main() -> i32 {
+    return SumTo(4)
+}
+"#;
+
+    // 10 + 6 + 3 + 1
+    assert_eq!(run_script(source), Some(Value::I32(20)));
+}
+
+#[test]
+fn test_loop_return() {
+    let source = r#"
+func FirstSquareOver(limit: i32) -> i32 {
+    var n = 0
+    while true {
+        n = n + 1
+        if n * n > limit {
+            return n
+        }
+    }
+    return 0
+}
+
+func Main() -> i32 {
+    return FirstSquareOver(50)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(8)));
+}
+
+#[test]
+fn test_for_range() {
+    let cases: &[(&str, i32)] = &[
+        ("func Main() -> i32 {\n    var t = 0\n    for i in 0...10 {\n        t = t + i\n    }\n    return t\n}", 45),
+        ("func Main() -> i32 {\n    var t = 0\n    for i in 1..10 {\n        t = t + i\n    }\n    return t\n}", 55),
+        ("func Main() -> i32 {\n    var t = 0\n    for i in -2..2 {\n        t = t + i\n    }\n    return t\n}", 0),
+        // Empty ranges never run the body.
+        ("func Main() -> i32 {\n    var t = 0\n    for i in 5...5 {\n        t = t + 1\n    }\n    return t\n}", 0),
+        ("func Main() -> i32 {\n    var t = 0\n    for i in 5..4 {\n        t = t + 1\n    }\n    return t\n}", 0),
+        // Upper bound of the type doesn't overflow the counter.
+        ("func Main() -> i32 {\n    var t = 0\n    for i in 2147483646..2147483647 {\n        t = t + 1\n    }\n    return t\n}", 2),
+    ];
+
+    for (index, (source, expected)) in cases.iter().enumerate() {
+        assert_eq!(
+            run_script(source),
+            Some(Value::I32(*expected)),
+            "unexpected result from range case-{index}"
+        );
+    }
+}
+
+#[test]
+fn test_for_nested() {
+    let source = r#"
+func Main() -> i32 {
+    var count = 0
+    for i in 0...10 {
+        if i == 3 {
+            continue
+        }
+        if i == 6 {
+            break
+        }
+
+        for j in 0..i {
+            var x = 1
+            while true {
+                count = count + x
+                break
+            }
+        }
+    }
+    return count
+}
+"#;
+
+    // i = 0, 1, 2, 4, 5 with i + 1 inner iterations each.
+    assert_eq!(run_script(source), Some(Value::I32(17)));
+}
+
+#[test]
+fn test_for_range_bytecode() {
+    let source = "func Main() {\n    for i in 0...3 {\n    }\n}";
+    let module = vuur_parse::parse_str(source).expect("parsing test script");
+    let chunk = vuur_compile::compile(&module).expect("compiling test script");
+
+    let mut buf = String::new();
+    vuur_compile::disassemble(&mut buf, &chunk).expect("disassembling test script");
+
+    // Range is iterated by a counter in a local slot, not by an object.
+    assert!(buf.contains("range.next.i32"), "range instruction not found in:\n{buf}");
+    assert!(!buf.contains("call"), "unexpected call in:\n{buf}");
+}
@@ -0,0 +1,108 @@
+//! Tests for methods declared on structure types.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_method_call() {
+    let source = r#"
+type Vec = struct {
+    x: float,
+    y: float,
+    z: float,
+}
+
+func (vec: *Vec) MagnitudeSqr() -> float {
+    return vec.x * vec.x + vec.y * vec.y + vec.z * vec.z
+}
+
+func Main() -> float {
+    var v = Vec { x: 1.0, y: 2.0, z: 3.0 }
+    return v.MagnitudeSqr()
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::F32(14.0)));
+}
+
+#[test]
+fn test_method_self_receiver() {
+    let source = r#"
+type Counter = struct { count: i32 }
+
+func (*Counter) Add(n: i32) -> i32 {
+    self.count = self.count + n
+    return self.count
+}
+
+func (*Counter) Get() -> i32 {
+    return self.count
+}
+
+func Main() -> i32 {
+    var c = Counter { count: 1 }
+    c.Add(10)
+    c.Add(c.Get())
+    return c.Get()
+}
+"#;
+
+    // Objects are shared, so the receiver is changed in place.
+    assert_eq!(run_script(source), Some(Value::I32(22)));
+}
+
+#[test]
+fn test_method_calls_method() {
+    let source = r#"
+type Rect = struct {
+    w: i32
+    h: i32
+}
+
+func (r: *Rect) Area() -> i32 {
+    return r.w * r.h
+}
+
+func (r: *Rect) Scaled(n: i32) -> Rect {
+    return Rect { w: r.w * n, h: r.h * n }
+}
+
+func (r: *Rect) ScaledArea(n: i32) -> i32 {
+    var scaled = r.Scaled(n)
+    return scaled.Area()
+}
+
+func Main() -> i32 {
+    var r = Rect { w: 2, h: 3 }
+    return r.ScaledArea(2) + r.Area()
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(30)));
+}
+
+#[test]
+fn test_method_and_func_names() {
+    let source = r#"
+type Box = struct { size: i32 }
+
+func Size() -> i32 {
+    return 100
+}
+
+func (b: *Box) Size() -> i32 {
+    return b.size
+}
+
+func Main() -> i32 {
+    var b = Box { size: 7 }
+    return b.Size() + Size()
+}
+"#;
+
+    // Methods are looked up on the type, so they
+    // don't conflict with functions of the same name.
+    assert_eq!(run_script(source), Some(Value::I32(107)));
+}
@@ -0,0 +1,84 @@
+//! Tests for typed number values.
+mod common;
+
+use vuur_lexer::span::SourceFile;
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_number_literals() {
+    let cases: &[(&str, Value)] = &[
+        ("func Main() -> i32 { return 0xFF + 0b1010 - 0o7 }", Value::I32(258)),
+        ("func Main() -> i32 { return 100_000_000 + 1 }", Value::I32(100_000_001)),
+        (
+            "func Main() -> i64 { return 3_000_000_000i64 * 2i64 }",
+            Value::I64(6_000_000_000),
+        ),
+        ("func Main() -> f32 { return 1.5 * 2.0 - 0.5 }", Value::F32(2.5)),
+        ("func Main() -> f64 { return -(0.5f64 + 2.5e-1f64) }", Value::F64(-0.75)),
+        ("func Main() -> bool { return 2.5e1 == 25.0 }", Value::Bool(true)),
+        ("func Main() -> str { return \"%(1.0) %(2i64)\" }", Value::from("1.0 2")),
+        // Literals without a suffix take the type the context expects.
+        ("func Main() -> f64 { return 1.5e-3 }", Value::F64(1.5e-3)),
+        (
+            "func F(x: i64) -> i64 { return x * 2i64 }\nfunc Main() -> i64 { return F(3_000_000) }",
+            Value::I64(6_000_000),
+        ),
+        // Minus is part of the literal, so the most negative values fit.
+        ("func Main() -> i32 { return -2147483648 }", Value::I32(i32::MIN)),
+        (
+            "func Main() -> i64 { return -9223372036854775808i64 }",
+            Value::I64(i64::MIN),
+        ),
+        ("func Main() -> i32 { return 1 - -2 * 3 }", Value::I32(7)),
+        // Repeated constants after a wide constant share its slots.
+        ("func Main() -> i64 { return 1i64 + 2i64 + 2i64 + 1i64 }", Value::I64(6)),
+    ];
+
+    for (index, (source, expected)) in cases.iter().enumerate() {
+        assert_eq!(
+            run_script(source).as_ref(),
+            Some(expected),
+            "unexpected result from number case-{index}"
+        );
+    }
+}
+
+#[test]
+fn test_number_args() {
+    let source = r#"
+func Half(x: f64) -> f64 {
+    return x / 2.0f64
+}
+
+func Main() -> f64 {
+    var x = 5.0f64
+    return Half(x)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::F64(2.5)));
+}
+
+#[test]
+fn test_number_divide_by_zero() {
+    let source = r#"
+func Main() -> i64 {
+    return 1i64 / 0i64
+}
+"#;
+
+    let module = vuur_parse::parse_str(source).expect("parsing test script");
+    let chunk = vuur_compile::compile(&module).expect("compiling test script");
+    let mut vm = vuur_vm::VM::new();
+    assert_eq!(vm.run(&chunk), None);
+
+    let fiber = vm.fiber();
+    assert_eq!(fiber.error(), Some("divide by zero"));
+
+    // Error points at the division operator.
+    let span = fiber.error_addr().and_then(|addr| chunk.source_span(addr)).unwrap();
+    let file = SourceFile::new("test.vu", source);
+    assert_eq!(file.location(span.start).to_string(), "test.vu:3:17");
+}
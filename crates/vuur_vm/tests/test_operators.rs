@@ -0,0 +1,99 @@
+//! Tests for operators overloaded by methods on structure types.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_arithmetic_operator() {
+    let source = r#"
+type Vec = struct {
+    x: f32,
+    y: f32,
+}
+
+func (a: *Vec) +(b: Vec) -> Vec {
+    return Vec { x: a.x + b.x, y: a.y + b.y }
+}
+
+func (a: *Vec) *(s: f32) -> Vec {
+    return Vec { x: a.x * s, y: a.y * s }
+}
+
+func Main() -> f32 {
+    var a = Vec { x: 1.0, y: 2.0 }
+    var b = Vec { x: 3.0, y: 4.0 }
+    var c = a + b * 2.0
+    return c.x + c.y
+}
+"#;
+
+    // Precedence is the same as for primitives: a + (b * 2)
+    assert_eq!(run_script(source), Some(Value::F32(17.0)));
+}
+
+#[test]
+fn test_comparison_operator() {
+    let source = r#"
+type Money = struct { cents: i32 }
+
+func (a: *Money) ==(b: Money) -> bool {
+    return a.cents == b.cents
+}
+
+func (a: *Money) <(b: Money) -> bool {
+    return a.cents < b.cents
+}
+
+func Main() -> i32 {
+    var a = Money { cents: 100 }
+    var b = Money { cents: 250 }
+    var result = 0
+    if a == b {
+        result = result + 1
+    }
+    if a < b {
+        result = result + 10
+    }
+    return result
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(10)));
+}
+
+#[test]
+fn test_index_operator() {
+    let source = r#"
+type Pair = struct {
+    first: i32,
+    second: i32,
+}
+
+func (p: *Pair) [](index: i32) -> i32 {
+    if index == 0 {
+        return p.first
+    }
+    return p.second
+}
+
+func (p: *Pair) []=(index: i32, value: i32) -> i32 {
+    if index == 0 {
+        p.first = value
+        return value
+    }
+    p.second = value
+    return value
+}
+
+func Main() -> i32 {
+    var p = Pair { first: 1, second: 2 }
+    p[1] = 40
+    var two = p[0] = 2
+    return p[0] + p[1] + two
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(44)));
+}
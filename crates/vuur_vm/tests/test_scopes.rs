@@ -0,0 +1,65 @@
+//! Tests for lexical scopes of local variables.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_shadow_local() {
+    let source = r#"
+func Main() -> i32 {
+    var x = 1
+    var total = 0
+    if true {
+        var x = x + 10
+        total = total + x
+    }
+    total = total + x
+    return total
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(12)));
+}
+
+#[test]
+fn test_shadow_argument() {
+    let source = r#"
+func Twice(n: i32) -> str {
+    var n = "%(n * 2)"
+    return n
+}
+
+func Main() -> str {
+    return Twice(4)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::from("8")));
+}
+
+#[test]
+fn test_reuse_slots() {
+    let source = r#"
+func Main() -> i32 {
+    var total = 0
+    for i in 0...3 {
+        var a = i
+        total = total + a
+    }
+    for i in 0...3 {
+        var b = i * 10
+        total = total + b
+    }
+    if true {
+        var c = 100
+        total = total + c
+    }
+    var d = 1000
+    return total + d
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::I32(1133)));
+}
@@ -0,0 +1,63 @@
+//! Tests for string values.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_string_literal() {
+    let source = r#"
+func Main() -> str {
+    return "hello,\tworld \u{1F525}"
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::from("hello,\tworld 🔥")));
+}
+
+#[test]
+fn test_string_local() {
+    let source = r#"
+func Greeting(name: str) -> str {
+    var unused = "unused"
+    return name
+}
+
+func Main() -> str {
+    var greeting = "hello"
+    var other = "hello"
+    return Greeting(other)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::from("hello")));
+}
+
+#[test]
+fn test_string_interpolation() {
+    let source = r#"
+func Greet(name: str, count: int) -> str {
+    return "hello %(name), you have %(count * 2) items"
+}
+
+func Main() -> str {
+    return Greet("world", 21)
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::from("hello world, you have 42 items")));
+}
+
+#[test]
+fn test_string_interpolation_nested() {
+    let source = r#"
+func Main() -> str {
+    var a = 1
+    var b = "b"
+    return "%(a)%("(%(b))") 100\%(a)"
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::from("1(b) 100%(a)")));
+}
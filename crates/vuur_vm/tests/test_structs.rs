@@ -0,0 +1,185 @@
+//! Tests for structure types.
+mod common;
+
+use vuur_vm::obj::LayoutScheme;
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+/// Struct declaration from the `func3.vu` parser fixture.
+const VEC_SOURCE: &str = r#"
+type Vec = struct {
+    x: float,
+    y: float,
+    z: float,
+}
+
+func Main() {
+}
+"#;
+
+#[test]
+fn test_struct_type_layout() {
+    let module = vuur_parse::parse_str(VEC_SOURCE).expect("parsing test script");
+    let chunk = vuur_compile::compile(&module).expect("compiling test script");
+
+    let type_def = &chunk.types()[0];
+    assert_eq!(type_def.name, "Vec");
+    assert_eq!(type_def.fields.len(), 3);
+
+    let mut vm = vuur_vm::VM::new();
+    vm.run(&chunk);
+
+    let obj_info = vm.obj_info(type_def.id).expect("object layout built at load time");
+    assert_eq!(obj_info.name(), "Vec");
+    assert_eq!(obj_info.size(), 12);
+    assert_eq!(obj_info.layout_scheme(), LayoutScheme::C99);
+}
+
+#[test]
+fn test_struct_type_padding() {
+    let source = r#"
+type Particle = struct {
+    alive: bool
+    position: f64
+    id: i32
+}
+
+func Main() {
+}
+"#;
+
+    let module = vuur_parse::parse_str(source).expect("parsing test script");
+    let chunk = vuur_compile::compile(&module).expect("compiling test script");
+
+    let mut vm = vuur_vm::VM::new();
+    vm.run(&chunk);
+
+    // Fields keep their declaration order, so the bool is padded
+    // up to the alignment of the f64.
+    let obj_info = vm.obj_info(chunk.types()[0].id).unwrap();
+    assert_eq!(obj_info.size(), 24);
+}
+
+#[test]
+fn test_struct_type_in_func_sig() {
+    let source = r#"
+type Vec = struct { x: f32, y: f32 }
+
+func Zero(v: Vec) -> Vec {
+    return v
+}
+
+func Main() -> i32 {
+    return 0
+}
+"#;
+
+    let module = vuur_parse::parse_str(source).expect("parsing test script");
+    vuur_compile::compile(&module).expect("struct types can be used in function signatures");
+}
+
+#[test]
+fn test_struct_literal() {
+    let source = r#"
+type Vec = struct {
+    x: float,
+    y: float,
+    z: float,
+}
+
+func Add(lhs: Vec, rhs: Vec) -> Vec {
+    return Vec {
+        x: lhs.x + rhs.x,
+        y: lhs.y + rhs.y,
+        z: lhs.z + rhs.z,
+    }
+}
+
+func Main() -> float {
+    var a = Vec { x: 1.0, y: 2.0, z: 3.0 }
+    var b = Add(a, Vec { x: 10.0, y: 20.0, z: 30.0 })
+    return b.x + b.y + b.z
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::F32(66.0)));
+}
+
+#[test]
+fn test_struct_field_kinds() {
+    let source = r#"
+type Particle = struct {
+    alive: bool
+    id: i32
+    age: i64
+    mass: f64
+}
+
+func Main() -> f64 {
+    var p = Particle { id: 7, mass: 1.5f64 }
+    if p.alive {
+        return 1.0f64
+    }
+    if p.age != 0i64 {
+        return 2.0f64
+    }
+    p.alive = true
+    p.age = 9000000000i64
+    if p.alive == (p.age == 9000000000i64) {
+        return p.mass
+    }
+    return 0.0f64
+}
+"#;
+
+    // Fields without a value in the literal are zeroed.
+    assert_eq!(run_script(source), Some(Value::F64(1.5)));
+}
+
+#[test]
+fn test_struct_field_assign() {
+    let source = r#"
+type Counter = struct { count: i32 }
+
+func Bump(c: Counter) {
+    c.count = c.count + 1
+}
+
+func Main() -> i32 {
+    var c: Counter
+    var d = c
+    for i in 0...3 {
+        Bump(c)
+    }
+    return (d.count = d.count * 10) + 1
+}
+"#;
+
+    // Objects are shared, so changes made through
+    // any copy of the value are visible to all.
+    assert_eq!(run_script(source), Some(Value::I32(31)));
+}
+
+#[test]
+fn test_struct_display() {
+    let source = r#"
+type Particle = struct {
+    alive: bool
+    id: i32
+    mass: f64
+}
+
+func Main() -> Particle {
+    return Particle { id: 7, mass: 1.5f64 }
+}
+"#;
+
+    let value = run_script(source).expect("script must return a value");
+    assert_eq!(value.to_string(), "Particle { alive: false, id: 7, mass: 1.5 }");
+
+    let Value::Obj(obj) = value else {
+        panic!("expected object, found {}", value.type_name());
+    };
+    assert_eq!(obj.borrow().field_by_name::<i32>("id"), Ok(&7));
+}
@@ -0,0 +1,96 @@
+//! Tests for local variable declarations.
+mod common;
+
+use vuur_vm::value::Value;
+
+use common::run_script;
+
+#[test]
+fn test_var_type_annotation() {
+    let source = r#"
+func Main() -> f64 {
+    var x: f64 = 1.5f64
+    var y : f64 = x * 2.0f64
+    return y
+}
+"#;
+
+    assert_eq!(run_script(source), Some(Value::F64(3.0)));
+}
+
+#[test]
+fn test_var_literal_annotation() {
+    let cases: &[(&str, Value)] = &[
+        (
+            "func Main() -> i64 {\n    var x: i64 = 5\n    return x\n}",
+            Value::I64(5),
+        ),
+        (
+            "func Main() -> i64 {\n    var x: i64 = -5\n    return x\n}",
+            Value::I64(-5),
+        ),
+        (
+            "func Main() -> f32 {\n    var x: f32 = 1.5\n    return x\n}",
+            Value::F32(1.5),
+        ),
+        (
+            "func Main() -> f64 {\n    var x: f64 = 1.5\n    return x\n}",
+            Value::F64(1.5),
+        ),
+        // The literal is converted to the declared type
+        // from its text, without losing precision.
+        (
+            "func Main() -> f64 {\n    var x: f64 = 0.1\n    return x\n}",
+            Value::F64(0.1),
+        ),
+    ];
+
+    for (index, (source, expected)) in cases.iter().enumerate() {
+        assert_eq!(
+            run_script(source).as_ref(),
+            Some(expected),
+            "unexpected result from annotation case-{index}"
+        );
+    }
+}
+
+#[test]
+fn test_var_zero_values() {
+    let cases: &[(&str, Value)] = &[
+        ("func Main() -> i32 {\n    var x: i32\n    return x\n}", Value::I32(0)),
+        ("func Main() -> i64 {\n    var x: i64\n    return x\n}", Value::I64(0)),
+        ("func Main() -> f32 {\n    var x: f32\n    return x\n}", Value::F32(0.0)),
+        ("func Main() -> f64 {\n    var x: f64\n    return x\n}", Value::F64(0.0)),
+        (
+            "func Main() -> bool {\n    var x: bool\n    return x\n}",
+            Value::Bool(false),
+        ),
+        ("func Main() -> str {\n    var x: str\n    return x\n}", Value::from("")),
+    ];
+
+    for (index, (source, expected)) in cases.iter().enumerate() {
+        assert_eq!(
+            run_script(source).as_ref(),
+            Some(expected),
+            "unexpected result from zero value case-{index}"
+        );
+    }
+}
+
+#[test]
+fn test_var_zero_value_in_loop() {
+    let source = r#"
+func Main() -> i32 {
+    var total: i32
+    for i in 0...3 {
+        var count: i32
+        count = count + 1
+        total = total + count
+    }
+    return total
+}
+"#;
+
+    // The zero value is assigned on every iteration.
+    assert_eq!(run_script(source), Some(Value::I32(3)));
+}